@@ -223,7 +223,7 @@ async fn create_team(libsql: &'static LibSQL, db: Connection) -> Result<()> {
         .copied()
         .collect::<Vec<_>>();
 
-    let Some((user_id, team_id)) = db
+    let Some((user_id, team_id, _)) = db
         .upsert_user_by_credentials(
             &dummy_user.username,
             &dummy_user.avatar,
@@ -241,7 +241,7 @@ async fn create_team(libsql: &'static LibSQL, db: Connection) -> Result<()> {
 
     for _ in 0..num_members {
         let dummy_user = create_dummy_user();
-        let Some((user_id, _)) = db
+        let Some((user_id, _, _)) = db
             .upsert_user_by_credentials(
                 &dummy_user.username,
                 &dummy_user.avatar,
@@ -646,7 +646,10 @@ const ChallengesComponent = ({
                             </button>
                           </div>
                         </div>
-                        <div>{challenge.description}</div>
+                        <div
+                          class="max-w-full prose dark:prose-invert"
+                          innerHTML={challenge.description}
+                        />
                       </li>
                     );
                   }}
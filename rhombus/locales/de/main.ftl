@@ -3,6 +3,11 @@ scoreboard = Anzeigetafel
 team = Team
 account = Konto
 challenges = Challenges
+admin = Admin
+announcements = Ankündigungen
+announcements-empty = Noch keine Ankündigungen
+live-teammate-solve = {$name} hat {$challenge} gelöst
+live-first-blood = {$team} hat First Blood auf {$challenge}
 
 account-description = Verwalten Sie Ihre individuellen Kontoeinstellungen. Sehen Sie sich
     {$link_start}öffentliches Profil{$link_end} an.
@@ -24,15 +29,35 @@ team-error-name-taken = Teamname bereits vergeben
 account-check-email = Überprüfen Sie Ihre E-Mail auf einen Bestätigungslink
 account-error-email-length = E-Mail-Adresse muss zwischen 1 und 255 Zeichen lang sein
 account-error-email-already-added = E-Mail-Adresse bereits hinzugefügt
+account-error-email-last = Sie können Ihre einzige E-Mail-Adresse nicht entfernen
 account-error-verification-email = Fehler beim Senden der Bestätigungs-E-Mail
+account-error-disabled = Dieses Konto wurde deaktiviert
+account-error-api-token-name-length = Der Tokenname muss zwischen 1 und 64 Zeichen lang sein
+account-error-api-token-limit = Zu viele API-Tokens, löschen Sie zuerst ein unbenutztes
 
 challenges-ticket-submitted = Ticket eingereicht
 challenges-challenge-solved = Challenge gelöst
 challenges-error-ticket-too-long = Ticket ist zu lang
+challenges-error-ticket-disabled = Tickets sind nicht aktiviert
 challenges-error-incorrect-flag = Falsche Flag
+challenges-error-rate-limited = Zu viele falsche Flags, versuchen Sie es später erneut
+challenges-error-ended = Das CTF ist beendet, es können keine Flags mehr eingereicht werden
+challenges-not-started = Die Challenges werden um {$time} UTC veröffentlicht
+scoreboard-frozen = Die Anzeigetafel ist seit {$time} UTC eingefroren
 challenges-error-writeup-invalid-url = Ungültige URL
 challenges-error-writeup-url-too-long = URL ist zu lang
 challenges-error-writeup-server-error = Server hat nicht erfolgreich geantwortet
+challenges-error-hint-ended = Das CTF ist beendet, es können keine Hinweise mehr freigeschaltet werden
+challenge-hint = Hinweis {$number}
+challenge-hint-unlock = {$cost ->
+  [0] Freischalten
+  *[other] Für {$cost} Punkte freischalten
+}
+challenge-hint-confirm = {$cost ->
+  [0] Diesen Hinweis freischalten?
+  *[other] Diesen Hinweis freischalten? Deinem Team werden {$cost} Punkte abgezogen.
+}
+challenge-hints-unlocked = Von deinem Team freigeschaltete Hinweise
 
 unknown-error = Unbekannter Fehler
 
@@ -47,6 +72,11 @@ points = {$points ->
   *[other] {$points} Punkte
 }
 solves-points = {solves} / {points}
+dynamic-scoring = {$curve ->
+  [logarithmic] Logarithmischer
+  [linear] Linearer
+  *[quadratic] Quadratischer
+} Abfall von {$initial} auf {$minimum} Punkte über {$decay} Lösungen
 
 command-palette = Befehlspalette...
     .hint = Befehl eintippen...
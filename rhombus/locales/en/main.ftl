@@ -3,6 +3,11 @@ scoreboard = Scoreboard
 team = Team
 account = Account
 challenges = Challenges
+admin = Admin
+announcements = Announcements
+announcements-empty = No announcements yet
+live-teammate-solve = {$name} solved {$challenge}
+live-first-blood = {$team} got first blood on {$challenge}
 
 account-description = Manage your individual account settings. View
     {$link_start}public profile{$link_end}.
@@ -40,17 +45,70 @@ team-members-limited-hint = {$count} out of {$limit ->
 account-check-email = Check your email for a verification link
 account-error-email-length = Email must be between 1 and 255 characters
 account-error-email-already-added = Email already added
+account-error-email-last = You cannot remove your only email
 account-error-verification-email = Failed to send verification email
+account-error-disabled = This account has been disabled
+account-error-api-token-name-length = Token name must be between 1 and 64 characters
+account-error-api-token-limit = Too many API tokens, delete an unused one first
 account-error-signin-email = Failed to send sign in email
 account-error-invalid-credentials = Invalid password
 
 challenges-ticket-submitted = Ticket submitted
 challenges-challenge-solved = Challenge solved
 challenges-error-ticket-too-long = Ticket is too long
+challenges-error-ticket-disabled = Tickets are not enabled
 challenges-error-incorrect-flag = Incorrect flag
+challenges-error-rate-limited = Too many incorrect flags, try again later
+challenges-error-ended = The CTF has ended, flags can no longer be submitted
+challenges-not-started = Challenges will be released at {$time} UTC
+scoreboard-frozen = The scoreboard has been frozen since {$time} UTC
 challenges-error-writeup-invalid-url = Invalid URL
 challenges-error-writeup-url-too-long = URL is too long
 challenges-error-writeup-server-error = Server did not respond successfully
+challenges-error-hint-ended = The CTF has ended, hints can no longer be unlocked
+challenge-hint = Hint {$number}
+challenge-hint-unlock = {$cost ->
+    [0] Unlock
+    *[other] Unlock for {$cost} points
+}
+challenge-hint-confirm = {$cost ->
+    [0] Unlock this hint?
+    *[other] Unlock this hint? {$cost} points will be deducted from your team.
+}
+challenge-hints-unlocked = Hints your team has unlocked
+challenges-error-instance-ended = The CTF has ended, instances can no longer be started
+challenges-error-instance-starting = Your team's instance is already starting
+challenges-error-instance-start = Failed to start the instance
+challenge-instance = Instance
+challenge-instance-start = Start instance
+challenge-instance-extend = Extend
+challenge-instance-stop = Stop
+challenge-instance-expires = Stops at {$time} UTC
+challenge-instance-timeout = {$minutes ->
+    [one] Runs for 1 minute for your team only
+    *[other] Runs for {$minutes} minutes for your team only
+}
+
+admin-error-challenge-name-taken = A challenge with this name already exists
+admin-error-challenge-required = Challenge name and flag are required
+admin-error-challenge-flag-regex = Flag is not a valid regular expression
+admin-error-challenge-flag-per-team = Per team flags must contain the {"{"}hmac{"}"} placeholder
+admin-error-challenge-flag-issued = Issued flags must contain the {"{"}random{"}"} placeholder
+admin-error-challenge-points = Points must be "dynamic" or a number
+admin-error-challenge-dynamic = Initial points must be at least the minimum, the minimum at least 0 and decay at least 1
+admin-error-challenge-category-author = Unknown category or author
+admin-error-challenge-healthcheck-interval = Healthcheck interval must be at least 1 second
+admin-error-challenge-release = Release time must be an RFC 3339 timestamp such as 2024-10-18T18:00:00Z
+admin-error-challenge-instance = Instance timeout must be at least 1 second
+admin-error-attachment-required = Attachment name and URL are required
+admin-error-category-required = Category name is required
+admin-error-category-in-use = Category still has challenges
+admin-error-author-required = Author name is required
+admin-error-author-discord-id = Invalid Discord ID
+admin-error-author-in-use = Author still has challenges
+admin-error-announcement-required = Announcement title and content are required
+admin-error-user-self = You cannot change this for your own account
+admin-error-user-team-owner = User already owns their team
 
 unknown-error = Unknown error
 
@@ -65,6 +123,11 @@ points = {$points ->
     *[other] {$points} pts
 }
 solves-points = {solves} / {points}
+dynamic-scoring = {$curve ->
+    [logarithmic] Logarithmic
+    [linear] Linear
+    *[quadratic] Quadratic
+} decay from {$initial} to {$minimum} points over {$decay} solves
 
 command-palette = Command Palette...
     .hint = Type a command...
@@ -236,4 +236,9 @@ BEGIN
     );
 END;
 
+CREATE TABLE IF NOT EXISTS rhombus_migration (
+    version INTEGER PRIMARY KEY NOT NULL,
+    applied_at INTEGER NOT NULL DEFAULT(strftime('%s', 'now'))
+);
+
 COMMIT;
@@ -0,0 +1 @@
+ALTER TABLE rhombus_challenge ADD COLUMN hidden BOOLEAN NOT NULL DEFAULT(FALSE);
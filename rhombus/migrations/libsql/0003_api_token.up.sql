@@ -0,0 +1,8 @@
+CREATE TABLE IF NOT EXISTS rhombus_api_token (
+    id INTEGER PRIMARY KEY NOT NULL,
+    user_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,
+    created_at INTEGER NOT NULL DEFAULT(strftime('%s', 'now')),
+    FOREIGN KEY (user_id) REFERENCES rhombus_user(id) ON DELETE CASCADE
+);
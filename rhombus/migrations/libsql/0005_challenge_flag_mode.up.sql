@@ -0,0 +1 @@
+ALTER TABLE rhombus_challenge ADD COLUMN flag_mode INTEGER NOT NULL DEFAULT(0);
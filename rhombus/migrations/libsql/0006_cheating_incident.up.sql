@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS rhombus_cheating_incident (
+    id INTEGER PRIMARY KEY NOT NULL,
+    challenge_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    team_id INTEGER NOT NULL,
+    flag_team_id INTEGER NOT NULL,
+    flag TEXT NOT NULL,
+    created_at INTEGER NOT NULL DEFAULT(strftime('%s', 'now')),
+    FOREIGN KEY (challenge_id) REFERENCES rhombus_challenge(id),
+    FOREIGN KEY (user_id) REFERENCES rhombus_user(id),
+    FOREIGN KEY (team_id) REFERENCES rhombus_team(id),
+    FOREIGN KEY (flag_team_id) REFERENCES rhombus_team(id)
+);
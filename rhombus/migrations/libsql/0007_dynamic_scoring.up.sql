@@ -0,0 +1,38 @@
+ALTER TABLE rhombus_challenge ADD COLUMN dynamic_initial INTEGER NOT NULL DEFAULT(500);
+ALTER TABLE rhombus_challenge ADD COLUMN dynamic_minimum INTEGER NOT NULL DEFAULT(100);
+ALTER TABLE rhombus_challenge ADD COLUMN dynamic_decay INTEGER NOT NULL DEFAULT(50);
+ALTER TABLE rhombus_challenge ADD COLUMN dynamic_curve INTEGER NOT NULL DEFAULT(0);
+
+DROP VIEW IF EXISTS rhombus_challenge_division_points;
+
+CREATE VIEW rhombus_challenge_division_points AS
+SELECT
+    rhombus_challenge.id AS challenge_id,
+    rhombus_division.id AS division_id,
+    CASE
+        WHEN rhombus_challenge.score_type = 0 THEN
+            MAX(ROUND(CASE rhombus_challenge.dynamic_curve
+                WHEN 1 THEN
+                    rhombus_challenge.dynamic_initial - (rhombus_challenge.dynamic_initial - rhombus_challenge.dynamic_minimum) * LN(1 + COUNT(DISTINCT rhombus_team.id)) / LN(1 + rhombus_challenge.dynamic_decay)
+                WHEN 2 THEN
+                    rhombus_challenge.dynamic_initial - (1.0 * (rhombus_challenge.dynamic_initial - rhombus_challenge.dynamic_minimum) * COUNT(DISTINCT rhombus_team.id)) / rhombus_challenge.dynamic_decay
+                ELSE
+                    ((1.0 * (rhombus_challenge.dynamic_minimum - rhombus_challenge.dynamic_initial)) / POWER(rhombus_challenge.dynamic_decay, 2)) * POWER(COUNT(DISTINCT rhombus_team.id), 2) + rhombus_challenge.dynamic_initial
+            END), rhombus_challenge.dynamic_minimum)
+        ELSE rhombus_challenge.static_points
+    END AS points,
+    COUNT(DISTINCT rhombus_team.id) AS solves
+FROM rhombus_challenge
+CROSS JOIN rhombus_division
+LEFT JOIN rhombus_solve ON
+    rhombus_challenge.id = rhombus_solve.challenge_id AND
+    rhombus_solve.user_id IN (
+        SELECT rhombus_user.id
+        FROM rhombus_user
+        JOIN rhombus_team ON rhombus_user.team_id = rhombus_team.id
+        JOIN rhombus_team_division ON rhombus_team.id = rhombus_team_division.team_id
+        WHERE rhombus_team_division.division_id = rhombus_division.id
+    )
+LEFT JOIN rhombus_user ON rhombus_solve.user_id = rhombus_user.id
+LEFT JOIN rhombus_team ON rhombus_user.team_id = rhombus_team.id
+GROUP BY rhombus_challenge.id, rhombus_division.id;
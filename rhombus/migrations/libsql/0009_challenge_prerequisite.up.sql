@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS rhombus_challenge_prerequisite (
+    challenge_id INTEGER NOT NULL,
+    id INTEGER NOT NULL,
+    required_challenge_id INTEGER,
+    category_id INTEGER,
+    points INTEGER,
+    PRIMARY KEY (challenge_id, id),
+    FOREIGN KEY (challenge_id) REFERENCES rhombus_challenge(id)
+);
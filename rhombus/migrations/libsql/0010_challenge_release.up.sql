@@ -0,0 +1,2 @@
+ALTER TABLE rhombus_challenge ADD COLUMN release_at INTEGER;
+ALTER TABLE rhombus_challenge ADD COLUMN released BOOLEAN NOT NULL DEFAULT(FALSE);
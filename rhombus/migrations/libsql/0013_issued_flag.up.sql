@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS rhombus_issued_flag (
+    challenge_id INTEGER NOT NULL,
+    team_id INTEGER NOT NULL,
+    flag TEXT NOT NULL,
+    issued_at INTEGER NOT NULL DEFAULT(strftime('%s', 'now')),
+    PRIMARY KEY (challenge_id, flag),
+    FOREIGN KEY (challenge_id) REFERENCES rhombus_challenge(id),
+    FOREIGN KEY (team_id) REFERENCES rhombus_team(id)
+);
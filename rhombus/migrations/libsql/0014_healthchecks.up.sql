@@ -0,0 +1,13 @@
+ALTER TABLE rhombus_challenge ADD COLUMN healthcheck_interval INTEGER;
+
+CREATE TABLE IF NOT EXISTS rhombus_healthcheck (
+    id INTEGER PRIMARY KEY NOT NULL,
+    challenge_id INTEGER NOT NULL,
+    checked_at INTEGER NOT NULL,
+    healthy BOOLEAN,
+    latency INTEGER NOT NULL,
+    error TEXT,
+    FOREIGN KEY (challenge_id) REFERENCES rhombus_challenge(id)
+);
+
+CREATE INDEX IF NOT EXISTS rhombus_healthcheck_checked_at ON rhombus_healthcheck(checked_at);
@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS rhombus_announcement (
+    id INTEGER PRIMARY KEY NOT NULL,
+    title TEXT NOT NULL,
+    content TEXT NOT NULL,
+    content_html TEXT NOT NULL,
+    banner BOOLEAN NOT NULL DEFAULT(FALSE),
+    created_at INTEGER NOT NULL DEFAULT(strftime('%s', 'now')),
+    updated_at INTEGER
+);
@@ -1,4 +1,27 @@
-DROP TABLE IF EXISTS Challenge;
-DROP TABLE IF EXISTS "User";
-DROP TABLE IF EXISTS Track;
-DROP TABLE IF EXISTS TrackConnection;
+DROP TRIGGER IF EXISTS rhombus_track_autodelete ON rhombus_track;
+DROP FUNCTION IF EXISTS rhombus_track_autodelete;
+DROP TRIGGER IF EXISTS rhombus_email_signin_autodelete ON rhombus_email_signin;
+DROP FUNCTION IF EXISTS rhombus_email_signin_autodelete;
+DROP VIEW IF EXISTS rhombus_team_division_points;
+DROP VIEW IF EXISTS rhombus_challenge_division_points;
+DROP TABLE IF EXISTS rhombus_track_ip;
+DROP TABLE IF EXISTS rhombus_track;
+DROP TABLE IF EXISTS rhombus_config;
+DROP SEQUENCE IF EXISTS rhombus_ticket_number_counter;
+DROP TABLE IF EXISTS rhombus_ticket_email_message_id_reference;
+DROP TABLE IF EXISTS rhombus_ticket;
+DROP TABLE IF EXISTS rhombus_team_division;
+DROP TABLE IF EXISTS rhombus_writeup;
+DROP TABLE IF EXISTS rhombus_email_signin;
+DROP TABLE IF EXISTS rhombus_email;
+DROP TABLE IF EXISTS rhombus_user_division;
+DROP TABLE IF EXISTS rhombus_solve;
+DROP TABLE IF EXISTS rhombus_points_snapshot;
+DROP TABLE IF EXISTS rhombus_division;
+DROP TABLE IF EXISTS rhombus_file;
+DROP TABLE IF EXISTS rhombus_file_attachment;
+DROP TABLE IF EXISTS rhombus_challenge;
+DROP TABLE IF EXISTS rhombus_author;
+DROP TABLE IF EXISTS rhombus_category;
+DROP TABLE IF EXISTS rhombus_user;
+DROP TABLE IF EXISTS rhombus_team;
@@ -1,34 +1,242 @@
-CREATE TABLE IF NOT EXISTS Challenge (
+CREATE TABLE IF NOT EXISTS rhombus_team (
     id BIGSERIAL PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    invite_token TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS rhombus_user (
+    id BIGSERIAL PRIMARY KEY,
+    name TEXT NOT NULL,
+    avatar TEXT NOT NULL,
+    discord_id BIGINT UNIQUE,
+    password TEXT,
+    team_id BIGINT NOT NULL,
+    owner_team_id BIGINT NOT NULL,
+    disabled BOOLEAN NOT NULL DEFAULT(FALSE),
+    is_admin BOOLEAN NOT NULL DEFAULT(FALSE),
+    FOREIGN KEY (team_id) REFERENCES rhombus_team(id),
+    FOREIGN KEY (owner_team_id) REFERENCES rhombus_team(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS rhombus_category (
+    id BIGINT PRIMARY KEY NOT NULL,
+    name TEXT NOT NULL,
+    color TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS rhombus_author (
+    id BIGINT PRIMARY KEY NOT NULL,
+    name TEXT NOT NULL,
+    avatar TEXT NOT NULL,
+    discord_id BIGINT NOT NULL UNIQUE
+);
+
+-- Foreign keys to categories and authors are deferred so that the challenge loader can
+-- replace them inside of a single transaction, in any order
+CREATE TABLE IF NOT EXISTS rhombus_challenge (
+    id BIGINT PRIMARY KEY NOT NULL,
     name TEXT NOT NULL,
+    description TEXT NOT NULL,
+    flag TEXT NOT NULL,
+    category_id BIGINT NOT NULL,
+    author_id BIGINT NOT NULL,
+    ticket_template TEXT,
+    healthscript TEXT,
+    healthy BOOLEAN,
+    last_healthcheck TIMESTAMPTZ,
+    score_type INTEGER NOT NULL,
+    static_points BIGINT,
+    FOREIGN KEY (category_id) REFERENCES rhombus_category(id) DEFERRABLE INITIALLY DEFERRED,
+    FOREIGN KEY (author_id) REFERENCES rhombus_author(id) DEFERRABLE INITIALLY DEFERRED
+);
+
+CREATE TABLE IF NOT EXISTS rhombus_file_attachment (
+    challenge_id BIGINT NOT NULL,
+    name TEXT NOT NULL,
+    url TEXT NOT NULL,
+    PRIMARY KEY (challenge_id, url),
+    FOREIGN KEY (challenge_id) REFERENCES rhombus_challenge(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS rhombus_file (
+    hash TEXT NOT NULL UNIQUE,
+    filename TEXT NOT NULL,
+    contents BYTEA NOT NULL,
+    PRIMARY KEY (hash)
+);
+
+CREATE TABLE IF NOT EXISTS rhombus_division (
+    id BIGINT PRIMARY KEY NOT NULL,
+    name TEXT NOT NULL UNIQUE,
     description TEXT NOT NULL
 );
 
-CREATE TABLE IF NOT EXISTS "User" (
+CREATE TABLE IF NOT EXISTS rhombus_points_snapshot (
+    team_id BIGINT NOT NULL,
+    division_id BIGINT NOT NULL,
+    at TIMESTAMPTZ NOT NULL DEFAULT(now()),
+    points BIGINT NOT NULL,
+    PRIMARY KEY (team_id, division_id, at),
+    FOREIGN KEY (team_id) REFERENCES rhombus_team(id) ON DELETE CASCADE,
+    FOREIGN KEY (division_id) REFERENCES rhombus_division(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS rhombus_solve (
+    challenge_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    solved_at TIMESTAMPTZ NOT NULL DEFAULT(now()),
+    PRIMARY KEY (challenge_id, user_id),
+    FOREIGN KEY (challenge_id) REFERENCES rhombus_challenge(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES rhombus_user(id)
+);
+
+CREATE TABLE IF NOT EXISTS rhombus_user_division (
+    user_id BIGINT NOT NULL,
+    division_id BIGINT NOT NULL,
+    PRIMARY KEY (user_id, division_id),
+    FOREIGN KEY (user_id) REFERENCES rhombus_user(id),
+    FOREIGN KEY (division_id) REFERENCES rhombus_division(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS rhombus_email (
+    email TEXT NOT NULL UNIQUE,
+    user_id BIGINT NOT NULL,
+    code TEXT UNIQUE,
+    PRIMARY KEY (email, user_id),
+    FOREIGN KEY (user_id) REFERENCES rhombus_user(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS rhombus_email_signin (
+    email TEXT NOT NULL,
+    code TEXT UNIQUE,
+    expires TIMESTAMPTZ NOT NULL DEFAULT(now() + INTERVAL '1 hour'),
+    PRIMARY KEY (email)
+);
+
+CREATE OR REPLACE FUNCTION rhombus_email_signin_autodelete() RETURNS TRIGGER AS $$
+BEGIN
+    DELETE FROM rhombus_email_signin
+    WHERE expires < now();
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER rhombus_email_signin_autodelete
+    BEFORE INSERT ON rhombus_email_signin
+    FOR EACH ROW EXECUTE FUNCTION rhombus_email_signin_autodelete();
+
+CREATE TABLE IF NOT EXISTS rhombus_writeup (
+    user_id BIGINT NOT NULL,
+    challenge_id BIGINT NOT NULL,
+    url TEXT NOT NULL,
+    PRIMARY KEY (user_id, challenge_id),
+    FOREIGN KEY (user_id) REFERENCES rhombus_user(id),
+    FOREIGN KEY (challenge_id) REFERENCES rhombus_challenge(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS rhombus_team_division (
+    team_id BIGINT NOT NULL,
+    division_id BIGINT NOT NULL,
+    PRIMARY KEY (team_id, division_id),
+    FOREIGN KEY (team_id) REFERENCES rhombus_team(id),
+    FOREIGN KEY (division_id) REFERENCES rhombus_division(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS rhombus_ticket (
+    ticket_number BIGINT NOT NULL UNIQUE,
+    user_id BIGINT NOT NULL,
+    challenge_id BIGINT NOT NULL,
+    closed_at TIMESTAMPTZ,
+    discord_channel_id BIGINT NOT NULL UNIQUE,
+    discord_last_message_id BIGINT,
+    PRIMARY KEY (ticket_number),
+    FOREIGN KEY (user_id) REFERENCES rhombus_user(id),
+    FOREIGN KEY (challenge_id) REFERENCES rhombus_challenge(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS rhombus_ticket_email_message_id_reference (
+    message_id TEXT NOT NULL,
+    ticket_number BIGINT NOT NULL,
+    user_sent BOOLEAN NOT NULL DEFAULT(FALSE),
+    PRIMARY KEY (message_id),
+    FOREIGN KEY (ticket_number) REFERENCES rhombus_ticket(ticket_number) ON DELETE CASCADE
+);
+
+CREATE SEQUENCE IF NOT EXISTS rhombus_ticket_number_counter;
+
+CREATE TABLE IF NOT EXISTS rhombus_config (
+    id BIGINT PRIMARY KEY NOT NULL,
+    config TEXT
+);
+
+CREATE OR REPLACE VIEW rhombus_challenge_division_points AS
+SELECT
+    rhombus_challenge.id AS challenge_id,
+    rhombus_division.id AS division_id,
+    CAST(CASE
+        WHEN rhombus_challenge.score_type = 0 THEN
+            GREATEST(ROUND((((100 - 500) / POWER(50, 2)) * POWER(COUNT(DISTINCT rhombus_team.id), 2)) + 500), 100)
+        ELSE rhombus_challenge.static_points
+    END AS BIGINT) AS points,
+    COUNT(DISTINCT rhombus_team.id) AS solves
+FROM rhombus_challenge
+CROSS JOIN rhombus_division
+LEFT JOIN rhombus_solve ON
+    rhombus_challenge.id = rhombus_solve.challenge_id AND
+    rhombus_solve.user_id IN (
+        SELECT rhombus_user.id
+        FROM rhombus_user
+        JOIN rhombus_team ON rhombus_user.team_id = rhombus_team.id
+        JOIN rhombus_team_division ON rhombus_team.id = rhombus_team_division.team_id
+        WHERE rhombus_team_division.division_id = rhombus_division.id
+    )
+LEFT JOIN rhombus_user ON rhombus_solve.user_id = rhombus_user.id
+LEFT JOIN rhombus_team ON rhombus_user.team_id = rhombus_team.id
+GROUP BY rhombus_challenge.id, rhombus_division.id;
+
+CREATE OR REPLACE VIEW rhombus_team_division_points AS
+SELECT rhombus_team_division.team_id, rhombus_team_division.division_id, CAST(SUM(points) AS BIGINT) as points
+FROM rhombus_team_division
+JOIN (
+    SELECT DISTINCT rhombus_user.team_id, rhombus_challenge_division_points.challenge_id, rhombus_challenge_division_points.division_id, rhombus_challenge_division_points.points
+    FROM rhombus_challenge_division_points
+    JOIN rhombus_solve ON rhombus_challenge_division_points.challenge_id = rhombus_solve.challenge_id
+    JOIN rhombus_user ON rhombus_solve.user_id = rhombus_user.id
+) AS unique_solves ON rhombus_team_division.team_id = unique_solves.team_id AND rhombus_team_division.division_id = unique_solves.division_id
+GROUP BY rhombus_team_division.team_id, rhombus_team_division.division_id;
+
+CREATE TABLE IF NOT EXISTS rhombus_track (
     id BIGSERIAL PRIMARY KEY,
-    name VARCHAR(100) NOT NULL,
-    email VARCHAR(255) NOT NULL UNIQUE,
-    avatar VARCHAR(255) NOT NULL UNIQUE,
-    discord_id VARCHAR(255) NOT NULL UNIQUE,
-    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
-    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
-);
-
-CREATE TABLE IF NOT EXISTS Track (
-    ip VARCHAR(39), -- 39 is the max length of an IPv6 address
-    user_agent TEXT,
-    last_seen_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
-    requests INT default(0),
-    PRIMARY KEY (ip, user_agent)
-);
-
-CREATE TABLE IF NOT EXISTS TrackConnection (
-    ip VARCHAR(39),
-    user_agent TEXT,
-    user_id BIGINT,
-    last_seen_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
-    requests INT default(0),
-    FOREIGN KEY (ip, user_agent) REFERENCES Track(ip, user_agent),
-    FOREIGN KEY (user_id) REFERENCES "User"(id),
-    PRIMARY KEY (ip, user_agent, user_id)
+    ip BYTEA NOT NULL,
+    user_agent TEXT NOT NULL,
+    last_seen_at TIMESTAMPTZ NOT NULL DEFAULT(now()),
+    requests BIGINT NOT NULL,
+    UNIQUE(ip, user_agent)
+);
+
+CREATE TABLE IF NOT EXISTS rhombus_track_ip (
+    user_id BIGINT NOT NULL,
+    track_id BIGINT NOT NULL,
+    PRIMARY KEY (user_id, track_id),
+    FOREIGN KEY (track_id) REFERENCES rhombus_track(id) ON DELETE CASCADE
 );
+
+-- Enforce a maximum number of `user_agent`s per ip, to make it less trivial for someone
+-- to fill up our db by simply spamming us with requests from different user agents
+CREATE OR REPLACE FUNCTION rhombus_track_autodelete() RETURNS TRIGGER AS $$
+BEGIN
+    DELETE FROM rhombus_track
+    WHERE id = (
+        SELECT id
+        FROM rhombus_track
+        WHERE ip = NEW.ip AND id != NEW.id
+        ORDER BY requests DESC
+        LIMIT 1 OFFSET 31
+    );
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER rhombus_track_autodelete
+    AFTER INSERT ON rhombus_track
+    FOR EACH ROW EXECUTE FUNCTION rhombus_track_autodelete();
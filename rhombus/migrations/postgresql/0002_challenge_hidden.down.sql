@@ -0,0 +1 @@
+ALTER TABLE rhombus_challenge DROP COLUMN IF EXISTS hidden;
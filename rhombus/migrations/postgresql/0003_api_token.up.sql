@@ -0,0 +1,8 @@
+CREATE TABLE IF NOT EXISTS rhombus_api_token (
+    id BIGSERIAL PRIMARY KEY,
+    user_id BIGINT NOT NULL,
+    name TEXT NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT(now()),
+    FOREIGN KEY (user_id) REFERENCES rhombus_user(id) ON DELETE CASCADE
+);
@@ -0,0 +1 @@
+DROP TABLE IF EXISTS rhombus_wrong_submission;
@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS rhombus_wrong_submission (
+    id BIGSERIAL PRIMARY KEY,
+    challenge_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    team_id BIGINT NOT NULL,
+    flag TEXT NOT NULL,
+    submitted_at TIMESTAMPTZ NOT NULL DEFAULT(now()),
+    FOREIGN KEY (challenge_id) REFERENCES rhombus_challenge(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES rhombus_user(id),
+    FOREIGN KEY (team_id) REFERENCES rhombus_team(id)
+);
+
+CREATE INDEX IF NOT EXISTS rhombus_wrong_submission_team_id ON rhombus_wrong_submission(team_id, submitted_at);
@@ -0,0 +1,29 @@
+CREATE OR REPLACE VIEW rhombus_challenge_division_points AS
+SELECT
+    rhombus_challenge.id AS challenge_id,
+    rhombus_division.id AS division_id,
+    CAST(CASE
+        WHEN rhombus_challenge.score_type = 0 THEN
+            GREATEST(ROUND((((100 - 500) / POWER(50, 2)) * POWER(COUNT(DISTINCT rhombus_team.id), 2)) + 500), 100)
+        ELSE rhombus_challenge.static_points
+    END AS BIGINT) AS points,
+    COUNT(DISTINCT rhombus_team.id) AS solves
+FROM rhombus_challenge
+CROSS JOIN rhombus_division
+LEFT JOIN rhombus_solve ON
+    rhombus_challenge.id = rhombus_solve.challenge_id AND
+    rhombus_solve.user_id IN (
+        SELECT rhombus_user.id
+        FROM rhombus_user
+        JOIN rhombus_team ON rhombus_user.team_id = rhombus_team.id
+        JOIN rhombus_team_division ON rhombus_team.id = rhombus_team_division.team_id
+        WHERE rhombus_team_division.division_id = rhombus_division.id
+    )
+LEFT JOIN rhombus_user ON rhombus_solve.user_id = rhombus_user.id
+LEFT JOIN rhombus_team ON rhombus_user.team_id = rhombus_team.id
+GROUP BY rhombus_challenge.id, rhombus_division.id;
+
+ALTER TABLE rhombus_challenge DROP COLUMN IF EXISTS dynamic_initial;
+ALTER TABLE rhombus_challenge DROP COLUMN IF EXISTS dynamic_minimum;
+ALTER TABLE rhombus_challenge DROP COLUMN IF EXISTS dynamic_decay;
+ALTER TABLE rhombus_challenge DROP COLUMN IF EXISTS dynamic_curve;
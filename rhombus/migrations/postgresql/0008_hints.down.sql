@@ -0,0 +1,13 @@
+CREATE OR REPLACE VIEW rhombus_team_division_points AS
+SELECT rhombus_team_division.team_id, rhombus_team_division.division_id, CAST(SUM(points) AS BIGINT) as points
+FROM rhombus_team_division
+JOIN (
+    SELECT DISTINCT rhombus_user.team_id, rhombus_challenge_division_points.challenge_id, rhombus_challenge_division_points.division_id, rhombus_challenge_division_points.points
+    FROM rhombus_challenge_division_points
+    JOIN rhombus_solve ON rhombus_challenge_division_points.challenge_id = rhombus_solve.challenge_id
+    JOIN rhombus_user ON rhombus_solve.user_id = rhombus_user.id
+) AS unique_solves ON rhombus_team_division.team_id = unique_solves.team_id AND rhombus_team_division.division_id = unique_solves.division_id
+GROUP BY rhombus_team_division.team_id, rhombus_team_division.division_id;
+
+DROP TABLE IF EXISTS rhombus_hint_unlock;
+DROP TABLE IF EXISTS rhombus_challenge_hint;
@@ -0,0 +1 @@
+DROP TABLE IF EXISTS rhombus_challenge_prerequisite;
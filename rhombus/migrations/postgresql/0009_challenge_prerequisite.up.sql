@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS rhombus_challenge_prerequisite (
+    challenge_id BIGINT NOT NULL,
+    id BIGINT NOT NULL,
+    required_challenge_id BIGINT,
+    category_id BIGINT,
+    points BIGINT,
+    PRIMARY KEY (challenge_id, id),
+    FOREIGN KEY (challenge_id) REFERENCES rhombus_challenge(id) ON DELETE CASCADE,
+    FOREIGN KEY (required_challenge_id) REFERENCES rhombus_challenge(id) ON DELETE CASCADE DEFERRABLE INITIALLY DEFERRED,
+    FOREIGN KEY (category_id) REFERENCES rhombus_category(id) ON DELETE CASCADE DEFERRABLE INITIALLY DEFERRED
+);
@@ -0,0 +1,2 @@
+ALTER TABLE rhombus_challenge DROP COLUMN IF EXISTS release_at;
+ALTER TABLE rhombus_challenge DROP COLUMN IF EXISTS released;
@@ -0,0 +1 @@
+ALTER TABLE rhombus_challenge DROP COLUMN IF EXISTS description_html;
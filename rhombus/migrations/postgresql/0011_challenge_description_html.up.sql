@@ -0,0 +1 @@
+ALTER TABLE rhombus_challenge ADD COLUMN description_html TEXT NOT NULL DEFAULT('');
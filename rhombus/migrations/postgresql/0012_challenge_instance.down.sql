@@ -0,0 +1,2 @@
+ALTER TABLE rhombus_challenge DROP COLUMN IF EXISTS instance_spec;
+ALTER TABLE rhombus_challenge DROP COLUMN IF EXISTS instance_timeout;
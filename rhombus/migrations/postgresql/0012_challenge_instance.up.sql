@@ -0,0 +1,2 @@
+ALTER TABLE rhombus_challenge ADD COLUMN instance_spec TEXT;
+ALTER TABLE rhombus_challenge ADD COLUMN instance_timeout BIGINT;
@@ -0,0 +1 @@
+DROP TABLE IF EXISTS rhombus_issued_flag;
@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS rhombus_issued_flag (
+    challenge_id BIGINT NOT NULL,
+    team_id BIGINT NOT NULL,
+    flag TEXT NOT NULL,
+    issued_at TIMESTAMPTZ NOT NULL DEFAULT(now()),
+    PRIMARY KEY (challenge_id, flag),
+    FOREIGN KEY (challenge_id) REFERENCES rhombus_challenge(id) ON DELETE CASCADE,
+    FOREIGN KEY (team_id) REFERENCES rhombus_team(id)
+);
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS rhombus_healthcheck;
+ALTER TABLE rhombus_challenge DROP COLUMN IF EXISTS healthcheck_interval;
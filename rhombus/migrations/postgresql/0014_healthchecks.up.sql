@@ -0,0 +1,13 @@
+ALTER TABLE rhombus_challenge ADD COLUMN healthcheck_interval BIGINT;
+
+CREATE TABLE IF NOT EXISTS rhombus_healthcheck (
+    id BIGSERIAL PRIMARY KEY,
+    challenge_id BIGINT NOT NULL,
+    checked_at TIMESTAMPTZ NOT NULL,
+    healthy BOOLEAN,
+    latency BIGINT NOT NULL,
+    error TEXT,
+    FOREIGN KEY (challenge_id) REFERENCES rhombus_challenge(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS rhombus_healthcheck_checked_at ON rhombus_healthcheck(checked_at);
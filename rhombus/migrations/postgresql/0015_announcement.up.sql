@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS rhombus_announcement (
+    id BIGSERIAL PRIMARY KEY,
+    title TEXT NOT NULL,
+    content TEXT NOT NULL,
+    content_html TEXT NOT NULL,
+    banner BOOLEAN NOT NULL DEFAULT(FALSE),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT(now()),
+    updated_at TIMESTAMPTZ
+);
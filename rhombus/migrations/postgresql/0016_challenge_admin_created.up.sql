@@ -0,0 +1 @@
+ALTER TABLE rhombus_challenge ADD COLUMN admin_created BOOLEAN NOT NULL DEFAULT(FALSE);
@@ -0,0 +1,18 @@
+CREATE OR REPLACE VIEW rhombus_team_division_points AS
+SELECT rhombus_team_division.team_id, rhombus_team_division.division_id, CAST(COALESCE(SUM(unique_solves.points), 0) - COALESCE(MAX(hint_penalties.cost), 0) AS BIGINT) AS points
+FROM rhombus_team_division
+LEFT JOIN (
+    SELECT DISTINCT rhombus_user.team_id, rhombus_challenge_division_points.challenge_id, rhombus_challenge_division_points.division_id, rhombus_challenge_division_points.points
+    FROM rhombus_challenge_division_points
+    JOIN rhombus_solve ON rhombus_challenge_division_points.challenge_id = rhombus_solve.challenge_id
+    JOIN rhombus_user ON rhombus_solve.user_id = rhombus_user.id
+) AS unique_solves ON rhombus_team_division.team_id = unique_solves.team_id AND rhombus_team_division.division_id = unique_solves.division_id
+-- only hints which still exist cost points
+LEFT JOIN (
+    SELECT rhombus_hint_unlock.team_id, SUM(rhombus_hint_unlock.cost) AS cost
+    FROM rhombus_hint_unlock
+    JOIN rhombus_challenge_hint ON rhombus_hint_unlock.challenge_id = rhombus_challenge_hint.challenge_id AND rhombus_hint_unlock.hint_id = rhombus_challenge_hint.id
+    GROUP BY rhombus_hint_unlock.team_id
+) AS hint_penalties ON rhombus_team_division.team_id = hint_penalties.team_id
+GROUP BY rhombus_team_division.team_id, rhombus_team_division.division_id
+HAVING COUNT(unique_solves.challenge_id) > 0 OR MAX(hint_penalties.cost) IS NOT NULL;
@@ -22,9 +22,10 @@ use crate::{
     errors::{DatabaseConfigurationError, RhombusError},
     internal::{
         auth::{
-            auth_injector_middleware, enforce_admin_middleware, enforce_auth_middleware,
-            route_signin, route_signin_credentials, route_signin_discord_callback,
-            route_signin_email, route_signin_email_callback, route_signout,
+            auth_injector_middleware, enforce_admin_middleware, enforce_api_auth_middleware,
+            enforce_auth_middleware, route_signin, route_signin_credentials,
+            route_signin_discord_callback, route_signin_email, route_signin_email_callback,
+            route_signout,
         },
         command_palette::route_command_palette_items,
         database::{
@@ -33,14 +34,16 @@ use crate::{
         },
         discord::Bot,
         division::{
-            Division, DivisionEligibilityProvider, EmailDivisionEligibilityProvider,
-            MaxDivisionPlayers, OpenDivisionEligibilityProvider,
+            division_role_reconciler, Division, DivisionEligibilityProvider,
+            EmailDivisionEligibilityProvider, MaxDivisionPlayers, OpenDivisionEligibilityProvider,
         },
         email::{
             imap::ImapEmailReciever, mailgun::MailgunProvider, outbound_mailer::OutboundMailer,
             provider::InboundEmail, smtp::SmtpProvider,
         },
-        health::{healthcheck_catch_up, healthcheck_runner},
+        events::EventBus,
+        health::healthcheck_runner,
+        instance::{instance_reaper, Instances},
         ip::{
             default_ip_extractor, ip_insert_blank_middleware, ip_insert_middleware,
             maybe_cf_connecting_ip, maybe_fly_client_ip, maybe_peer_ip,
@@ -49,17 +52,40 @@ use crate::{
         },
         locales::{self, jinja_timediff, jinja_translate, locale_middleware},
         open_graph::route_default_og_image,
+        release::release_runner,
         router::{RouterState, RouterStateInner},
         routes::{
             account::{
                 discord_cache_evictor, route_account, route_account_add_email,
+                route_account_create_api_token, route_account_delete_api_token,
                 route_account_delete_email, route_account_email_verify_callback,
             },
+            admin::{
+                route_admin, route_admin_announcement_create, route_admin_announcement_delete,
+                route_admin_announcement_update, route_admin_announcements,
+                route_admin_attachment_add, route_admin_attachment_delete,
+                route_admin_author_delete, route_admin_author_upsert, route_admin_category_delete,
+                route_admin_category_upsert, route_admin_challenge_create,
+                route_admin_challenge_delete, route_admin_challenge_update,
+                route_admin_challenge_view, route_admin_health, route_admin_incidents,
+                route_admin_submissions, route_admin_user_leave_team, route_admin_user_set_admin,
+                route_admin_user_set_disabled, route_admin_user_view, route_admin_users,
+            },
+            announcements::{route_announcements, route_banner},
+            api::{
+                route_api_account, route_api_account_add_email, route_api_account_delete_email,
+                route_api_account_emails, route_api_challenge, route_api_challenge_issue_flag,
+                route_api_challenge_submit, route_api_challenge_ticket, route_api_challenges,
+                route_api_divisions, route_api_leaderboard, route_api_openapi,
+                route_api_scoreboard, route_api_team, route_api_team_solves,
+            },
             challenges::{
-                route_challenge_submit, route_challenge_view, route_challenges,
+                route_challenge_submit, route_challenge_view, route_challenges, route_hint_unlock,
+                route_instance_extend, route_instance_start, route_instance_stop,
                 route_ticket_submit, route_ticket_view, route_writeup_delete, route_writeup_submit,
             },
             home::route_home,
+            live::route_live,
             public::{route_public_team, route_public_user},
             scoreboard::{
                 route_scoreboard, route_scoreboard_division, route_scoreboard_division_ctftime,
@@ -72,11 +98,13 @@ use crate::{
         settings::{DbConfig, IpPreset, Settings},
         static_serve::route_static_serve,
         templates::Templates,
+        webhook::Webhooks,
     },
-    plugin::{DatabaseProviderContext, RunContext, UploadProviderContext},
+    plugin::{DatabaseProviderContext, InstanceProviderContext, RunContext, UploadProviderContext},
+    process_instance_provider::ProcessInstanceProvider,
     s3_upload_provider::S3UploadProvider,
     upload_provider::UploadProvider,
-    LocalUploadProvider, Plugin, Result,
+    InstanceProviderRef, LocalUploadProvider, Plugin, Result,
 };
 
 pub enum RawDb {
@@ -453,6 +481,10 @@ impl<P: Plugin, U: UploadProvider + Send + Sync + 'static> Builder<P, U> {
         db.load_settings(&mut settings).await?;
         db.save_settings(&settings).await?;
 
+        if let Some(home) = settings.home.as_mut() {
+            home.render();
+        }
+
         let mut divisions = if let Some(divisions) = &settings.divisions {
             divisions
                 .iter()
@@ -498,6 +530,7 @@ impl<P: Plugin, U: UploadProvider + Send + Sync + 'static> Builder<P, U> {
                         name: division.name.clone(),
                         description: division.description.clone(),
                         max_players,
+                        discord_role_id: division.discord_role_id,
                         division_eligibility,
                     }
                 })
@@ -510,6 +543,7 @@ impl<P: Plugin, U: UploadProvider + Send + Sync + 'static> Builder<P, U> {
                 name,
                 description: "Open division for everyone".to_owned(),
                 max_players: MaxDivisionPlayers::Unlimited,
+                discord_role_id: None,
                 division_eligibility: Box::leak(Box::new(OpenDivisionEligibilityProvider {})),
             }]
         };
@@ -565,8 +599,32 @@ impl<P: Plugin, U: UploadProvider + Send + Sync + 'static> Builder<P, U> {
             .upload_provider(&plugin_upload_provider_builder)
             .await;
 
+        let plugin_instance_provider_builder = InstanceProviderContext {
+            settings: &settings,
+            db: cached_db,
+        };
+        let instance_provider = match self
+            .plugins
+            .instance_provider(&plugin_instance_provider_builder)
+            .await
+        {
+            Some(instance_provider) => Some(instance_provider),
+            None => settings
+                .instances
+                .as_ref()
+                .and_then(|i| i.process.as_ref())
+                .map(|process| -> InstanceProviderRef {
+                    info!(host = process.host, "Using process instance provider");
+                    Box::leak(Box::new(ProcessInstanceProvider::new(process)))
+                }),
+        };
+        let instances = instance_provider.map(|instance_provider| -> &'static Instances {
+            Box::leak(Box::new(Instances::new(instance_provider)))
+        });
+
         let uploads_settings = settings.uploads.clone();
         let settings: &'static _ = Box::leak(Box::new(RwLock::new(settings)));
+        let events: &'static _ = Box::leak(Box::new(EventBus::new()));
 
         let (plugin_router, upload_router) =
             if let Some(plugin_upload_provider) = plugin_upload_provider {
@@ -580,6 +638,7 @@ impl<P: Plugin, U: UploadProvider + Send + Sync + 'static> Builder<P, U> {
                     divisions: &mut divisions,
                     rawdb: &rawdb,
                     db: cached_db,
+                    events,
                 };
 
                 let plugin_router = self.plugins.run(&mut plugin_builder).await?;
@@ -596,6 +655,7 @@ impl<P: Plugin, U: UploadProvider + Send + Sync + 'static> Builder<P, U> {
                     divisions: &mut divisions,
                     rawdb: &rawdb,
                     db: cached_db,
+                    events,
                 };
 
                 let plugin_router = self.plugins.run(&mut plugin_builder).await?;
@@ -613,6 +673,7 @@ impl<P: Plugin, U: UploadProvider + Send + Sync + 'static> Builder<P, U> {
                     divisions: &mut divisions,
                     rawdb: &rawdb,
                     db: cached_db,
+                    events,
                 };
 
                 let plugin_router = self.plugins.run(&mut plugin_builder).await?;
@@ -634,6 +695,7 @@ impl<P: Plugin, U: UploadProvider + Send + Sync + 'static> Builder<P, U> {
                     divisions: &mut divisions,
                     rawdb: &rawdb,
                     db: cached_db,
+                    events,
                 };
 
                 let plugin_router = self.plugins.run(&mut plugin_builder).await?;
@@ -669,6 +731,7 @@ impl<P: Plugin, U: UploadProvider + Send + Sync + 'static> Builder<P, U> {
                     divisions: &mut divisions,
                     rawdb: &rawdb,
                     db: cached_db,
+                    events,
                 };
 
                 let plugin_router = self.plugins.run(&mut plugin_builder).await?;
@@ -752,9 +815,14 @@ impl<P: Plugin, U: UploadProvider + Send + Sync + 'static> Builder<P, U> {
                 (None, Router::new())
             };
 
+        let webhooks: &'static _ = Box::leak(Box::new(Webhooks::new(settings, cached_db)));
+        webhooks.listen(events);
+
+        let divisions: &'static Vec<Division> = Box::leak(Box::new(divisions));
+
         let bot = if settings.read().await.discord.is_some() {
             let bot: &'static _ = Box::leak(Box::new(
-                Bot::new(settings, cached_db, outbound_mailer).await,
+                Bot::new(settings, cached_db, outbound_mailer, events, divisions).await,
             ));
             discord_cache_evictor();
             Some(bot)
@@ -776,10 +844,16 @@ impl<P: Plugin, U: UploadProvider + Send + Sync + 'static> Builder<P, U> {
             }
         }
 
-        healthcheck_catch_up(cached_db).await;
-        healthcheck_runner(cached_db);
+        healthcheck_runner(cached_db, events, bot, outbound_mailer);
+        release_runner(cached_db, settings, bot);
+        if let Some(instances) = instances {
+            instance_reaper(instances);
+        }
 
-        cached_db.insert_divisions(&divisions).await?;
+        cached_db.insert_divisions(divisions).await?;
+        if let Some(bot) = bot {
+            division_role_reconciler(bot, divisions);
+        }
 
         let router_state: &RouterStateInner = Box::leak(Box::new(RouterStateInner {
             db: cached_db,
@@ -789,18 +863,86 @@ impl<P: Plugin, U: UploadProvider + Send + Sync + 'static> Builder<P, U> {
             settings,
             ip_extractor: ip_extractor.unwrap_or(default_ip_extractor),
             outbound_mailer,
-            divisions: Box::leak(Box::new(divisions)),
+            divisions,
+            instances,
+            events,
         }));
 
+        let api_router = Router::new()
+            .route("/challenges", get(route_api_challenges))
+            .route("/challenges/:id", get(route_api_challenge))
+            .route("/challenges/:id/submit", post(route_api_challenge_submit))
+            .route(
+                "/challenges/:id/flags",
+                post(route_api_challenge_issue_flag),
+            )
+            .route("/challenges/:id/ticket", post(route_api_challenge_ticket))
+            .route("/team", get(route_api_team))
+            .route("/team/solves", get(route_api_team_solves))
+            .route("/account", get(route_api_account))
+            .route(
+                "/account/emails",
+                get(route_api_account_emails).post(route_api_account_add_email),
+            )
+            .route(
+                "/account/emails/:email",
+                delete(route_api_account_delete_email),
+            )
+            .route_layer(middleware::from_fn(enforce_api_auth_middleware))
+            .route("/divisions", get(route_api_divisions))
+            .route("/scoreboard/:id", get(route_api_scoreboard))
+            .route("/leaderboard/:id", get(route_api_leaderboard))
+            .route("/openapi.json", get(route_api_openapi));
+
         let rhombus_router = Router::new()
             .fallback(handler_404)
-            .route("/admin", get(|| async { (StatusCode::OK, Html("Admin")) }))
+            .route("/admin", get(route_admin))
+            .route("/admin/challenges/new", get(route_admin_challenge_view))
+            .route("/admin/challenges", post(route_admin_challenge_create))
+            .route(
+                "/admin/challenges/:id",
+                get(route_admin_challenge_view)
+                    .post(route_admin_challenge_update)
+                    .delete(route_admin_challenge_delete),
+            )
+            .route(
+                "/admin/challenges/:id/attachments",
+                post(route_admin_attachment_add).delete(route_admin_attachment_delete),
+            )
+            .route("/admin/categories", post(route_admin_category_upsert))
+            .route("/admin/categories/:id", delete(route_admin_category_delete))
+            .route("/admin/authors", post(route_admin_author_upsert))
+            .route("/admin/authors/:id", delete(route_admin_author_delete))
+            .route("/admin/users", get(route_admin_users))
+            .route("/admin/submissions", get(route_admin_submissions))
+            .route("/admin/incidents", get(route_admin_incidents))
+            .route("/admin/health", get(route_admin_health))
+            .route(
+                "/admin/announcements",
+                get(route_admin_announcements).post(route_admin_announcement_create),
+            )
+            .route(
+                "/admin/announcements/:id",
+                post(route_admin_announcement_update).delete(route_admin_announcement_delete),
+            )
+            .route("/admin/users/:id", get(route_admin_user_view))
+            .route(
+                "/admin/users/:id/disabled",
+                post(route_admin_user_set_disabled),
+            )
+            .route("/admin/users/:id/admin", post(route_admin_user_set_admin))
+            .route("/admin/users/:id/team", delete(route_admin_user_leave_team))
             .route_layer(middleware::from_fn(enforce_admin_middleware))
             .route("/account/verify", get(route_account_email_verify_callback))
             .route(
                 "/account/email",
                 post(route_account_add_email).delete(route_account_delete_email),
             )
+            .route("/account/tokens", post(route_account_create_api_token))
+            .route(
+                "/account/tokens/:id",
+                delete(route_account_delete_api_token),
+            )
             .route("/account", get(route_account))
             .route("/team/division/:id", post(route_team_set_division))
             .route("/team/user/:id", delete(route_user_kick))
@@ -812,6 +954,15 @@ impl<P: Plugin, U: UploadProvider + Send + Sync + 'static> Builder<P, U> {
                 "/challenges/:id/writeup",
                 post(route_writeup_submit).delete(route_writeup_delete),
             )
+            .route("/challenges/:id/hints/:hint_id", post(route_hint_unlock))
+            .route(
+                "/challenges/:id/instance",
+                post(route_instance_start).delete(route_instance_stop),
+            )
+            .route(
+                "/challenges/:id/instance/extend",
+                post(route_instance_extend),
+            )
             .route(
                 "/challenges/:id/ticket",
                 get(route_ticket_view).post(route_ticket_submit),
@@ -824,7 +975,11 @@ impl<P: Plugin, U: UploadProvider + Send + Sync + 'static> Builder<P, U> {
             .route("/static/:file", get(route_static_serve))
             .route("/command-palette", get(route_command_palette_items))
             .route("/", get(route_home))
+            .route("/announcements", get(route_announcements))
+            .route("/banner", get(route_banner))
+            .route("/live", get(route_live))
             .merge(mailgun_router)
+            .nest("/api/v1", api_router)
             .route("/signout", get(route_signout))
             .route("/signin/credentials", post(route_signin_credentials))
             .route(
@@ -932,3 +1087,31 @@ pub fn hash(s: impl AsRef<str>) -> i64 {
     let hash_value = hasher.finish();
     (hash_value >> 11) as i64
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use axum::Router;
+
+    use super::builder;
+    use crate::internal::{
+        database::libsql::LocalLibSQL, ip::default_ip_extractor, settings::DbConfig,
+    };
+
+    pub const JWT_SECRET: &str = "secret";
+
+    /// The app without plugins or caching, on top of `database`
+    pub async fn router(database: &LocalLibSQL) -> Router {
+        builder()
+            .config_override("title", "Test")
+            .config_override("location_url", "http://localhost:3000")
+            .config_override("jwt_secret", JWT_SECRET)
+            .config_override("auth", vec!["credentials"])
+            .config_override("live_reload", false)
+            .config_override("in_memory_cache", "false")
+            .extractor(default_ip_extractor)
+            .database(DbConfig::RawLibSQLConnection(database.conn.clone()))
+            .build()
+            .await
+            .unwrap()
+    }
+}
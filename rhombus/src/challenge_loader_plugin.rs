@@ -1,16 +1,15 @@
 use core::panic;
 use std::{
-    collections::BTreeSet,
     fs::{self, ReadDir},
     hash::{BuildHasher, BuildHasherDefault, Hasher},
     path::{Path, PathBuf},
 };
 
 use axum::Router;
+use chrono::{DateTime, Utc};
 use config::Config;
-use futures::StreamExt;
+use fancy_regex::Regex;
 use futures::{TryFutureExt, TryStreamExt};
-use libsql::params;
 use serde::{Deserialize, Serialize};
 use tokio_util::{
     bytes::BytesMut,
@@ -18,7 +17,15 @@ use tokio_util::{
 };
 
 use crate::{
-    internal::{database::libsql::LibSQLConnection, router::RouterState},
+    internal::{
+        database::provider::{
+            AuthorDefinition, CategoryDefinition, ChallengeAttachment, ChallengeDefinition,
+            ChallengeDefinitions, ChallengeHint, ChallengeInstance, ChallengePrerequisite,
+            DynamicScoring, FlagMode, ScoringCurve, ScoringType, ISSUED_FLAG_PLACEHOLDER,
+            TEAM_FLAG_PLACEHOLDER,
+        },
+        router::RouterState,
+    },
     plugin::RunContext,
     Plugin, Result, UploadProvider,
 };
@@ -59,19 +66,99 @@ impl ChallengeLoaderPlugin {
                     name: challenge.name,
                     description: challenge.description,
                     flag: challenge.flag,
+                    flag_mode: challenge.flag_mode,
                     category: challenge.category,
                     author: challenge.author,
                     ticket_template: challenge.ticket_template,
                     points: challenge.points,
+                    dynamic: challenge.dynamic,
                     files: challenge.files,
+                    hints: challenge.hints,
+                    requires: challenge.requires,
+                    instance: challenge.instance,
                     healthscript: challenge.healthscript,
+                    healthcheck_interval: challenge.healthcheck_interval,
+                    release_at: challenge.release_at,
+                    hidden: challenge.hidden,
                     root,
                 }
             })
             .collect::<Vec<_>>();
 
         challenges.iter().for_each(|challenge| {
-            let name = challenge.stable_id.as_ref().unwrap_or(&challenge.name);
+            let name = challenge.key();
+            let (flag, flag_mode) = challenge.flag();
+            if matches!(challenge.flag, ChallengeFlag::Multiple(_))
+                && challenge
+                    .flag_mode
+                    .is_some_and(|flag_mode| flag_mode != FlagMode::Multiple)
+            {
+                panic!(
+                    "A list of flags can only use the multiple flag mode for challenge {}",
+                    name
+                );
+            }
+            if flag_mode == FlagMode::Regex {
+                if let Err(error) = Regex::new(&flag) {
+                    panic!("Invalid flag regex for challenge {}: {}", name, error);
+                }
+            }
+            if challenge.points == "dynamic"
+                && !challenge.dynamic.resolve(&config.dynamic).is_valid()
+            {
+                panic!(
+                    "Invalid dynamic points for challenge {}: initial must be at least minimum, minimum at least 0 and decay at least 1",
+                    name
+                );
+            }
+            if challenge
+                .instance
+                .as_ref()
+                .is_some_and(|instance| instance.timeout <= 0)
+            {
+                panic!("Instance timeout must be at least 1 second for challenge {}", name);
+            }
+            if challenge.hints.iter().any(|hint| hint.cost < 0) {
+                panic!("Negative hint cost for challenge {}", name);
+            }
+            for requirement in &challenge.requires {
+                match requirement {
+                    Requirement::Solve { solve } => {
+                        if !challenges.iter().any(|c| c.key() == solve) {
+                            panic!(
+                                "Required challenge {} not found for challenge {}",
+                                solve, name
+                            );
+                        }
+                    }
+                    Requirement::CategoryPoints { category, points } => {
+                        if *points < 0 {
+                            panic!("Negative required points for challenge {}", name);
+                        }
+                        if !config.categories.iter().any(|c| &c.name == category) {
+                            panic!(
+                                "Required category {} not found for challenge {}",
+                                category, name
+                            );
+                        }
+                    }
+                }
+            }
+            if requires_cycle(&challenges, challenge) {
+                panic!("Challenge {} requires a solve of itself", name);
+            }
+            if flag_mode == FlagMode::PerTeam && !flag.contains(TEAM_FLAG_PLACEHOLDER) {
+                panic!(
+                    "Per team flag for challenge {} is missing the {} placeholder",
+                    name, TEAM_FLAG_PLACEHOLDER
+                );
+            }
+            if flag_mode == FlagMode::Issued && !flag.contains(ISSUED_FLAG_PLACEHOLDER) {
+                panic!(
+                    "Issued flag for challenge {} is missing the {} placeholder",
+                    name, ISSUED_FLAG_PLACEHOLDER
+                );
+            }
             _ = config
                 .categories
                 .iter()
@@ -103,217 +190,164 @@ impl Plugin for ChallengeLoaderPlugin {
         &self,
         context: &mut RunContext<'_, U>,
     ) -> Result<Router<RouterState>> {
-        match context.rawdb {
-            #[allow(unused_labels)]
-            crate::builder::RawDb::Plugin(d) => 'raw: {
-                #[cfg(feature = "mysql")]
-                if let Some(mysql) = d.downcast_ref::<sqlx::MySqlPool>() {
-                    let row: (i64,) = sqlx::query_as("SELECT 3 + 8").fetch_one(mysql).await?;
-                    tracing::info!("Database provider: {}", row.0);
-                    break 'raw;
+        let authors = self
+            .config
+            .authors
+            .iter()
+            .map(|author| {
+                let name = author.stable_id.as_ref().unwrap_or(&author.name);
+                AuthorDefinition {
+                    id: hash(name) as i64,
+                    name: author.name.clone(),
+                    avatar: author.avatar.clone(),
+                    discord_id: author
+                        .discord_id
+                        .as_ref()
+                        .and_then(|discord_id| discord_id.parse().ok())
+                        .unwrap_or_else(|| panic!("Invalid discord id for author {}", name)),
                 }
+            })
+            .collect::<Vec<_>>();
 
-                _ = d;
-                panic!("Unsupported database type for ChallengeLoaderPlugin");
-            }
-
-            #[cfg(feature = "postgres")]
-            crate::builder::RawDb::Postgres(_) => panic!("Postgres not supported"),
-
-            #[cfg(feature = "libsql")]
-            crate::builder::RawDb::LibSQL(db) => {
-                let tx = db.connect()?.transaction().await?;
+        let categories = self
+            .config
+            .categories
+            .iter()
+            .map(|category| CategoryDefinition {
+                id: hash(category.stable_id.as_ref().unwrap_or(&category.name)) as i64,
+                name: category.name.clone(),
+                color: category
+                    .color
+                    .clone()
+                    .unwrap_or(get_color(hash(&category.name) as usize).to_owned()),
+            })
+            .collect::<Vec<_>>();
 
-                let new_challenge_ids = self
-                    .challenges
+        let mut challenges = vec![];
+        for challenge in &self.challenges {
+            let category_id = hash(
+                self.config
+                    .categories
                     .iter()
-                    .map(|challenge| {
-                        hash(challenge.stable_id.as_ref().unwrap_or(&challenge.name)) as i64
-                    })
-                    .collect::<Vec<_>>();
-                let mut challenge_id_rows =
-                    tx.query("SELECT id FROM rhombus_challenge", ()).await?;
-                while let Some(row) = challenge_id_rows.next().await? {
-                    let challenge_id = row.get::<i64>(0).unwrap();
-                    if !new_challenge_ids.contains(&challenge_id) {
-                        tx.execute(
-                            "DELETE FROM rhombus_file_attachment WHERE challenge_id = ?1",
-                            [challenge_id],
-                        )
-                        .await?;
-
-                        tx.execute(
-                            "DELETE FROM rhombus_challenge WHERE id = ?1",
-                            [challenge_id],
-                        )
-                        .await?;
-                    }
-                }
-
-                let new_author_ids = self
-                    .config
+                    .find(|category| category.name == challenge.category)
+                    .unwrap()
+                    .stable_id
+                    .as_ref()
+                    .unwrap_or(&challenge.category),
+            );
+            let author_id = hash(
+                self.config
                     .authors
                     .iter()
-                    .map(|author| hash(author.stable_id.as_ref().unwrap_or(&author.name)) as i64)
-                    .collect::<Vec<_>>();
-                let mut author_id_rows = tx.query("SELECT id FROM rhombus_author", ()).await?;
-                while let Some(row) = author_id_rows.next().await? {
-                    let author_id = row.get::<i64>(0).unwrap();
-                    if !new_author_ids.contains(&author_id) {
-                        tx.execute("DELETE FROM rhombus_author WHERE id = ?1", [author_id])
-                            .await?;
-                    }
-                }
+                    .find(|author| author.name == challenge.author)
+                    .unwrap()
+                    .stable_id
+                    .as_ref()
+                    .unwrap_or(&challenge.author),
+            );
+            let id = hash(challenge.key());
+
+            let (scoring_type, static_points) = if challenge.points == "dynamic" {
+                (ScoringType::Dynamic, None)
+            } else {
+                (
+                    ScoringType::Static,
+                    Some(challenge.points.parse::<i64>().unwrap()),
+                )
+            };
+
+            let mut attachments = vec![];
+            for file in challenge.files.iter() {
+                let url = if let Some(url) = &file.url {
+                    url.to_owned()
+                } else if let Some(src) = &file.src {
+                    let src = challenge.root.join(src);
+
+                    let stream = tokio::fs::File::open(&src)
+                        .map_ok(|file| {
+                            FramedRead::new(file, BytesCodec::new()).map_ok(BytesMut::freeze)
+                        })
+                        .try_flatten_stream();
+
+                    context
+                        .upload_provider
+                        .upload(src.file_name().unwrap().to_str().unwrap(), stream)
+                        .await
+                        .unwrap()
+                } else {
+                    panic!("No URL or source provided for file {}", file.dst);
+                };
+                attachments.push(ChallengeAttachment {
+                    name: file.dst.clone(),
+                    url,
+                });
+            }
 
-                let new_category_ids = self
-                    .config
-                    .categories
+            let (flag, flag_mode) = challenge.flag();
+
+            challenges.push(ChallengeDefinition {
+                id: id as i64,
+                name: challenge.name.clone(),
+                description: challenge.description.clone(),
+                flag,
+                flag_mode,
+                category_id: category_id as i64,
+                author_id: author_id as i64,
+                ticket_template: Some(challenge.ticket_template.clone()),
+                scoring_type,
+                static_points,
+                dynamic_scoring: challenge.dynamic.resolve(&self.config.dynamic),
+                healthscript: challenge.healthscript.clone(),
+                healthcheck_interval: challenge.healthcheck_interval,
+                attachments,
+                hints: challenge
+                    .hints
                     .iter()
-                    .map(|category| {
-                        hash(category.stable_id.as_ref().unwrap_or(&category.name)) as i64
+                    .enumerate()
+                    .map(|(i, hint)| ChallengeHint {
+                        id: i as i64,
+                        content: hint.content.clone(),
+                        cost: hint.cost,
                     })
-                    .collect::<Vec<_>>();
-
-                let mut category_id_rows = tx.query("SELECT id FROM rhombus_category", ()).await?;
-                while let Some(row) = category_id_rows.next().await? {
-                    let category_id = row.get::<i64>(0).unwrap();
-                    if !new_category_ids.contains(&category_id) {
-                        tx.execute("DELETE FROM rhombus_category WHERE id = ?1", [category_id])
-                            .await?;
-                    }
-                }
-
-                for author in &self.config.authors {
-                    let id = hash(author.stable_id.as_ref().unwrap_or(&author.name));
-                    _ = tx
-                        .execute(
-                            "INSERT OR REPLACE INTO rhombus_author (id, name, avatar, discord_id) VALUES (?1, ?2, ?3, ?4)",
-                            params!(id, author.name.as_str(), author.avatar.as_str(), author.discord_id.as_deref()),
-                        )
-                        .await?;
-                }
-
-                for category in &self.config.categories {
-                    let color = category
-                        .color
-                        .as_deref()
-                        .unwrap_or(get_color(hash(&category.name) as usize));
-                    let id = hash(category.stable_id.as_ref().unwrap_or(&category.name));
-                    let _ = tx
-                        .execute(
-                            "INSERT OR REPLACE INTO rhombus_category (id, name, color) VALUES (?1, ?2, ?3)",
-                            params!(id, category.name.as_str(), color),
-                        )
-                        .await?;
-                }
-
-                for challenge in &self.challenges {
-                    let category_id = hash(
-                        self.config
-                            .categories
-                            .iter()
-                            .find(|category| category.name == challenge.category)
-                            .unwrap()
-                            .stable_id
-                            .as_ref()
-                            .unwrap_or(&challenge.category),
-                    );
-                    let author_id = hash(
-                        self.config
-                            .authors
-                            .iter()
-                            .find(|author| author.name == challenge.author)
-                            .unwrap()
-                            .stable_id
-                            .as_ref()
-                            .unwrap_or(&challenge.author),
-                    );
-                    let id = hash(challenge.stable_id.as_ref().unwrap_or(&challenge.name));
-
-                    let (score_type, static_points) = if challenge.points == "dynamic" {
-                        (0, None)
-                    } else {
-                        (1, Some(challenge.points.parse::<i64>().unwrap()))
-                    };
-
-                    _ = tx
-                        .execute(
-                            "
-                            INSERT OR REPLACE INTO rhombus_challenge (id, name, description, flag, category_id, author_id, ticket_template, score_type, static_points, healthscript)
-                            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
-                        ",
-                            params!(
-                                id,
-                                challenge.name.as_str(),
-                                challenge.description.as_str(),
-                                challenge.flag.as_str(),
-                                category_id,
-                                author_id,
-                                challenge.ticket_template.as_str(),
-                                score_type,
-                                static_points,
-                                challenge.healthscript.as_deref()
-                            ),
-                        )
-                        .await?;
-
-                    let mut attachment_urls = tx
-                        .query(
-                            "SELECT url FROM rhombus_file_attachment WHERE challenge_id = ?1",
-                            [id],
-                        )
-                        .await?
-                        .into_stream()
-                        .map(|row| row.unwrap().get::<String>(0).unwrap())
-                        .collect::<BTreeSet<_>>()
-                        .await;
-
-                    for file in challenge.files.iter() {
-                        let url = if let Some(url) = &file.url {
-                            url.to_owned()
-                        } else if let Some(src) = &file.src {
-                            let src = challenge.root.join(src);
-
-                            let stream = tokio::fs::File::open(&src)
-                                .map_ok(|file| {
-                                    FramedRead::new(file, BytesCodec::new())
-                                        .map_ok(BytesMut::freeze)
-                                })
-                                .try_flatten_stream();
-
-                            context
-                                .upload_provider
-                                .upload(src.file_name().unwrap().to_str().unwrap(), stream)
-                                .await
-                                .unwrap()
-                        } else {
-                            panic!("No URL or source provided for file {}", file.dst);
-                        };
-                        let dst = file.dst.clone();
-                        _ = tx
-                            .execute(
-                                "
-                                INSERT OR REPLACE INTO rhombus_file_attachment (challenge_id, name, url)
-                                VALUES (?1, ?2, ?3)
-                            ",
-                                params!(id, dst, url.as_str()),
-                            )
-                            .await?;
-                        attachment_urls.remove(&url);
-                    }
-
-                    for url in attachment_urls {
-                        tx.execute(
-                            "DELETE FROM rhombus_file_attachment WHERE challenge_id = ?1 AND url = ?2",
-                            params!(id, url),
-                        )
-                        .await?;
-                    }
-                }
+                    .collect(),
+                prerequisites: challenge
+                    .requires
+                    .iter()
+                    .map(|requirement| match requirement {
+                        Requirement::Solve { solve } => ChallengePrerequisite::Solve {
+                            challenge_id: hash(solve) as i64,
+                        },
+                        Requirement::CategoryPoints { category, points } => {
+                            let category = self
+                                .config
+                                .categories
+                                .iter()
+                                .find(|c| &c.name == category)
+                                .unwrap();
+                            ChallengePrerequisite::CategoryPoints {
+                                category_id: hash(
+                                    category.stable_id.as_ref().unwrap_or(&category.name),
+                                ) as i64,
+                                points: *points,
+                            }
+                        }
+                    })
+                    .collect(),
+                instance: challenge.instance.clone(),
+                release_at: challenge.release_at,
+                hidden: challenge.hidden,
+                admin_created: false,
+            });
+        }
 
-                tx.commit().await?;
-            }
-        };
+        context
+            .db
+            .sync_challenges(&ChallengeDefinitions {
+                authors,
+                categories,
+                challenges,
+            })
+            .await?;
 
         Ok(Router::new())
     }
@@ -335,18 +369,41 @@ pub fn get_color(hash: usize) -> &'static str {
     colors[hash % colors.len()]
 }
 
+/// Either a single flag, or a list of flags which are all accepted
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(untagged)]
+pub enum ChallengeFlag {
+    Single(String),
+    Multiple(Vec<String>),
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Challenge {
     pub stable_id: Option<String>,
     pub name: String,
     pub description: String,
-    pub flag: String,
+    pub flag: ChallengeFlag,
+    pub flag_mode: Option<FlagMode>,
     pub category: String,
     pub author: String,
     pub ticket_template: String,
     pub points: String,
+    #[serde(default)]
+    pub dynamic: DynamicPoints,
     pub files: Vec<Attachment>,
+    #[serde(default)]
+    pub hints: Vec<Hint>,
+    #[serde(default)]
+    pub requires: Vec<Requirement>,
+    /// Run an isolated instance of the challenge for each team which asks for one
+    pub instance: Option<ChallengeInstance>,
     pub healthscript: Option<String>,
+    /// Seconds between runs of the healthscript. Defaults to 5 minutes.
+    pub healthcheck_interval: Option<i64>,
+    /// Hidden from players until this time, for releasing challenges in waves
+    pub release_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub hidden: bool,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -354,16 +411,93 @@ pub struct ChallengeIntermediate {
     pub stable_id: Option<String>,
     pub name: String,
     pub description: String,
-    pub flag: String,
+    pub flag: ChallengeFlag,
+    pub flag_mode: Option<FlagMode>,
     pub category: String,
     pub author: String,
     pub ticket_template: String,
     pub points: String,
+    pub dynamic: DynamicPoints,
     pub files: Vec<Attachment>,
+    pub hints: Vec<Hint>,
+    pub requires: Vec<Requirement>,
+    pub instance: Option<ChallengeInstance>,
     pub healthscript: Option<String>,
+    pub healthcheck_interval: Option<i64>,
+    pub release_at: Option<DateTime<Utc>>,
+    pub hidden: bool,
     pub root: PathBuf,
 }
 
+impl ChallengeIntermediate {
+    /// The `stable_id`, or the name if there is none. Challenge ids are
+    /// hashed from this, and `requires` references challenges by it.
+    pub fn key(&self) -> &str {
+        self.stable_id.as_ref().unwrap_or(&self.name)
+    }
+
+    /// The flag as stored in the database. A list of flags is always matched
+    /// in [FlagMode::Multiple], one flag per line, and loading rejects a list
+    /// with any other `flag_mode`.
+    pub fn flag(&self) -> (String, FlagMode) {
+        match &self.flag {
+            ChallengeFlag::Single(flag) => (flag.clone(), self.flag_mode.unwrap_or_default()),
+            ChallengeFlag::Multiple(flags) => (flags.join("\n"), FlagMode::Multiple),
+        }
+    }
+}
+
+/// Whether following the solve requirements of `challenge` leads back to it
+fn requires_cycle(challenges: &[ChallengeIntermediate], challenge: &ChallengeIntermediate) -> bool {
+    let mut seen = vec![];
+    let mut stack = vec![challenge.key()];
+    while let Some(key) = stack.pop() {
+        let Some(current) = challenges.iter().find(|c| c.key() == key) else {
+            continue;
+        };
+        for requirement in &current.requires {
+            if let Requirement::Solve { solve } = requirement {
+                if solve == challenge.key() {
+                    return true;
+                }
+                if !seen.contains(&solve.as_str()) {
+                    seen.push(solve.as_str());
+                    stack.push(solve);
+                }
+            }
+        }
+    }
+    false
+}
+
+/// Options for `points: dynamic`. Anything left out falls back to the
+/// `dynamic` section of `loader.yaml`, and then to [DynamicScoring::default].
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct DynamicPoints {
+    pub initial: Option<i64>,
+    pub minimum: Option<i64>,
+    pub decay: Option<i64>,
+    pub curve: Option<ScoringCurve>,
+}
+
+impl DynamicPoints {
+    pub fn resolve(&self, defaults: &DynamicPoints) -> DynamicScoring {
+        let fallback = DynamicScoring::default();
+        DynamicScoring {
+            initial: self
+                .initial
+                .or(defaults.initial)
+                .unwrap_or(fallback.initial),
+            minimum: self
+                .minimum
+                .or(defaults.minimum)
+                .unwrap_or(fallback.minimum),
+            decay: self.decay.or(defaults.decay).unwrap_or(fallback.decay),
+            curve: self.curve.or(defaults.curve).unwrap_or(fallback.curve),
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Attachment {
     pub src: Option<String>,
@@ -371,6 +505,24 @@ pub struct Attachment {
     pub dst: String,
 }
 
+/// A hint teams can unlock. Hints are identified by their position in the
+/// list, so reordering them changes which hints teams have already unlocked.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Hint {
+    pub content: String,
+    #[serde(default)]
+    pub cost: i64,
+}
+
+/// A prerequisite in `requires`. Challenges are referenced by `stable_id`, or
+/// by name if they have none, and categories by name.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(untagged)]
+pub enum Requirement {
+    Solve { solve: String },
+    CategoryPoints { category: String, points: i64 },
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Category {
     pub stable_id: Option<String>,
@@ -390,6 +542,8 @@ pub struct Author {
 pub struct ChallengeLoaderConfiguration {
     pub categories: Vec<Category>,
     pub authors: Vec<Author>,
+    #[serde(default)]
+    pub dynamic: DynamicPoints,
 }
 
 struct ChallengeYamlWalker {
@@ -430,3 +584,64 @@ impl Iterator for ChallengeYamlWalker {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::{requires_cycle, ChallengeFlag, ChallengeIntermediate, DynamicPoints, Requirement};
+
+    fn challenge(name: &str, requires: &[&str]) -> ChallengeIntermediate {
+        ChallengeIntermediate {
+            stable_id: None,
+            name: name.to_owned(),
+            description: String::new(),
+            flag: ChallengeFlag::Single("flag{test}".to_owned()),
+            flag_mode: None,
+            category: "misc".to_owned(),
+            author: "author".to_owned(),
+            ticket_template: String::new(),
+            points: "100".to_owned(),
+            dynamic: DynamicPoints::default(),
+            files: vec![],
+            hints: vec![],
+            requires: requires
+                .iter()
+                .map(|solve| Requirement::Solve {
+                    solve: solve.to_string(),
+                })
+                .collect(),
+            instance: None,
+            healthscript: None,
+            healthcheck_interval: None,
+            release_at: None,
+            hidden: false,
+            root: PathBuf::new(),
+        }
+    }
+
+    #[test]
+    fn requires_cycles() {
+        let challenges = [
+            challenge("a", &["b", "c"]),
+            challenge("b", &["c"]),
+            challenge("c", &[]),
+            challenge("d", &["e"]),
+            challenge("e", &["d"]),
+            challenge("f", &["f"]),
+            challenge("g", &["d"]),
+        ];
+
+        for (index, cycle) in [false, false, false, true, true, true, false]
+            .into_iter()
+            .enumerate()
+        {
+            assert_eq!(
+                cycle,
+                requires_cycle(&challenges, &challenges[index]),
+                "{}",
+                challenges[index].name
+            );
+        }
+    }
+}
@@ -61,6 +61,28 @@ pub enum RhombusError {
 
     #[error("IO error")]
     IO(#[from] std::io::Error),
+
+    #[error("Instance: {0}")]
+    Instance(String),
+}
+
+impl RhombusError {
+    /// Whether a query failed because it would have broken a unique
+    /// constraint, such as setting a team name which is already taken
+    pub fn is_unique_violation(&self) -> bool {
+        match self {
+            #[cfg(feature = "libsql")]
+            RhombusError::LibSQL(
+                libsql::Error::SqliteFailure(_, message)
+                | libsql::Error::RemoteSqliteFailure(_, _, message),
+            ) => message.contains("UNIQUE constraint failed"),
+            #[cfg(feature = "postgres")]
+            RhombusError::Postgres(error) => error
+                .as_database_error()
+                .is_some_and(|error| error.is_unique_violation()),
+            _ => false,
+        }
+    }
 }
 
 #[derive(Error, Debug)]
@@ -0,0 +1,21 @@
+use async_trait::async_trait;
+
+use crate::{internal::database::provider::Challenge, Result};
+
+pub type InstanceProviderRef = &'static (dyn InstanceProvider + Send + Sync);
+
+/// Runs an isolated instance of a challenge for each team which asks for one.
+/// Rhombus keeps track of which instances are running and when they expire,
+/// so a provider only has to start and stop them.
+#[async_trait]
+pub trait InstanceProvider {
+    /// Start an instance of `challenge` for `team_id`, following the challenge's
+    /// [instance](crate::internal::database::provider::ChallengeInstance) spec.
+    /// `flag` is the flag the team has to find. Returns what players use to
+    /// connect to the instance, like `nc localhost 31337` or a URL.
+    async fn start(&self, challenge: &Challenge, team_id: i64, flag: &str) -> Result<String>;
+
+    /// Stop the instance of `challenge_id` for `team_id`, when the team stops
+    /// it or its timeout passes.
+    async fn stop(&self, challenge_id: i64, team_id: i64) -> Result<()>;
+}
@@ -0,0 +1,120 @@
+use crate::{
+    internal::{
+        database::provider::{Announcement, AnnouncementDefinition, Connection},
+        discord::Bot,
+        email::outbound_mailer::OutboundMailer,
+        events::{EventBus, RhombusEvent},
+    },
+    Result,
+};
+
+/// Saves a new announcement and pushes it to every open page. It is also
+/// posted to the Discord announcement channel if `discord` is set, and emailed
+/// to every user with a verified email in the background if `email` is set.
+pub async fn create_announcement(
+    db: Connection,
+    events: &EventBus,
+    bot: Option<&Bot>,
+    outbound_mailer: Option<&'static OutboundMailer>,
+    announcement: &AnnouncementDefinition,
+    discord: bool,
+    email: bool,
+) -> Result<Announcement> {
+    let announcement = db.create_announcement(announcement).await?;
+
+    events.publish(RhombusEvent::AnnouncementCreated {
+        announcement: announcement.clone(),
+    });
+
+    if discord {
+        if let Some(bot) = bot {
+            if let Err(error) = bot.send_announcement(&announcement).await {
+                tracing::error!(
+                    announcement_id = announcement.id,
+                    ?error,
+                    "Failed to post announcement on Discord"
+                );
+            }
+        }
+    }
+
+    if email {
+        if let Some(outbound_mailer) = outbound_mailer {
+            let announcement = announcement.clone();
+            tokio::task::spawn(async move {
+                email_announcement(db, outbound_mailer, &announcement).await;
+            });
+        }
+    }
+
+    Ok(announcement)
+}
+
+async fn email_announcement(
+    db: Connection,
+    outbound_mailer: &OutboundMailer,
+    announcement: &Announcement,
+) {
+    let mailing_list = match db.get_mailing_list().await {
+        Ok(mailing_list) => mailing_list,
+        Err(error) => {
+            tracing::error!(?error, "Failed to get mailing list");
+            return;
+        }
+    };
+
+    tracing::info!(
+        announcement_id = announcement.id,
+        recipients = mailing_list.len(),
+        "Emailing announcement"
+    );
+
+    for to in mailing_list {
+        if let Err(error) = outbound_mailer.send_announcement(&to, announcement).await {
+            tracing::error!(
+                announcement_id = announcement.id,
+                to,
+                ?error,
+                "Failed to email announcement"
+            );
+        }
+    }
+}
+
+pub async fn update_announcement(
+    db: Connection,
+    events: &EventBus,
+    announcement_id: i64,
+    announcement: &AnnouncementDefinition,
+) -> Result<Announcement> {
+    let announcement = db
+        .update_announcement(announcement_id, announcement)
+        .await?;
+
+    events.publish(RhombusEvent::AnnouncementUpdated {
+        announcement: announcement.clone(),
+    });
+
+    Ok(announcement)
+}
+
+pub async fn delete_announcement(
+    db: Connection,
+    events: &EventBus,
+    announcement_id: i64,
+) -> Result<()> {
+    db.delete_announcement(announcement_id).await?;
+
+    events.publish(RhombusEvent::AnnouncementDeleted { announcement_id });
+
+    Ok(())
+}
+
+/// The announcement shown in the banner at the top of every page, which is the
+/// newest one marked as a banner
+pub fn current_banner(announcements: &[Announcement]) -> Option<&Announcement> {
+    // announcements are ordered newest first
+    announcements
+        .iter()
+        .find(|announcement| announcement.banner)
+}
@@ -6,7 +6,7 @@ use axum::{
     extract::{Query, State},
     http::{
         header::{self, AUTHORIZATION},
-        Request, Response, StatusCode, Uri,
+        Method, Request, Response, StatusCode, Uri,
     },
     middleware::Next,
     response::{IntoResponse, Redirect},
@@ -23,7 +23,8 @@ use serde::{Deserialize, Serialize};
 use serde_json::json;
 
 use crate::internal::{
-    discord, division::MaxDivisionPlayers, locales::Languages, router::RouterState,
+    discord, division::MaxDivisionPlayers, events::RhombusEvent, locales::Languages,
+    router::RouterState, routes::account::API_TOKEN_PREFIX,
 };
 
 #[derive(Debug, Serialize, Clone)]
@@ -72,10 +73,38 @@ pub async fn enforce_auth_middleware(
     req: Request<Body>,
     next: Next,
 ) -> Result<impl IntoResponse, impl IntoResponse> {
-    if maybe_user.is_none() || maybe_token_claims.is_none() {
+    if maybe_user.is_none() {
         return Err(Redirect::to("/signin").into_response());
     }
 
+    // api tokens have no token claims, outside of the api router they are only
+    // accepted for reading the json variants of pages
+    if maybe_token_claims.is_none() && !(req.method() == Method::GET && accepts_json(&req)) {
+        return Err(Redirect::to("/signin").into_response());
+    }
+
+    Ok(next.run(req).await)
+}
+
+fn accepts_json(req: &Request<Body>) -> bool {
+    req.headers()
+        .get(header::ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .is_some_and(|accept| accept == "application/json")
+}
+
+pub async fn enforce_api_auth_middleware(
+    Extension(maybe_user): Extension<MaybeUser>,
+    req: Request<Body>,
+    next: Next,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    if maybe_user.is_none() {
+        let json_error = ErrorResponse {
+            message: "Missing or invalid credentials".to_string(),
+        };
+        return Err((StatusCode::UNAUTHORIZED, Json(json_error)).into_response());
+    }
+
     Ok(next.run(req).await)
 }
 
@@ -105,15 +134,33 @@ pub async fn auth_injector_middleware(
                 })
         });
 
-    if let Some(token) = token {
-        if let Ok(token_data) = decode::<TokenClaims>(
-            &token,
-            &DecodingKey::from_secret(state.settings.read().await.jwt_secret.as_ref()),
-            &Validation::default(),
-        ) {
-            req.extensions_mut().insert(Some(token_data.claims.clone()));
-            req.extensions_mut().insert(token_data.claims.clone());
-            if let Ok(user) = state.db.get_user_from_id(token_data.claims.sub).await {
+    let Some(token) = token else {
+        return next.run(req).await;
+    };
+
+    let user_id = if token.starts_with(API_TOKEN_PREFIX) {
+        state
+            .db
+            .get_user_id_from_api_token(&token)
+            .await
+            .ok()
+            .flatten()
+    } else if let Ok(token_data) = decode::<TokenClaims>(
+        &token,
+        &DecodingKey::from_secret(state.settings.read().await.jwt_secret.as_ref()),
+        &Validation::default(),
+    ) {
+        req.extensions_mut().insert(Some(token_data.claims.clone()));
+        req.extensions_mut().insert(token_data.claims.clone());
+        Some(token_data.claims.sub)
+    } else {
+        None
+    };
+
+    if let Some(user_id) = user_id {
+        if let Ok(user) = state.db.get_user_from_id(user_id).await {
+            // disabled users are treated as signed out everywhere
+            if !user.disabled {
                 req.extensions_mut().insert(Some(user.clone()));
                 req.extensions_mut().insert(user);
             }
@@ -181,6 +228,10 @@ pub async fn route_signin(
                 .add_user_to_team(user.id, team.id, Some(user.team_id))
                 .await
                 .unwrap();
+            announce_team_joined(&state, user.id, team.id).await;
+            if let Some(bot) = state.bot {
+                bot.sync_team_division_roles(state.divisions, team.id);
+            }
             return Redirect::to("/team").into_response();
         }
 
@@ -406,7 +457,7 @@ pub async fn route_signin_discord_callback(
         )
     };
 
-    let Ok((user_id, team_id)) = state
+    let Ok((user_id, team_id, created)) = state
         .db
         .upsert_user_by_discord_id(
             &profile.global_name,
@@ -420,6 +471,10 @@ pub async fn route_signin_discord_callback(
         return Redirect::temporary("/signin").into_response();
     };
 
+    if created {
+        announce_team_created(&state, user_id, team_id).await;
+    }
+
     let cookie = sign_in_cookie(&state, user_id, team_id, &cookie_jar).await;
     let mut response = Redirect::temporary("/team").into_response();
     let headers = response.headers_mut();
@@ -522,7 +577,7 @@ pub async fn route_signin_credentials(
         hash
     );
 
-    let Some((user_id, team_id)) = state
+    let Some((user_id, team_id, created)) = state
         .db
         .upsert_user_by_credentials(&form.username, &avatar, &form.password)
         .await
@@ -540,6 +595,28 @@ pub async fn route_signin_credentials(
             .into_response();
     };
 
+    if state
+        .db
+        .get_user_from_id(user_id)
+        .await
+        .is_ok_and(|user| user.disabled)
+    {
+        return Response::builder()
+            .body(format!(
+                r#"<div id="htmx-toaster" data-toast="error" hx-swap-oob="true">{}</div>"#,
+                state
+                    .localizer
+                    .localize(&lang, "account-error-disabled", None)
+                    .unwrap(),
+            ))
+            .unwrap()
+            .into_response();
+    }
+
+    if created {
+        announce_team_created(&state, user_id, team_id).await;
+    }
+
     let cookie = sign_in_cookie(&state, user_id, team_id, &cookie_jar).await;
 
     Response::builder()
@@ -583,11 +660,17 @@ pub async fn route_signin_email_callback(
         hash
     );
 
-    let Ok((user_id, team_id)) = state.db.upsert_user_by_email(name, &email, &avatar).await else {
+    let Ok((user_id, team_id, created)) =
+        state.db.upsert_user_by_email(name, &email, &avatar).await
+    else {
         tracing::info!(email, "failed to upsert user by email");
         return Redirect::temporary("/signin").into_response();
     };
 
+    if created {
+        announce_team_created(&state, user_id, team_id).await;
+    }
+
     let cookie = sign_in_cookie(&state, user_id, team_id, &cookie_jar).await;
     let mut response = Redirect::temporary("/team").into_response();
     let headers = response.headers_mut();
@@ -595,10 +678,30 @@ pub async fn route_signin_email_callback(
     response
 }
 
+async fn announce_team_created(state: &State<RouterState>, user_id: i64, team_id: i64) {
+    let user = state.db.get_user_from_id(user_id);
+    let team = state.db.get_team_from_id(team_id);
+    if let (Ok(user), Ok(team)) = tokio::join!(user, team) {
+        state
+            .events
+            .publish(RhombusEvent::TeamCreated { user, team });
+    }
+}
+
+async fn announce_team_joined(state: &State<RouterState>, user_id: i64, team_id: i64) {
+    let user = state.db.get_user_from_id(user_id);
+    let team = state.db.get_team_from_id(team_id);
+    if let (Ok(user), Ok(team)) = tokio::join!(user, team) {
+        state
+            .events
+            .publish(RhombusEvent::TeamJoined { user, team });
+    }
+}
+
 async fn sign_in_cookie(
     state: &State<RouterState>,
     user_id: i64,
-    team_id: i64,
+    mut team_id: i64,
     cookie_jar: &CookieJar,
 ) -> Cookie<'static> {
     let jwt_secret = {
@@ -639,9 +742,15 @@ async fn sign_in_cookie(
                 .add_user_to_team(user_id, team.id, None)
                 .await
                 .unwrap();
+            announce_team_joined(state, user_id, team.id).await;
+            team_id = team.id;
         }
     }
 
+    if let Some(bot) = state.bot {
+        bot.sync_team_division_roles(state.divisions, team_id);
+    }
+
     let token = encode(
         &Header::default(),
         &claims,
@@ -670,3 +779,49 @@ pub async fn route_signout() -> impl IntoResponse {
         .insert(header::SET_COOKIE, cookie.to_string().parse().unwrap());
     response
 }
+
+#[cfg(test)]
+mod test {
+    use axum::{
+        body::Body,
+        http::{header, Request, StatusCode},
+    };
+    use tower::ServiceExt;
+
+    use crate::{
+        builder::tests::router,
+        internal::database::{libsql::LocalLibSQL, provider::Database},
+    };
+
+    #[tokio::test]
+    async fn api_token_reads_challenges_json() {
+        let database = LocalLibSQL::new_memory().await.unwrap();
+        database.migrate().await.unwrap();
+
+        let (user_id, _, _) = database
+            .upsert_user_by_email("user", "user@example.com", "")
+            .await
+            .unwrap();
+        let token = database.create_api_token(user_id, "test").await.unwrap();
+
+        let router = router(&database).await;
+
+        let request = |accept: &str| {
+            Request::get("/challenges")
+                .header(header::AUTHORIZATION, format!("Bearer {}", token))
+                .header(header::ACCEPT, accept)
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let response = router
+            .clone()
+            .oneshot(request("application/json"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = router.oneshot(request("text/html")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+    }
+}
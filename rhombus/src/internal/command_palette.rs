@@ -1,7 +1,11 @@
 use axum::{extract::State, response::IntoResponse, Extension, Json};
 use serde_json::{json, Value};
 
-use crate::internal::{auth::MaybeUser, router::RouterState};
+use crate::internal::{
+    auth::MaybeUser,
+    router::RouterState,
+    routes::challenges::{challenge_unlocked, challenges_visible},
+};
 
 use super::discord;
 
@@ -17,7 +21,9 @@ pub async fn route_command_palette_items(
         .map(|division| (division.id.to_string(), division.name.clone()))
         .collect::<Value>();
 
-    if user.is_some() {
+    if let Some(user) = &user {
+        let team = state.db.get_team_from_id(user.team_id).await.unwrap();
+        let visible = challenges_visible(&state, user).await;
         let challenges = challenge_data
             .categories
             .iter()
@@ -25,7 +31,12 @@ pub async fn route_command_palette_items(
                 let challenges = challenge_data
                     .challenges
                     .iter()
-                    .filter(|challenge| challenge.category_id == category.id)
+                    .filter(|challenge| {
+                        visible
+                            && challenge.category_id == category.id
+                            && !challenge.hidden
+                            && challenge_unlocked(user, &team, &challenge_data, challenge)
+                    })
                     .map(|challenge| challenge.name.clone())
                     .collect::<Value>();
 
@@ -10,8 +10,11 @@ use crate::{
     internal::{
         auth::User,
         database::provider::{
-            Challenge, ChallengeData, Challenges, Connection, Database, Email, FirstBloods,
-            Leaderboard, Scoreboard, Team, TeamMeta, TeamStandings, Ticket, Writeup,
+            Announcement, AnnouncementDefinition, ApiToken, AuthorDefinition, CategoryDefinition,
+            Challenge, ChallengeData, ChallengeDefinition, ChallengeDefinitions, ChallengeHint,
+            Challenges, CheatingIncidentList, Connection, Database, Email, FirstBloods,
+            Healthcheck, Leaderboard, Scoreboard, SharedWrongFlag, Team, TeamMeta, TeamStandings,
+            Ticket, UserIp, UserList, Writeup, WrongSubmissionList,
         },
         division::Division,
         settings::Settings,
@@ -49,7 +52,7 @@ impl Database for DbCache {
         avatar: &str,
         discord_id: NonZeroU64,
         user_id: Option<i64>,
-    ) -> Result<(i64, i64)> {
+    ) -> Result<(i64, i64, bool)> {
         let result = self
             .inner
             .upsert_user_by_discord_id(name, email, avatar, discord_id, user_id)
@@ -66,7 +69,7 @@ impl Database for DbCache {
         name: &str,
         email: &str,
         avatar: &str,
-    ) -> Result<(i64, i64)> {
+    ) -> Result<(i64, i64, bool)> {
         let result = self.inner.upsert_user_by_email(name, email, avatar).await;
         if let Ok(result) = result {
             USER_CACHE.remove(&result.0);
@@ -80,7 +83,7 @@ impl Database for DbCache {
         username: &str,
         avatar: &str,
         password: &str,
-    ) -> Result<Option<(i64, i64)>> {
+    ) -> Result<Option<(i64, i64, bool)>> {
         let result = self
             .inner
             .upsert_user_by_credentials(username, avatar, password)
@@ -109,6 +112,63 @@ impl Database for DbCache {
         get_challenges(&self.inner).await
     }
 
+    async fn sync_challenges(&self, definitions: &ChallengeDefinitions) -> Result<()> {
+        let result = self.inner.sync_challenges(definitions).await;
+        if result.is_ok() {
+            *CHALLENGES_CACHE.write().await = None;
+        }
+        result
+    }
+
+    async fn upsert_challenge(&self, challenge: &ChallengeDefinition) -> Result<()> {
+        let result = self.inner.upsert_challenge(challenge).await;
+        if result.is_ok() {
+            *CHALLENGES_CACHE.write().await = None;
+        }
+        result
+    }
+
+    async fn delete_challenge(&self, challenge_id: i64) -> Result<()> {
+        let result = self.inner.delete_challenge(challenge_id).await;
+        if result.is_ok() {
+            // solves and writeups of the challenge are gone, which touches nearly every cache
+            clear_all_caches().await;
+        }
+        result
+    }
+
+    async fn upsert_category(&self, category: &CategoryDefinition) -> Result<()> {
+        let result = self.inner.upsert_category(category).await;
+        if result.is_ok() {
+            *CHALLENGES_CACHE.write().await = None;
+        }
+        result
+    }
+
+    async fn delete_category(&self, category_id: i64) -> Result<bool> {
+        let result = self.inner.delete_category(category_id).await;
+        if result.is_ok() {
+            *CHALLENGES_CACHE.write().await = None;
+        }
+        result
+    }
+
+    async fn upsert_author(&self, author: &AuthorDefinition) -> Result<()> {
+        let result = self.inner.upsert_author(author).await;
+        if result.is_ok() {
+            *CHALLENGES_CACHE.write().await = None;
+        }
+        result
+    }
+
+    async fn delete_author(&self, author_id: i64) -> Result<bool> {
+        let result = self.inner.delete_author(author_id).await;
+        if result.is_ok() {
+            *CHALLENGES_CACHE.write().await = None;
+        }
+        result
+    }
+
     async fn set_challenge_health(
         &self,
         challenge_id: i64,
@@ -139,6 +199,22 @@ impl Database for DbCache {
         Ok(())
     }
 
+    async fn add_healthcheck(&self, healthcheck: &Healthcheck) -> Result<()> {
+        self.inner.add_healthcheck(healthcheck).await
+    }
+
+    async fn get_healthchecks(&self, since: DateTime<Utc>) -> Result<Vec<Healthcheck>> {
+        self.inner.get_healthchecks(since).await
+    }
+
+    async fn release_challenges(&self, now: DateTime<Utc>) -> Result<Vec<i64>> {
+        let released = self.inner.release_challenges(now).await?;
+        if !released.is_empty() {
+            *CHALLENGES_CACHE.write().await = None;
+        }
+        Ok(released)
+    }
+
     async fn get_team_meta_from_invite_token(
         &self,
         invite_token: &str,
@@ -189,6 +265,30 @@ impl Database for DbCache {
         result
     }
 
+    async fn get_users(&self, search: Option<&str>, page: u64) -> Result<UserList> {
+        self.inner.get_users(search, page).await
+    }
+
+    async fn get_team_ips(&self, team_id: i64) -> Result<Vec<UserIp>> {
+        self.inner.get_team_ips(team_id).await
+    }
+
+    async fn set_user_disabled(&self, user_id: i64, disabled: bool) -> Result<()> {
+        let result = self.inner.set_user_disabled(user_id, disabled).await;
+        if result.is_ok() {
+            USER_CACHE.remove(&user_id);
+        }
+        result
+    }
+
+    async fn set_user_admin(&self, user_id: i64, is_admin: bool) -> Result<()> {
+        let result = self.inner.set_user_admin(user_id, is_admin).await;
+        if result.is_ok() {
+            USER_CACHE.remove(&user_id);
+        }
+        result
+    }
+
     async fn roll_invite_token(&self, team_id: i64) -> Result<String> {
         let new_invite_token = self.inner.roll_invite_token(team_id).await;
         if new_invite_token.is_ok() {
@@ -220,12 +320,102 @@ impl Database for DbCache {
             TEAM_CACHE.clear();
             SCOREBOARD_CACHE.clear();
             LEADERBOARD_CACHE.clear();
+            DIVISION_SOLVE_COUNTS.clear();
             TEAM_STANDINGS.clear();
             *CHALLENGES_CACHE.write().await = None;
         }
         result
     }
 
+    async fn unlock_hint(
+        &self,
+        user_id: i64,
+        team_id: i64,
+        challenge_id: i64,
+        hint: &ChallengeHint,
+    ) -> Result<()> {
+        let result = self
+            .inner
+            .unlock_hint(user_id, team_id, challenge_id, hint)
+            .await;
+        if result.is_ok() {
+            TEAM_CACHE.remove(&team_id);
+            SCOREBOARD_CACHE.clear();
+            LEADERBOARD_CACHE.clear();
+            DIVISION_SOLVE_COUNTS.clear();
+            TEAM_STANDINGS.clear();
+        }
+        result
+    }
+
+    async fn add_wrong_submission(
+        &self,
+        user_id: i64,
+        team_id: i64,
+        challenge_id: i64,
+        flag: &str,
+    ) -> Result<()> {
+        self.inner
+            .add_wrong_submission(user_id, team_id, challenge_id, flag)
+            .await
+    }
+
+    async fn get_team_wrong_submission_times(
+        &self,
+        team_id: i64,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<(i64, DateTime<Utc>)>> {
+        self.inner
+            .get_team_wrong_submission_times(team_id, since)
+            .await
+    }
+
+    async fn get_wrong_submissions(
+        &self,
+        team_id: Option<i64>,
+        challenge_id: Option<i64>,
+        page: u64,
+    ) -> Result<WrongSubmissionList> {
+        self.inner
+            .get_wrong_submissions(team_id, challenge_id, page)
+            .await
+    }
+
+    async fn get_shared_wrong_flags(&self) -> Result<Vec<SharedWrongFlag>> {
+        self.inner.get_shared_wrong_flags().await
+    }
+
+    async fn get_team_ids(&self) -> Result<Vec<i64>> {
+        self.inner.get_team_ids().await
+    }
+
+    async fn add_cheating_incident(
+        &self,
+        user_id: i64,
+        team_id: i64,
+        flag_team_id: i64,
+        challenge_id: i64,
+        flag: &str,
+    ) -> Result<()> {
+        self.inner
+            .add_cheating_incident(user_id, team_id, flag_team_id, challenge_id, flag)
+            .await
+    }
+
+    async fn get_cheating_incidents(&self, page: u64) -> Result<CheatingIncidentList> {
+        self.inner.get_cheating_incidents(page).await
+    }
+
+    async fn add_issued_flag(&self, challenge_id: i64, team_id: i64, flag: &str) -> Result<()> {
+        self.inner
+            .add_issued_flag(challenge_id, team_id, flag)
+            .await
+    }
+
+    async fn get_issued_flag_team_id(&self, challenge_id: i64, flag: &str) -> Result<Option<i64>> {
+        self.inner.get_issued_flag_team_id(challenge_id, flag).await
+    }
+
     async fn add_writeup(
         &self,
         user_id: i64,
@@ -320,18 +510,31 @@ impl Database for DbCache {
         self.inner.load_settings(settings).await
     }
 
-    async fn get_scoreboard(&self, division_id: i64) -> Result<Scoreboard> {
-        get_scoreboard(&self.inner, division_id).await
+    async fn get_scoreboard(
+        &self,
+        division_id: i64,
+        frozen_at: Option<DateTime<Utc>>,
+    ) -> Result<Scoreboard> {
+        get_scoreboard(&self.inner, division_id, frozen_at).await
     }
 
-    async fn get_leaderboard(&self, division_id: i64, page: Option<u64>) -> Result<Leaderboard> {
-        get_leaderboard(&self.inner, division_id, page).await
+    async fn get_leaderboard(
+        &self,
+        division_id: i64,
+        page: Option<u64>,
+        frozen_at: Option<DateTime<Utc>>,
+    ) -> Result<Leaderboard> {
+        get_leaderboard(&self.inner, division_id, page, frozen_at).await
     }
 
     async fn get_emails_for_user_id(&self, user_id: i64) -> Result<Vec<Email>> {
         get_emails_for_user_id(&self.inner, user_id).await
     }
 
+    async fn get_mailing_list(&self) -> Result<Vec<String>> {
+        self.inner.get_mailing_list().await
+    }
+
     async fn create_email_verification_callback_code(
         &self,
         user_id: i64,
@@ -374,6 +577,22 @@ impl Database for DbCache {
         result
     }
 
+    async fn create_api_token(&self, user_id: i64, name: &str) -> Result<String> {
+        self.inner.create_api_token(user_id, name).await
+    }
+
+    async fn get_api_tokens(&self, user_id: i64) -> Result<Vec<ApiToken>> {
+        self.inner.get_api_tokens(user_id).await
+    }
+
+    async fn delete_api_token(&self, user_id: i64, token_id: i64) -> Result<()> {
+        self.inner.delete_api_token(user_id, token_id).await
+    }
+
+    async fn get_user_id_from_api_token(&self, token: &str) -> Result<Option<i64>> {
+        self.inner.get_user_id_from_api_token(token).await
+    }
+
     async fn get_user_divisions(&self, user_id: i64) -> Result<Vec<i64>> {
         get_user_divisions(&self.inner, user_id).await
     }
@@ -395,6 +614,7 @@ impl Database for DbCache {
             TEAM_STANDINGS.clear();
             SCOREBOARD_CACHE.clear();
             LEADERBOARD_CACHE.clear();
+            DIVISION_SOLVE_COUNTS.clear();
             *CHALLENGES_CACHE.write().await = None;
         }
         result
@@ -424,13 +644,63 @@ impl Database for DbCache {
             TEAM_STANDINGS.clear();
             SCOREBOARD_CACHE.clear();
             LEADERBOARD_CACHE.clear();
+            DIVISION_SOLVE_COUNTS.clear();
             *CHALLENGES_CACHE.write().await = None;
         }
         result
     }
 
-    async fn get_team_standings(&self, team_id: i64) -> Result<TeamStandings> {
-        get_team_standing(&self.inner, team_id).await
+    async fn get_team_standings(
+        &self,
+        team_id: i64,
+        frozen_at: Option<DateTime<Utc>>,
+    ) -> Result<TeamStandings> {
+        get_team_standing(&self.inner, team_id, frozen_at).await
+    }
+
+    async fn get_division_solve_counts(
+        &self,
+        at: DateTime<Utc>,
+    ) -> Result<BTreeMap<(i64, i64), u64>> {
+        get_division_solve_counts(&self.inner, at).await
+    }
+
+    async fn get_announcements(&self) -> Result<Vec<Announcement>> {
+        get_announcements(&self.inner).await
+    }
+
+    async fn create_announcement(
+        &self,
+        announcement: &AnnouncementDefinition,
+    ) -> Result<Announcement> {
+        let result = self.inner.create_announcement(announcement).await;
+        if result.is_ok() {
+            *ANNOUNCEMENTS_CACHE.write().await = None;
+        }
+        result
+    }
+
+    async fn update_announcement(
+        &self,
+        announcement_id: i64,
+        announcement: &AnnouncementDefinition,
+    ) -> Result<Announcement> {
+        let result = self
+            .inner
+            .update_announcement(announcement_id, announcement)
+            .await;
+        if result.is_ok() {
+            *ANNOUNCEMENTS_CACHE.write().await = None;
+        }
+        result
+    }
+
+    async fn delete_announcement(&self, announcement_id: i64) -> Result<()> {
+        let result = self.inner.delete_announcement(announcement_id).await;
+        if result.is_ok() {
+            *ANNOUNCEMENTS_CACHE.write().await = None;
+        }
+        result
     }
 
     async fn upload_file(&self, hash: &str, filename: &str, bytes: &[u8]) -> Result<()> {
@@ -462,6 +732,26 @@ pub async fn get_challenges(db: &Connection) -> Result<Challenges> {
     challenges
 }
 
+lazy_static::lazy_static! {
+    pub static ref ANNOUNCEMENTS_CACHE: RwLock<Option<Vec<Announcement>>> = None.into();
+}
+
+pub async fn get_announcements(db: &Connection) -> Result<Vec<Announcement>> {
+    if let Some(announcements) = &*ANNOUNCEMENTS_CACHE.read().await {
+        return Ok(announcements.clone());
+    }
+    tracing::trace!("cache miss: announcements");
+
+    let announcements = db.get_announcements().await;
+
+    if let Ok(announcements) = &announcements {
+        let mut cache = ANNOUNCEMENTS_CACHE.write().await;
+        *cache = Some(announcements.clone());
+    }
+
+    announcements
+}
+
 lazy_static::lazy_static! {
     pub static ref TEAM_CACHE: DashMap<i64, TimedCache<Team>> = DashMap::new();
 }
@@ -534,45 +824,74 @@ pub async fn get_writeups_from_user_id(db: &Connection, user_id: i64) -> Result<
 }
 
 lazy_static::lazy_static! {
-    pub static ref SCOREBOARD_CACHE: DashMap<i64, Scoreboard> = DashMap::new();
+    pub static ref SCOREBOARD_CACHE: DashMap<(i64, Option<DateTime<Utc>>), Scoreboard> = DashMap::new();
 }
 
-pub async fn get_scoreboard(db: &Connection, division_id: i64) -> Result<Scoreboard> {
-    if let Some(scoreboard) = SCOREBOARD_CACHE.get(&division_id) {
+pub async fn get_scoreboard(
+    db: &Connection,
+    division_id: i64,
+    frozen_at: Option<DateTime<Utc>>,
+) -> Result<Scoreboard> {
+    if let Some(scoreboard) = SCOREBOARD_CACHE.get(&(division_id, frozen_at)) {
         return Ok(scoreboard.clone());
     }
     tracing::trace!(division_id, "cache miss: get_scoreboard");
 
-    let scoreboard = db.get_scoreboard(division_id).await;
+    let scoreboard = db.get_scoreboard(division_id, frozen_at).await;
 
     if let Ok(scoreboard) = &scoreboard {
-        SCOREBOARD_CACHE.insert(division_id, scoreboard.clone());
+        SCOREBOARD_CACHE.insert((division_id, frozen_at), scoreboard.clone());
     }
     scoreboard
 }
 
+/// Division, page and the time the standings are frozen at
+type LeaderboardKey = (i64, Option<u64>, Option<DateTime<Utc>>);
+
 lazy_static::lazy_static! {
-    pub static ref LEADERBOARD_CACHE: DashMap<(i64, Option<u64>), Leaderboard> = DashMap::new();
+    pub static ref LEADERBOARD_CACHE: DashMap<LeaderboardKey, Leaderboard> = DashMap::new();
 }
 
 pub async fn get_leaderboard(
     db: &Connection,
     division_id: i64,
     page: Option<u64>,
+    frozen_at: Option<DateTime<Utc>>,
 ) -> Result<Leaderboard> {
-    if let Some(leaderboard) = LEADERBOARD_CACHE.get(&(division_id, page)) {
+    if let Some(leaderboard) = LEADERBOARD_CACHE.get(&(division_id, page, frozen_at)) {
         return Ok(leaderboard.clone());
     }
     tracing::trace!(division_id, page, "cache miss: get_leaderboard");
 
-    let leaderboard = db.get_leaderboard(division_id, page).await;
+    let leaderboard = db.get_leaderboard(division_id, page, frozen_at).await;
 
     if let Ok(leaderboard) = &leaderboard {
-        LEADERBOARD_CACHE.insert((division_id, page), leaderboard.clone());
+        LEADERBOARD_CACHE.insert((division_id, page, frozen_at), leaderboard.clone());
     }
     leaderboard
 }
 
+lazy_static::lazy_static! {
+    pub static ref DIVISION_SOLVE_COUNTS: DashMap<DateTime<Utc>, BTreeMap<(i64, i64), u64>> = DashMap::new();
+}
+
+pub async fn get_division_solve_counts(
+    db: &Connection,
+    at: DateTime<Utc>,
+) -> Result<BTreeMap<(i64, i64), u64>> {
+    if let Some(solve_counts) = DIVISION_SOLVE_COUNTS.get(&at) {
+        return Ok(solve_counts.clone());
+    }
+    tracing::trace!(%at, "cache miss: get_division_solve_counts");
+
+    let solve_counts = db.get_division_solve_counts(at).await;
+
+    if let Ok(solve_counts) = &solve_counts {
+        DIVISION_SOLVE_COUNTS.insert(at, solve_counts.clone());
+    }
+    solve_counts
+}
+
 lazy_static::lazy_static! {
     pub static ref USER_EMAILS_CACHE: DashMap<i64, TimedCache<Vec<Email>>> = DashMap::new();
 }
@@ -628,19 +947,23 @@ pub async fn get_team_divisions(db: &Connection, team_id: i64) -> Result<Vec<i64
 }
 
 lazy_static::lazy_static! {
-    pub static ref TEAM_STANDINGS: DashMap<i64, TimedCache<TeamStandings>> = DashMap::new();
+    pub static ref TEAM_STANDINGS: DashMap<(i64, Option<DateTime<Utc>>), TimedCache<TeamStandings>> = DashMap::new();
 }
 
-pub async fn get_team_standing(db: &Connection, team_id: i64) -> Result<TeamStandings> {
-    if let Some(standings) = TEAM_STANDINGS.get(&team_id) {
+pub async fn get_team_standing(
+    db: &Connection,
+    team_id: i64,
+    frozen_at: Option<DateTime<Utc>>,
+) -> Result<TeamStandings> {
+    if let Some(standings) = TEAM_STANDINGS.get(&(team_id, frozen_at)) {
         return Ok(standings.value.clone());
     }
     tracing::trace!(team_id, "cache miss: get_team_standing");
 
-    let standings = db.get_team_standings(team_id).await;
+    let standings = db.get_team_standings(team_id, frozen_at).await;
 
     if let Ok(standings) = &standings {
-        TEAM_STANDINGS.insert(team_id, TimedCache::new(standings.clone()));
+        TEAM_STANDINGS.insert((team_id, frozen_at), TimedCache::new(standings.clone()));
     }
     standings
 }
@@ -714,6 +1037,16 @@ pub fn database_cache_evictor(seconds: u64) {
                 tracing::trace!(count, "Evicted leaderboard cache");
             }
 
+            // Division solve counts cache
+            let mut count: i64 = 0;
+            DIVISION_SOLVE_COUNTS.retain(|_, _| {
+                count += 1;
+                false
+            });
+            if count > 0 {
+                tracing::trace!(count, "Evicted division solve counts cache");
+            }
+
             // User emails cache
             let mut count: i64 = 0;
             USER_EMAILS_CACHE.retain(|_, v| {
@@ -773,9 +1106,11 @@ pub async fn clear_all_caches() {
     USER_WRITEUP_CACHE.clear();
     SCOREBOARD_CACHE.clear();
     LEADERBOARD_CACHE.clear();
+    DIVISION_SOLVE_COUNTS.clear();
     USER_EMAILS_CACHE.clear();
     USER_DIVISIONS.clear();
     TEAM_DIVISIONS.clear();
     TEAM_STANDINGS.clear();
     *CHALLENGES_CACHE.write().await = None;
+    *ANNOUNCEMENTS_CACHE.write().await = None;
 }
@@ -1,6 +1,6 @@
 use std::{
-    collections::{btree_map::Entry, BTreeMap},
-    net::IpAddr,
+    collections::{btree_map::Entry, BTreeMap, BTreeSet},
+    net::{IpAddr, Ipv6Addr},
     num::NonZeroU64,
     path::Path,
     sync::Arc,
@@ -24,15 +24,23 @@ use crate::{
         database::{
             cache::Writeups,
             provider::{
-                Author, Category, Challenge, ChallengeAttachment, ChallengeData, ChallengeDivision,
-                ChallengeDivisionPoints, ChallengeSolve, Challenges, Database, Email, FirstBloods,
-                Leaderboard, LeaderboardEntry, Scoreboard, ScoreboardSeriesPoint, ScoreboardTeam,
-                Team, TeamInner, TeamMeta, TeamMetaInner, TeamStandingEntry, TeamStandings,
-                TeamUser, Ticket, Writeup,
+                Announcement, AnnouncementDefinition, ApiToken, Author, AuthorDefinition, Category,
+                CategoryDefinition, Challenge, ChallengeAttachment, ChallengeData,
+                ChallengeDefinition, ChallengeDefinitions, ChallengeDivision,
+                ChallengeDivisionPoints, ChallengeHint, ChallengeInstance, ChallengePrerequisite,
+                ChallengeSolve, Challenges, CheatingIncident, CheatingIncidentList, Database,
+                DynamicScoring, Email, FirstBloods, Healthcheck, Leaderboard, LeaderboardEntry,
+                Scoreboard, ScoreboardSeriesPoint, ScoreboardTeam, SharedWrongFlag, Team,
+                TeamInner, TeamMeta, TeamMetaInner, TeamStandingEntry, TeamStandings, TeamUser,
+                Ticket, UserIp, UserList, UserListEntry, Writeup, WrongSubmission,
+                WrongSubmissionList,
             },
         },
         division::Division,
-        routes::{account::generate_email_callback_code, team::create_team_invite_token},
+        routes::{
+            account::{generate_api_token, generate_email_callback_code, hash_api_token},
+            team::create_team_invite_token,
+        },
         settings::Settings,
     },
     Result,
@@ -149,13 +157,56 @@ struct Migrations;
 
 #[async_trait]
 impl<T: LibSQLConnection + Send + Sync> Database for T {
+    /// Runs the setup, which only creates what is missing, and then every
+    /// numbered migration after it which has not been applied yet. Numbered
+    /// migrations are recorded in `rhombus_migration` so they run only once.
     async fn migrate(&self) -> Result<()> {
-        self.connect()?
-            .execute_batch(
-                std::str::from_utf8(Migrations::get("0001_setup.up.sql").unwrap().data.as_ref())
-                    .unwrap(),
+        let conn = self.connect()?;
+        conn.execute_batch(
+            std::str::from_utf8(Migrations::get("0001_setup.up.sql").unwrap().data.as_ref())
+                .unwrap(),
+        )
+        .await?;
+
+        let applied = conn
+            .query("SELECT version FROM rhombus_migration", ())
+            .await?
+            .into_stream()
+            .map(|row| row.and_then(|row| row.get::<i64>(0)))
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<std::result::Result<BTreeSet<_>, _>>()?;
+
+        let mut migrations = Migrations::iter()
+            .filter_map(|name| {
+                let version = name
+                    .strip_suffix(".up.sql")?
+                    .split_once('_')?
+                    .0
+                    .parse::<i64>()
+                    .ok()?;
+                Some((version, name))
+            })
+            .filter(|(version, _)| *version > 1 && !applied.contains(version))
+            .collect::<Vec<_>>();
+        migrations.sort();
+
+        for (version, name) in migrations {
+            tracing::info!(version, "Applying migration");
+            let tx = conn.transaction().await?;
+            tx.execute_batch(
+                std::str::from_utf8(Migrations::get(&name).unwrap().data.as_ref()).unwrap(),
+            )
+            .await?;
+            tx.execute(
+                "INSERT INTO rhombus_migration (version) VALUES (?1)",
+                [version],
             )
             .await?;
+            tx.commit().await?;
+        }
+
         Ok(())
     }
 
@@ -166,7 +217,7 @@ impl<T: LibSQLConnection + Send + Sync> Database for T {
         avatar: &str,
         discord_id: NonZeroU64,
         user_id: Option<i64>,
-    ) -> Result<(i64, i64)> {
+    ) -> Result<(i64, i64, bool)> {
         let tx = self.connect()?.transaction().await?;
 
         let existing_user = if let Some(user_id) = user_id {
@@ -211,7 +262,7 @@ impl<T: LibSQLConnection + Send + Sync> Database for T {
             .await?;
 
             tx.commit().await?;
-            return Ok(existing_user);
+            return Ok((existing_user.0, existing_user.1, false));
         }
 
         let team_id = create_team(&tx).await?;
@@ -235,7 +286,7 @@ impl<T: LibSQLConnection + Send + Sync> Database for T {
         .await?;
 
         tx.commit().await?;
-        return Ok((user_id, team_id));
+        return Ok((user_id, team_id, true));
     }
 
     async fn upsert_user_by_email(
@@ -243,7 +294,7 @@ impl<T: LibSQLConnection + Send + Sync> Database for T {
         name: &str,
         email: &str,
         avatar: &str,
-    ) -> Result<(i64, i64)> {
+    ) -> Result<(i64, i64, bool)> {
         let tx = self.connect()?.transaction().await?;
 
         let existing_user = tx
@@ -263,7 +314,7 @@ impl<T: LibSQLConnection + Send + Sync> Database for T {
             .map(|row| (row.get::<i64>(0).unwrap(), row.get::<i64>(1).unwrap()));
         if let Some(existing_user) = existing_user {
             tx.commit().await?;
-            return Ok(existing_user);
+            return Ok((existing_user.0, existing_user.1, false));
         }
 
         let team_id = create_team(&tx).await?;
@@ -288,7 +339,7 @@ impl<T: LibSQLConnection + Send + Sync> Database for T {
 
         tx.commit().await?;
 
-        return Ok((user_id, team_id));
+        return Ok((user_id, team_id, true));
     }
 
     async fn upsert_user_by_credentials(
@@ -296,7 +347,7 @@ impl<T: LibSQLConnection + Send + Sync> Database for T {
         username: &str,
         avatar: &str,
         password: &str,
-    ) -> Result<Option<(i64, i64)>> {
+    ) -> Result<Option<(i64, i64, bool)>> {
         let tx = self.connect()?.transaction().await?;
 
         #[derive(Debug, Deserialize)]
@@ -329,7 +380,7 @@ impl<T: LibSQLConnection + Send + Sync> Database for T {
                 .verify_password(password.as_bytes(), &parsed_hash)
                 .is_ok()
             {
-                Ok(Some((existing_user.id, existing_user.team_id)))
+                Ok(Some((existing_user.id, existing_user.team_id, false)))
             } else {
                 Ok(None)
             }
@@ -355,7 +406,7 @@ impl<T: LibSQLConnection + Send + Sync> Database for T {
                 .unwrap();
 
             tx.commit().await?;
-            return Ok(Some((user_id, team_id)));
+            return Ok(Some((user_id, team_id, true)));
         }
     }
 
@@ -472,6 +523,80 @@ impl<T: LibSQLConnection + Send + Sync> Database for T {
             };
         }
 
+        let mut query_hints = tx
+            .query(
+                "
+                SELECT challenge_id, id, content, cost
+                FROM rhombus_challenge_hint
+                ORDER BY challenge_id, id
+            ",
+                (),
+            )
+            .await?;
+        #[derive(Debug, Deserialize)]
+        struct QueryChallengeHint {
+            challenge_id: i64,
+            id: i64,
+            content: String,
+            cost: i64,
+        }
+        let mut hints = BTreeMap::new();
+        while let Some(row) = query_hints.next().await? {
+            let query_hint = de::from_row::<QueryChallengeHint>(&row).unwrap();
+            let hint = ChallengeHint {
+                id: query_hint.id,
+                content: query_hint.content,
+                cost: query_hint.cost,
+            };
+            match hints.get_mut(&query_hint.challenge_id) {
+                None => _ = hints.insert(query_hint.challenge_id, vec![hint]),
+                Some(hs) => hs.push(hint),
+            };
+        }
+
+        let mut query_prerequisites = tx
+            .query(
+                "
+                SELECT challenge_id, required_challenge_id, category_id, points
+                FROM rhombus_challenge_prerequisite
+                ORDER BY challenge_id, id
+            ",
+                (),
+            )
+            .await?;
+        #[derive(Debug, Deserialize)]
+        struct QueryChallengePrerequisite {
+            challenge_id: i64,
+            required_challenge_id: Option<i64>,
+            category_id: Option<i64>,
+            points: Option<i64>,
+        }
+        let mut prerequisites = BTreeMap::new();
+        while let Some(row) = query_prerequisites.next().await? {
+            let query_prerequisite = de::from_row::<QueryChallengePrerequisite>(&row).unwrap();
+            let prerequisite = match query_prerequisite {
+                QueryChallengePrerequisite {
+                    required_challenge_id: Some(challenge_id),
+                    ..
+                } => ChallengePrerequisite::Solve { challenge_id },
+                QueryChallengePrerequisite {
+                    category_id: Some(category_id),
+                    points,
+                    ..
+                } => ChallengePrerequisite::CategoryPoints {
+                    category_id,
+                    points: points.unwrap_or(0),
+                },
+                _ => continue,
+            };
+            match prerequisites.get_mut(&query_prerequisite.challenge_id) {
+                None => {
+                    _ = prerequisites.insert(query_prerequisite.challenge_id, vec![prerequisite])
+                }
+                Some(ps) => ps.push(prerequisite),
+            };
+        }
+
         let challenge_rows = tx
             .query(
                 "
@@ -488,14 +613,26 @@ impl<T: LibSQLConnection + Send + Sync> Database for T {
             id: i64,
             name: String,
             description: String,
+            description_html: String,
             category_id: i64,
             author_id: i64,
             healthy: Option<bool>,
             healthscript: Option<String>,
             last_healthcheck: Option<i64>,
+            healthcheck_interval: Option<i64>,
             flag: String,
+            flag_mode: i64,
             score_type: i64,
+            static_points: Option<i64>,
+            dynamic_initial: i64,
+            dynamic_minimum: i64,
+            dynamic_decay: i64,
+            dynamic_curve: i64,
             ticket_template: Option<String>,
+            instance_spec: Option<String>,
+            instance_timeout: Option<i64>,
+            release_at: Option<i64>,
+            hidden: bool,
         }
         let challenges = challenge_rows
             .into_stream()
@@ -504,6 +641,7 @@ impl<T: LibSQLConnection + Send + Sync> Database for T {
                 id: challenge.id,
                 name: challenge.name,
                 description: challenge.description,
+                description_html: challenge.description_html,
                 category_id: challenge.category_id,
                 author_id: challenge.author_id,
                 healthy: challenge.healthy,
@@ -511,11 +649,30 @@ impl<T: LibSQLConnection + Send + Sync> Database for T {
                 last_healthcheck: challenge
                     .last_healthcheck
                     .map(|t| Utc.timestamp_opt(t, 0).unwrap()),
+                healthcheck_interval: challenge.healthcheck_interval,
                 flag: challenge.flag,
+                flag_mode: challenge.flag_mode.into(),
                 scoring_type: challenge.score_type.into(),
+                static_points: challenge.static_points,
+                dynamic_scoring: DynamicScoring {
+                    initial: challenge.dynamic_initial,
+                    minimum: challenge.dynamic_minimum,
+                    decay: challenge.dynamic_decay,
+                    curve: challenge.dynamic_curve.into(),
+                },
                 division_points: dbps.get(&challenge.id).unwrap_or(&vec![]).to_vec(),
                 ticket_template: challenge.ticket_template,
                 attachments: attachments.get(&challenge.id).unwrap_or(&vec![]).to_vec(),
+                hints: hints.get(&challenge.id).unwrap_or(&vec![]).to_vec(),
+                prerequisites: prerequisites.get(&challenge.id).unwrap_or(&vec![]).to_vec(),
+                instance: challenge
+                    .instance_spec
+                    .zip(challenge.instance_timeout)
+                    .map(|(spec, timeout)| ChallengeInstance { spec, timeout }),
+                release_at: challenge
+                    .release_at
+                    .map(|t| Utc.timestamp_opt(t, 0).unwrap()),
+                hidden: challenge.hidden,
             })
             .collect::<Vec<Challenge>>()
             .await;
@@ -586,6 +743,269 @@ impl<T: LibSQLConnection + Send + Sync> Database for T {
         }))
     }
 
+    async fn sync_challenges(&self, definitions: &ChallengeDefinitions) -> Result<()> {
+        let tx = self.connect()?.transaction().await?;
+
+        let mut challenge_id_rows = tx
+            .query(
+                "SELECT id FROM rhombus_challenge WHERE NOT admin_created",
+                (),
+            )
+            .await?;
+        while let Some(row) = challenge_id_rows.next().await? {
+            let challenge_id = row.get::<i64>(0).unwrap();
+            if !definitions
+                .challenges
+                .iter()
+                .any(|challenge| challenge.id == challenge_id)
+            {
+                tx.execute(
+                    "DELETE FROM rhombus_file_attachment WHERE challenge_id = ?1",
+                    [challenge_id],
+                )
+                .await?;
+
+                tx.execute(
+                    "DELETE FROM rhombus_hint_unlock WHERE challenge_id = ?1",
+                    [challenge_id],
+                )
+                .await?;
+
+                tx.execute(
+                    "DELETE FROM rhombus_challenge_hint WHERE challenge_id = ?1",
+                    [challenge_id],
+                )
+                .await?;
+
+                tx.execute(
+                    "DELETE FROM rhombus_challenge_prerequisite WHERE challenge_id = ?1 OR required_challenge_id = ?1",
+                    [challenge_id],
+                )
+                .await?;
+
+                tx.execute(
+                    "DELETE FROM rhombus_challenge WHERE id = ?1",
+                    [challenge_id],
+                )
+                .await?;
+            }
+        }
+
+        for author in &definitions.authors {
+            upsert_author(&tx, author).await?;
+        }
+
+        for category in &definitions.categories {
+            upsert_category(&tx, category).await?;
+        }
+
+        for challenge in &definitions.challenges {
+            upsert_challenge(&tx, challenge).await?;
+        }
+
+        // after the challenges, so categories and authors are only kept while a
+        // challenge still uses them
+        let mut author_id_rows = tx
+            .query(
+                "
+                SELECT id FROM rhombus_author
+                WHERE NOT EXISTS (
+                    SELECT 1 FROM rhombus_challenge WHERE author_id = rhombus_author.id
+                )
+            ",
+                (),
+            )
+            .await?;
+        while let Some(row) = author_id_rows.next().await? {
+            let author_id = row.get::<i64>(0).unwrap();
+            if !definitions
+                .authors
+                .iter()
+                .any(|author| author.id == author_id)
+            {
+                tx.execute("DELETE FROM rhombus_author WHERE id = ?1", [author_id])
+                    .await?;
+            }
+        }
+
+        let mut category_id_rows = tx
+            .query(
+                "
+                SELECT id FROM rhombus_category
+                WHERE NOT EXISTS (
+                    SELECT 1 FROM rhombus_challenge WHERE category_id = rhombus_category.id
+                )
+            ",
+                (),
+            )
+            .await?;
+        while let Some(row) = category_id_rows.next().await? {
+            let category_id = row.get::<i64>(0).unwrap();
+            if !definitions
+                .categories
+                .iter()
+                .any(|category| category.id == category_id)
+            {
+                tx.execute(
+                    "DELETE FROM rhombus_challenge_prerequisite WHERE category_id = ?1",
+                    [category_id],
+                )
+                .await?;
+
+                tx.execute("DELETE FROM rhombus_category WHERE id = ?1", [category_id])
+                    .await?;
+            }
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn upsert_challenge(&self, challenge: &ChallengeDefinition) -> Result<()> {
+        let tx = self.connect()?.transaction().await?;
+        upsert_challenge(&tx, challenge).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn delete_challenge(&self, challenge_id: i64) -> Result<()> {
+        let tx = self.connect()?.transaction().await?;
+
+        tx.execute(
+            "DELETE FROM rhombus_file_attachment WHERE challenge_id = ?1",
+            [challenge_id],
+        )
+        .await?;
+
+        tx.execute(
+            "DELETE FROM rhombus_solve WHERE challenge_id = ?1",
+            [challenge_id],
+        )
+        .await?;
+
+        tx.execute(
+            "DELETE FROM rhombus_wrong_submission WHERE challenge_id = ?1",
+            [challenge_id],
+        )
+        .await?;
+
+        tx.execute(
+            "DELETE FROM rhombus_cheating_incident WHERE challenge_id = ?1",
+            [challenge_id],
+        )
+        .await?;
+
+        tx.execute(
+            "DELETE FROM rhombus_issued_flag WHERE challenge_id = ?1",
+            [challenge_id],
+        )
+        .await?;
+
+        tx.execute(
+            "DELETE FROM rhombus_challenge_hint WHERE challenge_id = ?1",
+            [challenge_id],
+        )
+        .await?;
+
+        tx.execute(
+            "DELETE FROM rhombus_hint_unlock WHERE challenge_id = ?1",
+            [challenge_id],
+        )
+        .await?;
+
+        tx.execute(
+            "DELETE FROM rhombus_challenge_prerequisite WHERE challenge_id = ?1 OR required_challenge_id = ?1",
+            [challenge_id],
+        )
+        .await?;
+
+        tx.execute(
+            "DELETE FROM rhombus_writeup WHERE challenge_id = ?1",
+            [challenge_id],
+        )
+        .await?;
+
+        tx.execute(
+            "DELETE FROM rhombus_healthcheck WHERE challenge_id = ?1",
+            [challenge_id],
+        )
+        .await?;
+
+        tx.execute(
+            "DELETE FROM rhombus_challenge WHERE id = ?1",
+            [challenge_id],
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn upsert_category(&self, category: &CategoryDefinition) -> Result<()> {
+        let tx = self.connect()?.transaction().await?;
+        upsert_category(&tx, category).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn delete_category(&self, category_id: i64) -> Result<bool> {
+        let tx = self.connect()?.transaction().await?;
+
+        let deleted = tx
+            .execute(
+                "
+                DELETE FROM rhombus_category
+                WHERE id = ?1 AND NOT EXISTS (
+                    SELECT 1 FROM rhombus_challenge WHERE category_id = ?1
+                )
+            ",
+                [category_id],
+            )
+            .await?
+            > 0;
+
+        if deleted {
+            tx.execute(
+                "DELETE FROM rhombus_challenge_prerequisite WHERE category_id = ?1",
+                [category_id],
+            )
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(deleted)
+    }
+
+    async fn upsert_author(&self, author: &AuthorDefinition) -> Result<()> {
+        let tx = self.connect()?.transaction().await?;
+        upsert_author(&tx, author).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn delete_author(&self, author_id: i64) -> Result<bool> {
+        let deleted = self
+            .connect()?
+            .execute(
+                "
+                DELETE FROM rhombus_author
+                WHERE id = ?1 AND NOT EXISTS (
+                    SELECT 1 FROM rhombus_challenge WHERE author_id = ?1
+                )
+            ",
+                [author_id],
+            )
+            .await?;
+
+        Ok(deleted > 0)
+    }
+
     async fn set_challenge_health(
         &self,
         challenge_id: i64,
@@ -602,6 +1022,86 @@ impl<T: LibSQLConnection + Send + Sync> Database for T {
         Ok(())
     }
 
+    async fn add_healthcheck(&self, healthcheck: &Healthcheck) -> Result<()> {
+        self.connect()?
+            .execute(
+                "
+                INSERT INTO rhombus_healthcheck (challenge_id, checked_at, healthy, latency, error)
+                VALUES (?1, ?2, ?3, ?4, ?5)
+                ",
+                params!(
+                    healthcheck.challenge_id,
+                    healthcheck.checked_at.timestamp(),
+                    healthcheck.healthy,
+                    healthcheck.latency,
+                    healthcheck.error.as_deref()
+                ),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_healthchecks(&self, since: DateTime<Utc>) -> Result<Vec<Healthcheck>> {
+        #[derive(Debug, Deserialize)]
+        struct DbHealthcheck {
+            challenge_id: i64,
+            checked_at: i64,
+            healthy: Option<bool>,
+            latency: i64,
+            error: Option<String>,
+        }
+
+        let healthchecks = self
+            .connect()?
+            .query(
+                "
+                SELECT challenge_id, checked_at, healthy, latency, error
+                FROM rhombus_healthcheck
+                WHERE checked_at >= ?1
+                ORDER BY checked_at, id
+                ",
+                [since.timestamp()],
+            )
+            .await?
+            .into_stream()
+            .map(|row| {
+                let healthcheck = de::from_row::<DbHealthcheck>(&row.unwrap()).unwrap();
+                Healthcheck {
+                    challenge_id: healthcheck.challenge_id,
+                    checked_at: Utc.timestamp_opt(healthcheck.checked_at, 0).unwrap(),
+                    healthy: healthcheck.healthy,
+                    latency: healthcheck.latency,
+                    error: healthcheck.error,
+                }
+            })
+            .collect::<Vec<_>>()
+            .await;
+
+        Ok(healthchecks)
+    }
+
+    async fn release_challenges(&self, now: DateTime<Utc>) -> Result<Vec<i64>> {
+        let released = self
+            .connect()?
+            .query(
+                "
+                UPDATE rhombus_challenge
+                SET released = TRUE
+                WHERE NOT released AND release_at <= ?1
+                RETURNING id
+                ",
+                [now.timestamp()],
+            )
+            .await?
+            .into_stream()
+            .map(|row| row.unwrap().get::<i64>(0).unwrap())
+            .collect::<Vec<_>>()
+            .await;
+
+        Ok(released)
+    }
+
     async fn get_team_meta_from_invite_token(
         &self,
         invite_token: &str,
@@ -745,6 +1245,27 @@ impl<T: LibSQLConnection + Send + Sync> Database for T {
             };
         }
 
+        let mut query_hint_unlocks = tx
+            .query(
+                "
+                SELECT challenge_id, hint_id
+                FROM rhombus_hint_unlock
+                WHERE team_id = ?1
+                ORDER BY hint_id
+            ",
+                [team_id],
+            )
+            .await?;
+        let mut unlocked_hints = BTreeMap::new();
+        while let Some(row) = query_hint_unlocks.next().await? {
+            let challenge_id = row.get::<i64>(0).unwrap();
+            let hint_id = row.get::<i64>(1).unwrap();
+            match unlocked_hints.get_mut(&challenge_id) {
+                None => _ = unlocked_hints.insert(challenge_id, vec![hint_id]),
+                Some(hs) => hs.push(hint_id),
+            };
+        }
+
         tx.commit().await?;
 
         Ok(Arc::new(TeamInner {
@@ -754,6 +1275,7 @@ impl<T: LibSQLConnection + Send + Sync> Database for T {
             users,
             solves,
             writeups,
+            unlocked_hints,
         }))
     }
 
@@ -858,6 +1380,137 @@ impl<T: LibSQLConnection + Send + Sync> Database for T {
         Ok(())
     }
 
+    async fn get_users(&self, search: Option<&str>, page: u64) -> Result<UserList> {
+        #[derive(Debug, Deserialize)]
+        struct DbUserListEntry {
+            id: i64,
+            name: String,
+            avatar: String,
+            discord_id: Option<NonZeroU64>,
+            team_id: i64,
+            owner_team_id: i64,
+            team_name: String,
+            disabled: bool,
+            is_admin: bool,
+        }
+
+        let search = search.unwrap_or("").to_lowercase();
+
+        let tx = self.connect()?.transaction().await?;
+
+        let num_users = tx
+            .query(
+                "
+                SELECT COUNT(*)
+                FROM rhombus_user
+                JOIN rhombus_team ON rhombus_user.team_id = rhombus_team.id
+                WHERE instr(lower(rhombus_user.name), ?1) > 0
+                    OR instr(lower(rhombus_team.name), ?1) > 0
+            ",
+                [search.as_str()],
+            )
+            .await?
+            .next()
+            .await?
+            .unwrap()
+            .get::<u64>(0)
+            .unwrap();
+
+        const PAGE_SIZE: u64 = 50;
+
+        let num_pages = (num_users + (PAGE_SIZE - 1)) / PAGE_SIZE;
+
+        let users = tx
+            .query(
+                "
+                SELECT rhombus_user.id, rhombus_user.name, avatar, discord_id, team_id,
+                    owner_team_id, rhombus_team.name AS team_name, disabled, is_admin
+                FROM rhombus_user
+                JOIN rhombus_team ON rhombus_user.team_id = rhombus_team.id
+                WHERE instr(lower(rhombus_user.name), ?1) > 0
+                    OR instr(lower(rhombus_team.name), ?1) > 0
+                ORDER BY rhombus_user.id
+                LIMIT ?3 OFFSET ?2
+            ",
+                params!(search.as_str(), page * PAGE_SIZE, PAGE_SIZE),
+            )
+            .await?
+            .into_stream()
+            .map(|row| {
+                let user = de::from_row::<DbUserListEntry>(&row.unwrap()).unwrap();
+                UserListEntry {
+                    id: user.id,
+                    name: user.name,
+                    avatar_url: user.avatar,
+                    discord_id: user.discord_id,
+                    team_id: user.team_id,
+                    team_name: user.team_name,
+                    is_team_owner: user.team_id == user.owner_team_id,
+                    disabled: user.disabled,
+                    is_admin: user.is_admin,
+                }
+            })
+            .collect::<Vec<_>>()
+            .await;
+
+        tx.commit().await?;
+
+        Ok(UserList { num_pages, users })
+    }
+
+    async fn get_team_ips(&self, team_id: i64) -> Result<Vec<UserIp>> {
+        let ips = self
+            .connect()?
+            .query(
+                "
+                SELECT rhombus_user.id, ip, user_agent, last_seen_at, requests
+                FROM rhombus_track_ip
+                JOIN rhombus_track ON rhombus_track_ip.track_id = rhombus_track.id
+                JOIN rhombus_user ON rhombus_track_ip.user_id = rhombus_user.id
+                WHERE rhombus_user.team_id = ?1
+                ORDER BY last_seen_at DESC
+                ",
+                [team_id],
+            )
+            .await?
+            .into_stream()
+            .map(|row| {
+                let row = row.unwrap();
+                let ip: [u8; 16] = row.get::<Vec<u8>>(1).unwrap().try_into().unwrap();
+                UserIp {
+                    user_id: row.get(0).unwrap(),
+                    ip: Ipv6Addr::from(ip).to_canonical(),
+                    user_agent: row.get(2).unwrap(),
+                    last_seen_at: Utc.timestamp_opt(row.get(3).unwrap(), 0).unwrap(),
+                    requests: row.get(4).unwrap(),
+                }
+            })
+            .collect::<Vec<_>>()
+            .await;
+
+        Ok(ips)
+    }
+
+    async fn set_user_disabled(&self, user_id: i64, disabled: bool) -> Result<()> {
+        self.connect()?
+            .execute(
+                "UPDATE rhombus_user SET disabled = ?2 WHERE id = ?1",
+                params!(user_id, disabled),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn set_user_admin(&self, user_id: i64, is_admin: bool) -> Result<()> {
+        self.connect()?
+            .execute(
+                "UPDATE rhombus_user SET is_admin = ?2 WHERE id = ?1",
+                params!(user_id, is_admin),
+            )
+            .await?;
+        Ok(())
+    }
+
     async fn roll_invite_token(&self, team_id: i64) -> Result<String> {
         let new_invite_token = create_team_invite_token();
 
@@ -891,6 +1544,21 @@ impl<T: LibSQLConnection + Send + Sync> Database for T {
 
         let now = chrono::Utc::now().timestamp();
 
+        let first_team_solve = tx
+            .query(
+                "
+                SELECT 1
+                FROM rhombus_solve
+                JOIN rhombus_user ON rhombus_solve.user_id = rhombus_user.id
+                WHERE rhombus_solve.challenge_id = ?1 AND rhombus_user.team_id = ?2
+            ",
+                [challenge.id, team_id],
+            )
+            .await?
+            .next()
+            .await?
+            .is_none();
+
         tx.execute(
             "INSERT INTO rhombus_solve (challenge_id, user_id, solved_at) VALUES (?1, ?2, ?3)",
             [challenge.id, user_id, now],
@@ -907,7 +1575,11 @@ impl<T: LibSQLConnection + Send + Sync> Database for T {
         )
         .await?;
 
-        let first_blood_division_ids = tx
+        // a teammate solving it again is never a first blood
+        let first_blood_division_ids = if !first_team_solve {
+            vec![]
+        } else {
+            tx
             .query(
                 "
                 SELECT rhombus_team_division.division_id
@@ -923,315 +1595,439 @@ impl<T: LibSQLConnection + Send + Sync> Database for T {
             .into_stream()
             .map(|row| row.unwrap().get::<i64>(0).unwrap())
             .collect::<Vec<_>>()
-            .await;
+            .await
+        };
 
         tx.commit().await?;
 
         Ok(FirstBloods {
             division_ids: first_blood_division_ids,
+            first_team_solve,
         })
     }
 
-    async fn add_writeup(
+    async fn unlock_hint(
         &self,
         user_id: i64,
-        _team_id: i64,
+        team_id: i64,
         challenge_id: i64,
-        writeup_url: &str,
+        hint: &ChallengeHint,
     ) -> Result<()> {
-        self.connect()?
+        let tx = self.connect()?.transaction().await?;
+
+        let now = chrono::Utc::now().timestamp();
+
+        let unlocked = tx
             .execute(
                 "
-                INSERT INTO rhombus_writeup (user_id, challenge_id, url) VALUES (?1, ?2, ?3)
+                INSERT INTO rhombus_hint_unlock (team_id, challenge_id, hint_id, user_id, cost, unlocked_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                ON CONFLICT (team_id, challenge_id, hint_id) DO NOTHING
             ",
-                params!(user_id, challenge_id, writeup_url),
+                [team_id, challenge_id, hint.id, user_id, hint.cost, now],
             )
             .await?;
 
-        Ok(())
-    }
-
-    async fn get_writeups_from_user_id(&self, user_id: i64) -> Result<Writeups> {
-        #[derive(Debug, Deserialize)]
-        struct QueryWriteup {
-            pub user_id: i64,
-            pub challenge_id: i64,
-            pub url: String,
-        }
-        let mut db_writeups = self
-            .connect()?
-            .query(
+        if unlocked > 0 && hint.cost != 0 {
+            tx.execute(
                 "
-                SELECT user_id, challenge_id, url
-                FROM rhombus_writeup
-                WHERE user_id = ?1
-            ",
-                [user_id],
+                INSERT OR REPLACE INTO rhombus_points_snapshot
+                SELECT team_id, division_id, ?1, points
+                FROM rhombus_team_division_points
+                WHERE team_id = ?2
+                ",
+                [now, team_id],
             )
             .await?;
-        let mut writeups = BTreeMap::new();
-        while let Some(writeup) = db_writeups.next().await? {
-            let writeup = de::from_row::<QueryWriteup>(&writeup).unwrap();
-            writeups.insert(
-                writeup.challenge_id,
-                Writeup {
-                    url: writeup.url,
-                    user_id: writeup.user_id,
-                },
-            );
         }
-        Ok(writeups)
+
+        tx.commit().await?;
+
+        Ok(())
     }
 
-    async fn delete_writeup(&self, challenge_id: i64, user_id: i64, _team_id: i64) -> Result<()> {
+    async fn add_wrong_submission(
+        &self,
+        user_id: i64,
+        team_id: i64,
+        challenge_id: i64,
+        flag: &str,
+    ) -> Result<()> {
         self.connect()?
             .execute(
                 "
-                DELETE FROM rhombus_writeup
-                WHERE challenge_id = ?1 AND user_id = ?2
+                INSERT INTO rhombus_wrong_submission (challenge_id, user_id, team_id, flag)
+                VALUES (?1, ?2, ?3, ?4)
             ",
-                [challenge_id, user_id],
+                params!(challenge_id, user_id, team_id, flag),
             )
             .await?;
 
         Ok(())
     }
 
-    async fn get_next_ticket_number(&self) -> Result<u64> {
-        let ticket_number = self
+    async fn get_team_wrong_submission_times(
+        &self,
+        team_id: i64,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<(i64, DateTime<Utc>)>> {
+        let times = self
             .connect()?
             .query(
                 "
-                UPDATE rhombus_ticket_number_counter
-                SET ticket_number = ticket_number + 1
-                WHERE rowid = 1
-                RETURNING ticket_number
+                SELECT challenge_id, submitted_at
+                FROM rhombus_wrong_submission
+                WHERE team_id = ?1 AND submitted_at >= ?2
+                ORDER BY submitted_at
             ",
-                (),
+                [team_id, since.timestamp()],
             )
             .await?
-            .next()
-            .await?
-            .unwrap()
-            .get::<u64>(0)
-            .unwrap();
+            .into_stream()
+            .map(|row| {
+                let row = row.unwrap();
+                (
+                    row.get::<i64>(0).unwrap(),
+                    DateTime::<Utc>::from_timestamp(row.get::<i64>(1).unwrap(), 0).unwrap(),
+                )
+            })
+            .collect::<Vec<_>>()
+            .await;
 
-        Ok(ticket_number)
+        Ok(times)
     }
 
-    async fn create_ticket(
+    async fn get_wrong_submissions(
         &self,
-        ticket_number: u64,
-        user_id: i64,
-        challenge_id: i64,
-        discord_channel_id: NonZeroU64,
-    ) -> Result<()> {
-        self
-            .connect()?
-            .execute(
-                "
-                INSERT INTO rhombus_ticket (ticket_number, user_id, challenge_id, discord_channel_id) VALUES (?1, ?2, ?3, ?4)
-            ",
-                params!(ticket_number, user_id, challenge_id, discord_channel_id.get()),
-            )
-            .await?;
-
-        Ok(())
-    }
-
-    async fn get_ticket_by_ticket_number(&self, ticket_number: u64) -> Result<Ticket> {
+        team_id: Option<i64>,
+        challenge_id: Option<i64>,
+        page: u64,
+    ) -> Result<WrongSubmissionList> {
         #[derive(Debug, Deserialize)]
-        struct DbTicket {
-            pub user_id: i64,
-            pub challenge_id: i64,
-            pub closed_at: Option<i64>,
-            pub discord_channel_id: NonZeroU64,
+        struct DbWrongSubmission {
+            user_id: i64,
+            user_name: String,
+            team_id: i64,
+            team_name: String,
+            challenge_id: i64,
+            flag: String,
+            submitted_at: i64,
         }
 
         let tx = self.connect()?.transaction().await?;
 
-        let ticket_row = tx
+        let num_submissions = tx
             .query(
                 "
-                SELECT user_id, challenge_id, closed_at, discord_channel_id
-                FROM rhombus_ticket
-                WHERE ticket_number = ?1
+                SELECT COUNT(*)
+                FROM rhombus_wrong_submission
+                WHERE (?1 IS NULL OR team_id = ?1) AND (?2 IS NULL OR challenge_id = ?2)
             ",
-                [ticket_number],
+                params!(team_id, challenge_id),
             )
             .await?
             .next()
-            .await?;
+            .await?
+            .unwrap()
+            .get::<u64>(0)
+            .unwrap();
 
-        let db_ticket = de::from_row::<DbTicket>(&ticket_row.unwrap()).unwrap();
+        const PAGE_SIZE: u64 = 50;
 
-        #[derive(Debug, Deserialize)]
-        struct DbTicketEmailMessageIdReference {
-            pub message_id: String,
-            pub user_sent: bool,
-        }
+        let num_pages = (num_submissions + (PAGE_SIZE - 1)) / PAGE_SIZE;
 
-        let email_references = tx
+        let submissions = tx
             .query(
                 "
-                SELECT message_id, user_sent
-                FROM rhombus_ticket_email_message_id_reference
-                WHERE ticket_number = ?1
+                SELECT user_id, rhombus_user.name AS user_name, rhombus_wrong_submission.team_id,
+                    rhombus_team.name AS team_name, challenge_id, flag, submitted_at
+                FROM rhombus_wrong_submission
+                JOIN rhombus_user ON rhombus_wrong_submission.user_id = rhombus_user.id
+                JOIN rhombus_team ON rhombus_wrong_submission.team_id = rhombus_team.id
+                WHERE (?1 IS NULL OR rhombus_wrong_submission.team_id = ?1)
+                    AND (?2 IS NULL OR challenge_id = ?2)
+                ORDER BY rhombus_wrong_submission.id DESC
+                LIMIT ?4 OFFSET ?3
             ",
-                [ticket_number],
+                params!(team_id, challenge_id, page * PAGE_SIZE, PAGE_SIZE),
             )
             .await?
             .into_stream()
-            .map(|row| de::from_row::<DbTicketEmailMessageIdReference>(&row.unwrap()).unwrap())
+            .map(|row| {
+                let submission = de::from_row::<DbWrongSubmission>(&row.unwrap()).unwrap();
+                WrongSubmission {
+                    user_id: submission.user_id,
+                    user_name: submission.user_name,
+                    team_id: submission.team_id,
+                    team_name: submission.team_name,
+                    challenge_id: submission.challenge_id,
+                    flag: submission.flag,
+                    submitted_at: DateTime::<Utc>::from_timestamp(submission.submitted_at, 0)
+                        .unwrap(),
+                }
+            })
             .collect::<Vec<_>>()
             .await;
 
         tx.commit().await?;
 
-        Ok(Ticket {
-            ticket_number,
-            user_id: db_ticket.user_id,
-            challenge_id: db_ticket.challenge_id,
-            closed_at: db_ticket
-                .closed_at
-                .map(|ts| DateTime::<Utc>::from_timestamp(ts, 0).unwrap()),
-            discord_channel_id: db_ticket.discord_channel_id,
-            email_in_reply_to: email_references
-                .iter()
-                .find(|r| !r.user_sent)
-                .map(|r| r.message_id.clone()),
-            email_references: email_references.into_iter().map(|r| r.message_id).collect(),
+        Ok(WrongSubmissionList {
+            num_pages,
+            submissions,
         })
     }
 
-    async fn get_ticket_by_discord_channel_id(
-        &self,
-        discord_channel_id: NonZeroU64,
-    ) -> Result<Ticket> {
-        #[derive(Debug, Deserialize)]
-        struct DbTicket {
-            pub ticket_number: u64,
-            pub user_id: i64,
-            pub challenge_id: i64,
-            pub closed_at: Option<i64>,
-            pub discord_channel_id: NonZeroU64,
-        }
-
-        let tx = self.connect()?.transaction().await?;
-
-        let ticket_row = self
+    async fn get_shared_wrong_flags(&self) -> Result<Vec<SharedWrongFlag>> {
+        let mut rows = self
             .connect()?
             .query(
                 "
-                SELECT ticket_number, user_id, challenge_id, closed_at, discord_channel_id
-                FROM rhombus_ticket
-                WHERE discord_channel_id = ?1
+                SELECT DISTINCT submission.challenge_id, submission.flag, submission.team_id,
+                    rhombus_team.name
+                FROM rhombus_wrong_submission AS submission
+                JOIN rhombus_team ON submission.team_id = rhombus_team.id
+                WHERE EXISTS (
+                    SELECT 1
+                    FROM rhombus_wrong_submission AS other
+                    WHERE other.challenge_id = submission.challenge_id
+                        AND other.flag = submission.flag
+                        AND other.team_id != submission.team_id
+                )
+                ORDER BY submission.challenge_id, submission.flag
             ",
-                [discord_channel_id.get()],
+                (),
             )
+            .await?;
+
+        let mut shared_flags: Vec<SharedWrongFlag> = vec![];
+        while let Some(row) = rows.next().await? {
+            let challenge_id = row.get::<i64>(0).unwrap();
+            let flag = row.get::<String>(1).unwrap();
+            let team_id = row.get::<i64>(2).unwrap();
+            let team_name = row.get::<String>(3).unwrap();
+
+            match shared_flags.last_mut() {
+                Some(last) if last.challenge_id == challenge_id && last.flag == flag => {
+                    last.teams.insert(team_id, team_name);
+                }
+                _ => shared_flags.push(SharedWrongFlag {
+                    challenge_id,
+                    flag,
+                    teams: BTreeMap::from([(team_id, team_name)]),
+                }),
+            }
+        }
+
+        Ok(shared_flags)
+    }
+
+    async fn get_team_ids(&self) -> Result<Vec<i64>> {
+        let team_ids = self
+            .connect()?
+            .query("SELECT id FROM rhombus_team", ())
             .await?
-            .next()
+            .into_stream()
+            .map(|row| row.unwrap().get::<i64>(0).unwrap())
+            .collect::<Vec<_>>()
+            .await;
+
+        Ok(team_ids)
+    }
+
+    async fn add_cheating_incident(
+        &self,
+        user_id: i64,
+        team_id: i64,
+        flag_team_id: i64,
+        challenge_id: i64,
+        flag: &str,
+    ) -> Result<()> {
+        self.connect()?
+            .execute(
+                "
+                INSERT INTO rhombus_cheating_incident (challenge_id, user_id, team_id, flag_team_id, flag)
+                VALUES (?1, ?2, ?3, ?4, ?5)
+            ",
+                params!(challenge_id, user_id, team_id, flag_team_id, flag),
+            )
             .await?;
 
-        let db_ticket = de::from_row::<DbTicket>(&ticket_row.unwrap()).unwrap();
+        Ok(())
+    }
+
+    async fn add_issued_flag(&self, challenge_id: i64, team_id: i64, flag: &str) -> Result<()> {
+        self.connect()?
+            .execute(
+                "INSERT INTO rhombus_issued_flag (challenge_id, team_id, flag) VALUES (?1, ?2, ?3)",
+                params!(challenge_id, team_id, flag),
+            )
+            .await?;
+
+        Ok(())
+    }
 
+    async fn get_issued_flag_team_id(&self, challenge_id: i64, flag: &str) -> Result<Option<i64>> {
+        let team_id = self
+            .connect()?
+            .query(
+                "SELECT team_id FROM rhombus_issued_flag WHERE challenge_id = ?1 AND flag = ?2",
+                params!(challenge_id, flag),
+            )
+            .await?
+            .next()
+            .await?
+            .map(|row| row.get::<i64>(0).unwrap());
+
+        Ok(team_id)
+    }
+
+    async fn get_cheating_incidents(&self, page: u64) -> Result<CheatingIncidentList> {
         #[derive(Debug, Deserialize)]
-        struct DbTicketEmailMessageIdReference {
-            pub message_id: String,
-            pub user_sent: bool,
+        struct DbCheatingIncident {
+            user_id: i64,
+            user_name: String,
+            team_id: i64,
+            team_name: String,
+            flag_team_id: i64,
+            flag_team_name: String,
+            challenge_id: i64,
+            flag: String,
+            created_at: i64,
         }
 
-        let email_references = tx
+        let tx = self.connect()?.transaction().await?;
+
+        let num_incidents = tx
+            .query("SELECT COUNT(*) FROM rhombus_cheating_incident", ())
+            .await?
+            .next()
+            .await?
+            .unwrap()
+            .get::<u64>(0)
+            .unwrap();
+
+        const PAGE_SIZE: u64 = 50;
+
+        let num_pages = (num_incidents + (PAGE_SIZE - 1)) / PAGE_SIZE;
+
+        let incidents = tx
             .query(
                 "
-                SELECT message_id, user_sent
-                FROM rhombus_ticket_email_message_id_reference
-                WHERE ticket_number = ?1
+                SELECT user_id, rhombus_user.name AS user_name, rhombus_cheating_incident.team_id,
+                    team.name AS team_name, flag_team_id, flag_team.name AS flag_team_name,
+                    challenge_id, flag, created_at
+                FROM rhombus_cheating_incident
+                JOIN rhombus_user ON rhombus_cheating_incident.user_id = rhombus_user.id
+                JOIN rhombus_team AS team ON rhombus_cheating_incident.team_id = team.id
+                JOIN rhombus_team AS flag_team ON flag_team_id = flag_team.id
+                ORDER BY rhombus_cheating_incident.id DESC
+                LIMIT ?2 OFFSET ?1
             ",
-                [db_ticket.ticket_number],
+                [page * PAGE_SIZE, PAGE_SIZE],
             )
             .await?
             .into_stream()
-            .map(|row| de::from_row::<DbTicketEmailMessageIdReference>(&row.unwrap()).unwrap())
+            .map(|row| {
+                let incident = de::from_row::<DbCheatingIncident>(&row.unwrap()).unwrap();
+                CheatingIncident {
+                    user_id: incident.user_id,
+                    user_name: incident.user_name,
+                    team_id: incident.team_id,
+                    team_name: incident.team_name,
+                    flag_team_id: incident.flag_team_id,
+                    flag_team_name: incident.flag_team_name,
+                    challenge_id: incident.challenge_id,
+                    flag: incident.flag,
+                    created_at: DateTime::<Utc>::from_timestamp(incident.created_at, 0).unwrap(),
+                }
+            })
             .collect::<Vec<_>>()
             .await;
 
         tx.commit().await?;
 
-        Ok(Ticket {
-            ticket_number: db_ticket.ticket_number,
-            user_id: db_ticket.user_id,
-            challenge_id: db_ticket.challenge_id,
-            closed_at: db_ticket
-                .closed_at
-                .map(|ts| DateTime::<Utc>::from_timestamp(ts, 0).unwrap()),
-            discord_channel_id: db_ticket.discord_channel_id,
-            email_in_reply_to: email_references
-                .iter()
-                .find(|r| !r.user_sent)
-                .map(|r| r.message_id.clone()),
-            email_references: email_references.into_iter().map(|r| r.message_id).collect(),
+        Ok(CheatingIncidentList {
+            num_pages,
+            incidents,
         })
     }
 
-    async fn close_ticket(&self, ticket_number: u64, time: DateTime<Utc>) -> Result<()> {
+    async fn add_writeup(
+        &self,
+        user_id: i64,
+        _team_id: i64,
+        challenge_id: i64,
+        writeup_url: &str,
+    ) -> Result<()> {
         self.connect()?
             .execute(
                 "
-                UPDATE rhombus_ticket
-                SET closed_at = ?1
-                WHERE ticket_number = ?2
+                INSERT INTO rhombus_writeup (user_id, challenge_id, url) VALUES (?1, ?2, ?3)
             ",
-                params!(time.timestamp(), ticket_number),
+                params!(user_id, challenge_id, writeup_url),
             )
             .await?;
 
         Ok(())
     }
 
-    async fn reopen_ticket(&self, ticket_number: u64) -> Result<()> {
-        self.connect()?
-            .execute(
+    async fn get_writeups_from_user_id(&self, user_id: i64) -> Result<Writeups> {
+        #[derive(Debug, Deserialize)]
+        struct QueryWriteup {
+            pub user_id: i64,
+            pub challenge_id: i64,
+            pub url: String,
+        }
+        let mut db_writeups = self
+            .connect()?
+            .query(
                 "
-                UPDATE rhombus_ticket
-                SET closed_at = NULL
-                WHERE ticket_number = ?1
+                SELECT user_id, challenge_id, url
+                FROM rhombus_writeup
+                WHERE user_id = ?1
             ",
-                [ticket_number],
+                [user_id],
             )
             .await?;
-
-        Ok(())
+        let mut writeups = BTreeMap::new();
+        while let Some(writeup) = db_writeups.next().await? {
+            let writeup = de::from_row::<QueryWriteup>(&writeup).unwrap();
+            writeups.insert(
+                writeup.challenge_id,
+                Writeup {
+                    url: writeup.url,
+                    user_id: writeup.user_id,
+                },
+            );
+        }
+        Ok(writeups)
     }
 
-    async fn add_email_message_id_to_ticket(
-        &self,
-        ticket_number: u64,
-        message_id: &str,
-        user_sent: bool,
-    ) -> Result<()> {
+    async fn delete_writeup(&self, challenge_id: i64, user_id: i64, _team_id: i64) -> Result<()> {
         self.connect()?
             .execute(
                 "
-                INSERT INTO rhombus_ticket_email_message_id_reference (ticket_number, message_id, user_sent) VALUES (?1, ?2, ?3)
+                DELETE FROM rhombus_writeup
+                WHERE challenge_id = ?1 AND user_id = ?2
             ",
-                params!(ticket_number, message_id, user_sent),
+                [challenge_id, user_id],
             )
             .await?;
 
         Ok(())
     }
 
-    async fn get_ticket_number_by_message_id(&self, message_id: &str) -> Result<u64> {
+    async fn get_next_ticket_number(&self) -> Result<u64> {
         let ticket_number = self
             .connect()?
             .query(
                 "
-                SELECT ticket_number
-                FROM rhombus_ticket_email_message_id_reference
-                WHERE message_id = ?1
+                UPDATE rhombus_ticket_number_counter
+                SET ticket_number = ticket_number + 1
+                WHERE rowid = 1
+                RETURNING ticket_number
             ",
-                [message_id],
+                (),
             )
             .await?
             .next()
@@ -1243,88 +2039,319 @@ impl<T: LibSQLConnection + Send + Sync> Database for T {
         Ok(ticket_number)
     }
 
-    async fn save_settings(&self, settings: &Settings) -> Result<()> {
-        if settings.immutable_config {
-            return Ok(());
-        }
-
-        let json = serde_json::to_string(settings).unwrap();
-
-        self.connect()?
+    async fn create_ticket(
+        &self,
+        ticket_number: u64,
+        user_id: i64,
+        challenge_id: i64,
+        discord_channel_id: NonZeroU64,
+    ) -> Result<()> {
+        self
+            .connect()?
             .execute(
                 "
-                INSERT OR REPLACE INTO rhombus_config (id, config) VALUES (1, ?1)
+                INSERT INTO rhombus_ticket (ticket_number, user_id, challenge_id, discord_channel_id) VALUES (?1, ?2, ?3, ?4)
             ",
-                [json],
+                params!(ticket_number, user_id, challenge_id, discord_channel_id.get()),
             )
             .await?;
 
         Ok(())
     }
 
-    async fn load_settings(&self, _settings: &mut Settings) -> Result<()> {
-        // if settings.immutable_config {
-        //     return Ok(());
-        // }
-
-        // let db_settings: Option<Settings> = self
-        //     .db
-        //     .query("SELECT config FROM rhombus_config", ())
-        //     .await?
-        //     .next()
-        //     .await?
-        //     .map(|row| serde_json::from_str(&row.get::<String>(0).unwrap()).unwrap());
-
-        // if let Some(db_settings) = db_settings {
-        //     if settings.discord.first_blood_channel_id.is_none() {
-        //         settings.discord.first_blood_channel_id =
-        //             db_settings.discord.first_blood_channel_id;
-        //     }
-
-        //     if settings.discord.support_channel_id.is_none() {
-        //         settings.discord.support_channel_id = db_settings.discord.support_channel_id;
-        //     }
-
-        //     if settings.discord.author_role_id.is_none() {
-        //         settings.discord.author_role_id = db_settings.discord.author_role_id;
-        //     }
-        // }
-
-        Ok(())
-    }
-
-    async fn get_scoreboard(&self, division_id: i64) -> Result<Scoreboard> {
-        let tx = self.connect()?.transaction().await?;
-
+    async fn get_ticket_by_ticket_number(&self, ticket_number: u64) -> Result<Ticket> {
         #[derive(Debug, Deserialize)]
-        struct DbTeam {
-            id: i64,
-            name: String,
+        struct DbTicket {
+            pub user_id: i64,
+            pub challenge_id: i64,
+            pub closed_at: Option<i64>,
+            pub discord_channel_id: NonZeroU64,
         }
 
-        let mut db_teams = tx.query("SELECT id, name FROM rhombus_team", ()).await?;
-        let mut all_teams = BTreeMap::new();
-        while let Some(row) = db_teams.next().await? {
-            let team = de::from_row::<DbTeam>(&row).unwrap();
-            all_teams.insert(team.id, team.name);
-        }
+        let tx = self.connect()?.transaction().await?;
 
-        let top_team_ids = tx
+        let ticket_row = tx
             .query(
                 "
-                SELECT team_id
-                FROM rhombus_team_division_points
-                WHERE division_id = ?1
-                ORDER BY points DESC
-                LIMIT 10
+                SELECT user_id, challenge_id, closed_at, discord_channel_id
+                FROM rhombus_ticket
+                WHERE ticket_number = ?1
             ",
-                [division_id],
+                [ticket_number],
             )
             .await?
-            .into_stream()
-            .map(|row| row.unwrap().get::<i64>(0).unwrap())
-            .collect::<Vec<_>>()
-            .await;
+            .next()
+            .await?;
+
+        let db_ticket = de::from_row::<DbTicket>(&ticket_row.unwrap()).unwrap();
+
+        #[derive(Debug, Deserialize)]
+        struct DbTicketEmailMessageIdReference {
+            pub message_id: String,
+            pub user_sent: bool,
+        }
+
+        let email_references = tx
+            .query(
+                "
+                SELECT message_id, user_sent
+                FROM rhombus_ticket_email_message_id_reference
+                WHERE ticket_number = ?1
+            ",
+                [ticket_number],
+            )
+            .await?
+            .into_stream()
+            .map(|row| de::from_row::<DbTicketEmailMessageIdReference>(&row.unwrap()).unwrap())
+            .collect::<Vec<_>>()
+            .await;
+
+        tx.commit().await?;
+
+        Ok(Ticket {
+            ticket_number,
+            user_id: db_ticket.user_id,
+            challenge_id: db_ticket.challenge_id,
+            closed_at: db_ticket
+                .closed_at
+                .map(|ts| DateTime::<Utc>::from_timestamp(ts, 0).unwrap()),
+            discord_channel_id: db_ticket.discord_channel_id,
+            email_in_reply_to: email_references
+                .iter()
+                .find(|r| !r.user_sent)
+                .map(|r| r.message_id.clone()),
+            email_references: email_references.into_iter().map(|r| r.message_id).collect(),
+        })
+    }
+
+    async fn get_ticket_by_discord_channel_id(
+        &self,
+        discord_channel_id: NonZeroU64,
+    ) -> Result<Ticket> {
+        #[derive(Debug, Deserialize)]
+        struct DbTicket {
+            pub ticket_number: u64,
+            pub user_id: i64,
+            pub challenge_id: i64,
+            pub closed_at: Option<i64>,
+            pub discord_channel_id: NonZeroU64,
+        }
+
+        let tx = self.connect()?.transaction().await?;
+
+        let ticket_row = self
+            .connect()?
+            .query(
+                "
+                SELECT ticket_number, user_id, challenge_id, closed_at, discord_channel_id
+                FROM rhombus_ticket
+                WHERE discord_channel_id = ?1
+            ",
+                [discord_channel_id.get()],
+            )
+            .await?
+            .next()
+            .await?;
+
+        let db_ticket = de::from_row::<DbTicket>(&ticket_row.unwrap()).unwrap();
+
+        #[derive(Debug, Deserialize)]
+        struct DbTicketEmailMessageIdReference {
+            pub message_id: String,
+            pub user_sent: bool,
+        }
+
+        let email_references = tx
+            .query(
+                "
+                SELECT message_id, user_sent
+                FROM rhombus_ticket_email_message_id_reference
+                WHERE ticket_number = ?1
+            ",
+                [db_ticket.ticket_number],
+            )
+            .await?
+            .into_stream()
+            .map(|row| de::from_row::<DbTicketEmailMessageIdReference>(&row.unwrap()).unwrap())
+            .collect::<Vec<_>>()
+            .await;
+
+        tx.commit().await?;
+
+        Ok(Ticket {
+            ticket_number: db_ticket.ticket_number,
+            user_id: db_ticket.user_id,
+            challenge_id: db_ticket.challenge_id,
+            closed_at: db_ticket
+                .closed_at
+                .map(|ts| DateTime::<Utc>::from_timestamp(ts, 0).unwrap()),
+            discord_channel_id: db_ticket.discord_channel_id,
+            email_in_reply_to: email_references
+                .iter()
+                .find(|r| !r.user_sent)
+                .map(|r| r.message_id.clone()),
+            email_references: email_references.into_iter().map(|r| r.message_id).collect(),
+        })
+    }
+
+    async fn close_ticket(&self, ticket_number: u64, time: DateTime<Utc>) -> Result<()> {
+        self.connect()?
+            .execute(
+                "
+                UPDATE rhombus_ticket
+                SET closed_at = ?1
+                WHERE ticket_number = ?2
+            ",
+                params!(time.timestamp(), ticket_number),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn reopen_ticket(&self, ticket_number: u64) -> Result<()> {
+        self.connect()?
+            .execute(
+                "
+                UPDATE rhombus_ticket
+                SET closed_at = NULL
+                WHERE ticket_number = ?1
+            ",
+                [ticket_number],
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn add_email_message_id_to_ticket(
+        &self,
+        ticket_number: u64,
+        message_id: &str,
+        user_sent: bool,
+    ) -> Result<()> {
+        self.connect()?
+            .execute(
+                "
+                INSERT INTO rhombus_ticket_email_message_id_reference (ticket_number, message_id, user_sent) VALUES (?1, ?2, ?3)
+            ",
+                params!(ticket_number, message_id, user_sent),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_ticket_number_by_message_id(&self, message_id: &str) -> Result<u64> {
+        let ticket_number = self
+            .connect()?
+            .query(
+                "
+                SELECT ticket_number
+                FROM rhombus_ticket_email_message_id_reference
+                WHERE message_id = ?1
+            ",
+                [message_id],
+            )
+            .await?
+            .next()
+            .await?
+            .unwrap()
+            .get::<u64>(0)
+            .unwrap();
+
+        Ok(ticket_number)
+    }
+
+    async fn save_settings(&self, settings: &Settings) -> Result<()> {
+        if settings.immutable_config {
+            return Ok(());
+        }
+
+        let json = serde_json::to_string(settings).unwrap();
+
+        self.connect()?
+            .execute(
+                "
+                INSERT OR REPLACE INTO rhombus_config (id, config) VALUES (1, ?1)
+            ",
+                [json],
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn load_settings(&self, _settings: &mut Settings) -> Result<()> {
+        // if settings.immutable_config {
+        //     return Ok(());
+        // }
+
+        // let db_settings: Option<Settings> = self
+        //     .db
+        //     .query("SELECT config FROM rhombus_config", ())
+        //     .await?
+        //     .next()
+        //     .await?
+        //     .map(|row| serde_json::from_str(&row.get::<String>(0).unwrap()).unwrap());
+
+        // if let Some(db_settings) = db_settings {
+        //     if settings.discord.first_blood_channel_id.is_none() {
+        //         settings.discord.first_blood_channel_id =
+        //             db_settings.discord.first_blood_channel_id;
+        //     }
+
+        //     if settings.discord.support_channel_id.is_none() {
+        //         settings.discord.support_channel_id = db_settings.discord.support_channel_id;
+        //     }
+
+        //     if settings.discord.author_role_id.is_none() {
+        //         settings.discord.author_role_id = db_settings.discord.author_role_id;
+        //     }
+        // }
+
+        Ok(())
+    }
+
+    async fn get_scoreboard(
+        &self,
+        division_id: i64,
+        frozen_at: Option<DateTime<Utc>>,
+    ) -> Result<Scoreboard> {
+        let tx = self.connect()?.transaction().await?;
+
+        #[derive(Debug, Deserialize)]
+        struct DbTeam {
+            id: i64,
+            name: String,
+        }
+
+        let mut db_teams = tx.query("SELECT id, name FROM rhombus_team", ()).await?;
+        let mut all_teams = BTreeMap::new();
+        while let Some(row) = db_teams.next().await? {
+            let team = de::from_row::<DbTeam>(&row).unwrap();
+            all_teams.insert(team.id, team.name);
+        }
+
+        let top_team_ids = tx
+            .query(
+                &format!(
+                    "
+                SELECT team_id
+                FROM {}
+                WHERE division_id = ?1
+                ORDER BY points DESC
+                LIMIT 10
+            ",
+                    team_division_points_source(frozen_at)
+                ),
+                [division_id],
+            )
+            .await?
+            .into_stream()
+            .map(|row| row.unwrap().get::<i64>(0).unwrap())
+            .collect::<Vec<_>>()
+            .await;
 
         if top_team_ids.is_empty() {
             return Ok(Scoreboard {
@@ -1332,554 +2359,1606 @@ impl<T: LibSQLConnection + Send + Sync> Database for T {
             });
         }
 
-        #[derive(Debug, Deserialize)]
-        struct DbScoreboard {
-            team_id: i64,
-            at: i64,
-            points: i64,
-        }
+        #[derive(Debug, Deserialize)]
+        struct DbScoreboard {
+            team_id: i64,
+            at: i64,
+            points: i64,
+        }
+
+        let mut db_scoreboard = tx
+            .query(
+                "
+                SELECT team_id, at, points
+                FROM rhombus_points_snapshot
+                WHERE division_id = ?1 AND team_id in (?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+                    AND at <= ?12
+                ORDER BY at ASC
+            ",
+                params!(
+                    division_id,
+                    top_team_ids[0],
+                    top_team_ids.get(1).unwrap_or(&top_team_ids[0]),
+                    top_team_ids.get(2).unwrap_or(&top_team_ids[0]),
+                    top_team_ids.get(3).unwrap_or(&top_team_ids[0]),
+                    top_team_ids.get(4).unwrap_or(&top_team_ids[0]),
+                    top_team_ids.get(5).unwrap_or(&top_team_ids[0]),
+                    top_team_ids.get(6).unwrap_or(&top_team_ids[0]),
+                    top_team_ids.get(7).unwrap_or(&top_team_ids[0]),
+                    top_team_ids.get(8).unwrap_or(&top_team_ids[0]),
+                    top_team_ids.get(9).unwrap_or(&top_team_ids[0]),
+                    frozen_at.map_or(i64::MAX, |frozen_at| frozen_at.timestamp()),
+                ),
+            )
+            .await?;
+
+        let mut teams: BTreeMap<i64, ScoreboardTeam> = BTreeMap::new();
+        while let Some(row) = db_scoreboard.next().await? {
+            let scoreboard = de::from_row::<DbScoreboard>(&row).unwrap();
+            let series_point = ScoreboardSeriesPoint {
+                timestamp: scoreboard.at,
+                total_score: scoreboard.points,
+            };
+
+            match teams.entry(scoreboard.team_id) {
+                Entry::Vacant(entry) => {
+                    entry.insert(ScoreboardTeam {
+                        team_name: all_teams[&scoreboard.team_id].clone(),
+                        series: vec![series_point],
+                    });
+                }
+                Entry::Occupied(mut entry) => {
+                    entry.get_mut().series.push(series_point);
+                }
+            }
+        }
+
+        Ok(Scoreboard { teams })
+    }
+
+    async fn get_leaderboard(
+        &self,
+        division_id: i64,
+        page: Option<u64>,
+        frozen_at: Option<DateTime<Utc>>,
+    ) -> Result<Leaderboard> {
+        let source = team_division_points_source(frozen_at);
+
+        #[derive(Debug, Deserialize)]
+        struct DbLeaderboard {
+            team_id: i64,
+            name: String,
+            points: f64,
+        }
+
+        if let Some(page) = page {
+            let tx = self.connect()?.transaction().await?;
+
+            let num_teams = tx
+                .query(
+                    &format!(
+                        "
+            SELECT COUNT(*)
+            FROM {}
+            WHERE division_id = ?1
+        ",
+                        source
+                    ),
+                    [division_id],
+                )
+                .await?
+                .next()
+                .await?
+                .unwrap()
+                .get::<u64>(0)
+                .unwrap();
+
+            const PAGE_SIZE: u64 = 25;
+
+            let num_pages = (num_teams + (PAGE_SIZE - 1)) / PAGE_SIZE;
+
+            let page = page.min(num_pages);
+
+            let mut rank = page * PAGE_SIZE;
+
+            let leaderboard_entries = tx
+                .query(
+                    &format!(
+                        "
+                SELECT team_id, name, points
+                FROM {}
+                JOIN rhombus_team ON rhombus_team_division_points.team_id = rhombus_team.id
+                WHERE division_id = ?1
+                ORDER BY points DESC
+                LIMIT ?3 OFFSET ?2
+            ",
+                        source
+                    ),
+                    params!(division_id, page * PAGE_SIZE, PAGE_SIZE),
+                )
+                .await?
+                .into_stream()
+                .map(|row| {
+                    let db_leaderboard = de::from_row::<DbLeaderboard>(&row.unwrap()).unwrap();
+                    rank += 1;
+                    LeaderboardEntry {
+                        rank,
+                        team_id: db_leaderboard.team_id,
+                        team_name: db_leaderboard.name,
+                        score: db_leaderboard.points.round() as i64,
+                    }
+                })
+                .collect::<Vec<_>>()
+                .await;
+
+            tx.commit().await?;
+
+            Ok(Leaderboard {
+                entries: leaderboard_entries,
+                num_pages,
+            })
+        } else {
+            let mut rank = 0;
+
+            let leaderboard_entries = self
+                .connect()?
+                .query(
+                    &format!(
+                        "
+                SELECT team_id, name, points
+                FROM {}
+                JOIN rhombus_team ON rhombus_team_division_points.team_id = rhombus_team.id
+                WHERE division_id = ?1
+                ORDER BY points DESC
+            ",
+                        source
+                    ),
+                    params!(division_id),
+                )
+                .await?
+                .into_stream()
+                .map(|row| {
+                    let db_leaderboard = de::from_row::<DbLeaderboard>(&row.unwrap()).unwrap();
+                    rank += 1;
+                    LeaderboardEntry {
+                        rank,
+                        team_id: db_leaderboard.team_id,
+                        team_name: db_leaderboard.name,
+                        score: db_leaderboard.points.round() as i64,
+                    }
+                })
+                .collect::<Vec<_>>()
+                .await;
+
+            Ok(Leaderboard {
+                entries: leaderboard_entries,
+                num_pages: 1,
+            })
+        }
+    }
+
+    async fn get_emails_for_user_id(&self, user_id: i64) -> Result<Vec<Email>> {
+        #[derive(Debug, Deserialize)]
+        struct QueryEmail {
+            email: String,
+            code: Option<String>,
+        }
+
+        let emails = self
+            .connect()?
+            .query(
+                "SELECT email, code FROM rhombus_email WHERE user_id = ?1",
+                [user_id],
+            )
+            .await?
+            .into_stream()
+            .map(|row| {
+                let email = de::from_row::<QueryEmail>(&row.unwrap()).unwrap();
+                Email {
+                    address: email.email,
+                    verified: email.code.is_none(),
+                }
+            })
+            .collect::<Vec<_>>()
+            .await;
+
+        Ok(emails)
+    }
+
+    async fn get_mailing_list(&self) -> Result<Vec<String>> {
+        let emails = self
+            .connect()?
+            .query(
+                "
+                SELECT MIN(email)
+                FROM rhombus_email
+                JOIN rhombus_user ON rhombus_email.user_id = rhombus_user.id
+                WHERE code IS NULL AND NOT disabled
+                GROUP BY user_id
+                ",
+                (),
+            )
+            .await?
+            .into_stream()
+            .map(|row| row.unwrap().get::<String>(0).unwrap())
+            .collect::<Vec<_>>()
+            .await;
+
+        Ok(emails)
+    }
+
+    async fn create_email_verification_callback_code(
+        &self,
+        user_id: i64,
+        email: &str,
+    ) -> Result<String> {
+        let code = generate_email_callback_code();
+
+        self.connect()?
+            .execute(
+                "INSERT INTO rhombus_email (email, user_id, code) VALUES (?1, ?2, ?3)",
+                params!(email, user_id, code.as_str()),
+            )
+            .await?;
+
+        Ok(code)
+    }
+
+    async fn verify_email_verification_callback_code(&self, code: &str) -> Result<()> {
+        self.connect()?
+            .execute(
+                "
+                UPDATE rhombus_email
+                SET code = NULL
+                WHERE code = ?1
+            ",
+                [code],
+            )
+            .await?;
+
+        Ok(())
+    }
 
-        let mut db_scoreboard = tx
+    async fn create_email_signin_callback_code(&self, email: &str) -> Result<String> {
+        let code = generate_email_callback_code();
+
+        self.connect()?
+            .execute(
+                "INSERT INTO rhombus_email_signin (email, code) VALUES (?1, ?2)",
+                params!(email, code.as_str()),
+            )
+            .await?;
+
+        Ok(code)
+    }
+
+    async fn verify_email_signin_callback_code(&self, code: &str) -> Result<String> {
+        let email = self
+            .connect()?
             .query(
                 "
-                SELECT team_id, at, points
-                FROM rhombus_points_snapshot
-                WHERE division_id = ?1 AND team_id in (?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
-                ORDER BY at ASC
+                DELETE FROM rhombus_email_signin
+                WHERE code = ?1
+                RETURNING email
             ",
-                params!(
-                    division_id,
-                    top_team_ids[0],
-                    top_team_ids.get(1).unwrap_or(&top_team_ids[0]),
-                    top_team_ids.get(2).unwrap_or(&top_team_ids[0]),
-                    top_team_ids.get(3).unwrap_or(&top_team_ids[0]),
-                    top_team_ids.get(4).unwrap_or(&top_team_ids[0]),
-                    top_team_ids.get(5).unwrap_or(&top_team_ids[0]),
-                    top_team_ids.get(6).unwrap_or(&top_team_ids[0]),
-                    top_team_ids.get(7).unwrap_or(&top_team_ids[0]),
-                    top_team_ids.get(8).unwrap_or(&top_team_ids[0]),
-                    top_team_ids.get(9).unwrap_or(&top_team_ids[0]),
-                ),
+                [code],
+            )
+            .await?
+            .next()
+            .await?
+            .ok_or(libsql::Error::QueryReturnedNoRows)?
+            .get::<String>(0)
+            .unwrap();
+
+        Ok(email)
+    }
+
+    async fn delete_email(&self, user_id: i64, email: &str) -> Result<()> {
+        self.connect()?
+            .execute(
+                "DELETE FROM rhombus_email WHERE user_id = ?1 AND email = ?2",
+                params!(user_id, email),
             )
             .await?;
 
-        let mut teams: BTreeMap<i64, ScoreboardTeam> = BTreeMap::new();
-        while let Some(row) = db_scoreboard.next().await? {
-            let scoreboard = de::from_row::<DbScoreboard>(&row).unwrap();
-            let series_point = ScoreboardSeriesPoint {
-                timestamp: scoreboard.at,
-                total_score: scoreboard.points,
-            };
+        Ok(())
+    }
 
-            match teams.entry(scoreboard.team_id) {
-                Entry::Vacant(entry) => {
-                    entry.insert(ScoreboardTeam {
-                        team_name: all_teams[&scoreboard.team_id].clone(),
-                        series: vec![series_point],
-                    });
-                }
-                Entry::Occupied(mut entry) => {
-                    entry.get_mut().series.push(series_point);
-                }
-            }
-        }
+    async fn create_api_token(&self, user_id: i64, name: &str) -> Result<String> {
+        let token = generate_api_token();
 
-        Ok(Scoreboard { teams })
+        self.connect()?
+            .execute(
+                "INSERT INTO rhombus_api_token (user_id, name, token_hash) VALUES (?1, ?2, ?3)",
+                params!(user_id, name, hash_api_token(&token)),
+            )
+            .await?;
+
+        Ok(token)
     }
 
-    async fn get_leaderboard(&self, division_id: i64, page: Option<u64>) -> Result<Leaderboard> {
+    async fn get_api_tokens(&self, user_id: i64) -> Result<Vec<ApiToken>> {
         #[derive(Debug, Deserialize)]
-        struct DbLeaderboard {
-            team_id: i64,
+        struct QueryApiToken {
+            id: i64,
             name: String,
-            points: f64,
+            created_at: i64,
         }
 
-        if let Some(page) = page {
-            let tx = self.connect()?.transaction().await?;
+        let tokens = self
+            .connect()?
+            .query(
+                "SELECT id, name, created_at FROM rhombus_api_token WHERE user_id = ?1 ORDER BY id",
+                [user_id],
+            )
+            .await?
+            .into_stream()
+            .map(|row| {
+                let token = de::from_row::<QueryApiToken>(&row.unwrap()).unwrap();
+                ApiToken {
+                    id: token.id,
+                    name: token.name,
+                    created_at: DateTime::<Utc>::from_timestamp(token.created_at, 0).unwrap(),
+                }
+            })
+            .collect::<Vec<_>>()
+            .await;
 
-            let num_teams = tx
+        Ok(tokens)
+    }
+
+    async fn delete_api_token(&self, user_id: i64, token_id: i64) -> Result<()> {
+        self.connect()?
+            .execute(
+                "DELETE FROM rhombus_api_token WHERE user_id = ?1 AND id = ?2",
+                [user_id, token_id],
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_user_id_from_api_token(&self, token: &str) -> Result<Option<i64>> {
+        let user_id = self
+            .connect()?
+            .query(
+                "SELECT user_id FROM rhombus_api_token WHERE token_hash = ?1",
+                [hash_api_token(token)],
+            )
+            .await?
+            .next()
+            .await?
+            .map(|row| row.get::<i64>(0).unwrap());
+
+        Ok(user_id)
+    }
+
+    async fn get_user_divisions(&self, user_id: i64) -> Result<Vec<i64>> {
+        let divisions = self
+            .connect()?
+            .query(
+                "SELECT division_id FROM rhombus_user_division WHERE user_id = ?1",
+                [user_id],
+            )
+            .await?
+            .into_stream()
+            .map(|row| row.unwrap().get::<i64>(0).unwrap())
+            .collect::<Vec<_>>()
+            .await;
+
+        Ok(divisions)
+    }
+
+    async fn set_user_division(
+        &self,
+        user_id: i64,
+        team_id: i64,
+        division_id: i64,
+        join: bool,
+    ) -> Result<()> {
+        let tx = self.connect()?.transaction().await?;
+
+        if join {
+            let num_users_in_team = tx
                 .query(
-                    "
-            SELECT COUNT(*)
-            FROM rhombus_team_division_points
-            WHERE division_id = ?1
-        ",
-                    [division_id],
+                    "SELECT COUNT(*) FROM rhombus_user WHERE team_id = ?1",
+                    [team_id],
                 )
                 .await?
                 .next()
                 .await?
                 .unwrap()
-                .get::<u64>(0)
+                .get::<i64>(0)
                 .unwrap();
 
-            const PAGE_SIZE: u64 = 25;
+            tx
+                .execute(
+                    "INSERT OR IGNORE INTO rhombus_user_division (user_id, division_id) VALUES (?1, ?2)",
+                    [user_id, division_id],
+                )
+                .await?;
 
-            let num_pages = (num_teams + (PAGE_SIZE - 1)) / PAGE_SIZE;
+            if num_users_in_team == 1 {
+                tx
+                    .execute(
+                        "INSERT OR IGNORE INTO rhombus_team_division (team_id, division_id) VALUES (?1, ?2)",
+                        [team_id, division_id],
+                    )
+                    .await?;
+            }
+        } else {
+            _ = tx
+                .execute(
+                    "DELETE FROM rhombus_user_division WHERE user_id = ?1 AND division_id = ?2",
+                    [user_id, division_id],
+                )
+                .await;
+            _ = tx
+                .execute(
+                    "DELETE FROM rhombus_team_division WHERE team_id = ?1 AND division_id = ?2",
+                    [team_id, division_id],
+                )
+                .await;
+        }
 
-            let page = page.min(num_pages);
+        tx.commit().await?;
 
-            let mut rank = page * PAGE_SIZE;
+        Ok(())
+    }
 
-            let leaderboard_entries = tx
-                .query(
-                    "
-                SELECT team_id, name, points
-                FROM rhombus_team_division_points
-                JOIN rhombus_team ON rhombus_team_division_points.team_id = rhombus_team.id
-                WHERE division_id = ?1
-                ORDER BY points DESC
-                LIMIT ?3 OFFSET ?2
-            ",
-                    params!(division_id, page * PAGE_SIZE, PAGE_SIZE),
-                )
-                .await?
-                .into_stream()
-                .map(|row| {
-                    let db_leaderboard = de::from_row::<DbLeaderboard>(&row.unwrap()).unwrap();
-                    rank += 1;
-                    LeaderboardEntry {
-                        rank,
-                        team_id: db_leaderboard.team_id,
-                        team_name: db_leaderboard.name,
-                        score: db_leaderboard.points.round() as i64,
-                    }
-                })
-                .collect::<Vec<_>>()
-                .await;
+    async fn insert_divisions(&self, divisions: &[Division]) -> Result<()> {
+        let tx = self.connect()?.transaction().await?;
+
+        let current_division_ids = tx
+            .query("SELECT id FROM rhombus_division", ())
+            .await?
+            .into_stream()
+            .map(|row| row.unwrap().get::<i64>(0).unwrap())
+            .collect::<Vec<_>>()
+            .await;
+
+        for division_id in current_division_ids {
+            if !divisions.iter().any(|d| d.id == division_id) {
+                _ = tx
+                    .execute("DELETE FROM rhombus_division WHERE id = ?1", [division_id])
+                    .await;
+            }
+        }
+
+        for division in divisions {
+            tx.execute(
+                "INSERT OR REPLACE INTO rhombus_division (id, name, description) VALUES (?1, ?2, ?3)",
+                params!(division.id, division.name.as_str(), division.description.as_str()),
+            )
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
 
-            tx.commit().await?;
+    async fn get_team_divisions(&self, team_id: i64) -> Result<Vec<i64>> {
+        let divisions = self
+            .connect()?
+            .query(
+                "SELECT division_id FROM rhombus_team_division WHERE team_id = ?1",
+                [team_id],
+            )
+            .await?
+            .into_stream()
+            .map(|row| row.unwrap().get::<i64>(0).unwrap())
+            .collect::<Vec<_>>()
+            .await;
 
-            Ok(Leaderboard {
-                entries: leaderboard_entries,
-                num_pages,
-            })
-        } else {
-            let mut rank = 0;
+        Ok(divisions)
+    }
 
-            let leaderboard_entries = self
+    async fn set_team_division(&self, team_id: i64, division_id: i64, join: bool) -> Result<()> {
+        if join {
+            self.connect()?
+                .execute(
+                    "INSERT OR IGNORE INTO rhombus_team_division (team_id, division_id) VALUES (?1, ?2)",
+                    [team_id, division_id],
+                )
+                .await?;
+        } else {
+            _ = self
                 .connect()?
-                .query(
-                    "
-                SELECT team_id, name, points
-                FROM rhombus_team_division_points
-                JOIN rhombus_team ON rhombus_team_division_points.team_id = rhombus_team.id
-                WHERE division_id = ?1
-                ORDER BY points DESC
-            ",
-                    params!(division_id),
+                .execute(
+                    "DELETE FROM rhombus_team_division WHERE team_id = ?1 AND division_id = ?2",
+                    [team_id, division_id],
                 )
-                .await?
-                .into_stream()
-                .map(|row| {
-                    let db_leaderboard = de::from_row::<DbLeaderboard>(&row.unwrap()).unwrap();
-                    rank += 1;
-                    LeaderboardEntry {
-                        rank,
-                        team_id: db_leaderboard.team_id,
-                        team_name: db_leaderboard.name,
-                        score: db_leaderboard.points.round() as i64,
-                    }
-                })
-                .collect::<Vec<_>>()
                 .await;
-
-            Ok(Leaderboard {
-                entries: leaderboard_entries,
-                num_pages: 1,
-            })
         }
+
+        Ok(())
     }
 
-    async fn get_emails_for_user_id(&self, user_id: i64) -> Result<Vec<Email>> {
+    async fn get_team_standings(
+        &self,
+        team_id: i64,
+        frozen_at: Option<DateTime<Utc>>,
+    ) -> Result<TeamStandings> {
         #[derive(Debug, Deserialize)]
-        struct QueryEmail {
-            email: String,
-            code: Option<String>,
+        struct DbTeamDivisionPoints {
+            division_id: i64,
+            points: f64,
+            rank: i64,
         }
 
-        let emails = self
+        let mut standings = BTreeMap::new();
+        let mut standing_rows = self
             .connect()?
             .query(
-                "SELECT email, code FROM rhombus_email WHERE user_id = ?1",
-                [user_id],
+                &format!(
+                    "
+                WITH ranked_teams AS (
+                    SELECT
+                        team_id,
+                        division_id,
+                        points,
+                        RANK() OVER (PARTITION BY division_id ORDER BY points DESC) AS rank
+                    FROM
+                        {}
+                )
+                SELECT
+                    division_id,
+                    points,
+                    rank
+                FROM
+                    ranked_teams
+                WHERE
+                    team_id = ?1
+            ",
+                    team_division_points_source(frozen_at)
+                ),
+                [team_id],
+            )
+            .await?;
+        while let Some(row) = standing_rows.next().await? {
+            let standing = de::from_row::<DbTeamDivisionPoints>(&row).unwrap();
+            standings.insert(
+                standing.division_id,
+                TeamStandingEntry {
+                    points: standing.points as u64,
+                    rank: standing.rank as u64,
+                },
+            );
+        }
+
+        Ok(TeamStandings { standings })
+    }
+
+    async fn get_division_solve_counts(
+        &self,
+        at: DateTime<Utc>,
+    ) -> Result<BTreeMap<(i64, i64), u64>> {
+        let mut solve_counts = BTreeMap::new();
+        let mut rows = self
+            .connect()?
+            .query(
+                "
+                SELECT rhombus_solve.challenge_id, rhombus_team_division.division_id, COUNT(DISTINCT rhombus_user.team_id)
+                FROM rhombus_solve
+                JOIN rhombus_user ON rhombus_solve.user_id = rhombus_user.id
+                JOIN rhombus_team_division ON rhombus_user.team_id = rhombus_team_division.team_id
+                WHERE rhombus_solve.solved_at <= ?1
+                GROUP BY rhombus_solve.challenge_id, rhombus_team_division.division_id
+            ",
+                [at.timestamp()],
+            )
+            .await?;
+        while let Some(row) = rows.next().await? {
+            solve_counts.insert(
+                (row.get::<i64>(0)?, row.get::<i64>(1)?),
+                row.get::<i64>(2)? as u64,
+            );
+        }
+
+        Ok(solve_counts)
+    }
+
+    async fn get_announcements(&self) -> Result<Vec<Announcement>> {
+        let announcements = self
+            .connect()?
+            .query(
+                "
+                SELECT id, title, content, content_html, banner, created_at, updated_at
+                FROM rhombus_announcement
+                ORDER BY created_at DESC, id DESC
+                ",
+                (),
             )
             .await?
             .into_stream()
-            .map(|row| {
-                let email = de::from_row::<QueryEmail>(&row.unwrap()).unwrap();
-                Email {
-                    address: email.email,
-                    verified: email.code.is_none(),
-                }
-            })
+            .map(|row| db_announcement(&row.unwrap()))
             .collect::<Vec<_>>()
             .await;
 
-        Ok(emails)
+        Ok(announcements)
     }
 
-    async fn create_email_verification_callback_code(
+    async fn create_announcement(
         &self,
-        user_id: i64,
-        email: &str,
-    ) -> Result<String> {
-        let code = generate_email_callback_code();
+        announcement: &AnnouncementDefinition,
+    ) -> Result<Announcement> {
+        let row = self
+            .connect()?
+            .query(
+                "
+                INSERT INTO rhombus_announcement (title, content, content_html, banner)
+                VALUES (?1, ?2, ?3, ?4)
+                RETURNING id, title, content, content_html, banner, created_at, updated_at
+                ",
+                params!(
+                    announcement.title.as_str(),
+                    announcement.content.as_str(),
+                    announcement.content_html(),
+                    announcement.banner
+                ),
+            )
+            .await?
+            .next()
+            .await?
+            .ok_or(libsql::Error::QueryReturnedNoRows)?;
+
+        Ok(db_announcement(&row))
+    }
 
+    async fn update_announcement(
+        &self,
+        announcement_id: i64,
+        announcement: &AnnouncementDefinition,
+    ) -> Result<Announcement> {
+        let row = self
+            .connect()?
+            .query(
+                "
+                UPDATE rhombus_announcement
+                SET title = ?2, content = ?3, content_html = ?4, banner = ?5,
+                    updated_at = strftime('%s', 'now')
+                WHERE id = ?1
+                RETURNING id, title, content, content_html, banner, created_at, updated_at
+                ",
+                params!(
+                    announcement_id,
+                    announcement.title.as_str(),
+                    announcement.content.as_str(),
+                    announcement.content_html(),
+                    announcement.banner
+                ),
+            )
+            .await?
+            .next()
+            .await?
+            .ok_or(libsql::Error::QueryReturnedNoRows)?;
+
+        Ok(db_announcement(&row))
+    }
+
+    async fn delete_announcement(&self, announcement_id: i64) -> Result<()> {
         self.connect()?
             .execute(
-                "INSERT INTO rhombus_email (email, user_id, code) VALUES (?1, ?2, ?3)",
-                params!(email, user_id, code.as_str()),
+                "DELETE FROM rhombus_announcement WHERE id = ?1",
+                [announcement_id],
             )
             .await?;
 
-        Ok(code)
+        Ok(())
     }
 
-    async fn verify_email_verification_callback_code(&self, code: &str) -> Result<()> {
+    async fn upload_file(&self, hash: &str, filename: &str, bytes: &[u8]) -> Result<()> {
         self.connect()?
-            .execute(
-                "
-                UPDATE rhombus_email
-                SET code = NULL
-                WHERE code = ?1
-            ",
-                [code],
+            .query(
+                "INSERT INTO rhombus_file (hash, filename, contents) VALUES (?1, ?2, ?3)",
+                params!(hash, filename, bytes),
             )
             .await?;
 
-        Ok(())
+        Ok(())
+    }
+
+    async fn download_file(&self, hash: &str) -> Result<(Bytes, String)> {
+        #[derive(Debug, Deserialize)]
+        struct DbFile {
+            filename: String,
+            contents: Vec<u8>,
+        }
+
+        let row = self
+            .connect()?
+            .query(
+                "SELECT filename, contents FROM rhombus_file WHERE hash = ?1",
+                [hash],
+            )
+            .await?
+            .next()
+            .await?
+            .ok_or(libsql::Error::QueryReturnedNoRows)?;
+
+        let file = de::from_row::<DbFile>(&row).unwrap();
+
+        Ok((Bytes::from(file.contents), file.filename))
+    }
+}
+
+async fn upsert_author(tx: &Transaction, author: &AuthorDefinition) -> Result<()> {
+    tx.execute(
+        "INSERT OR REPLACE INTO rhombus_author (id, name, avatar, discord_id) VALUES (?1, ?2, ?3, ?4)",
+        params!(
+            author.id,
+            author.name.as_str(),
+            author.avatar.as_str(),
+            author.discord_id.get() as i64
+        ),
+    )
+    .await?;
+
+    Ok(())
+}
+
+async fn upsert_category(tx: &Transaction, category: &CategoryDefinition) -> Result<()> {
+    tx.execute(
+        "INSERT OR REPLACE INTO rhombus_category (id, name, color) VALUES (?1, ?2, ?3)",
+        params!(category.id, category.name.as_str(), category.color.as_str()),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// The live points view, or when `frozen_at` is set the last points snapshot
+/// every team had at that time, in the same shape.
+fn team_division_points_source(frozen_at: Option<DateTime<Utc>>) -> String {
+    match frozen_at {
+        None => "rhombus_team_division_points".to_owned(),
+        Some(frozen_at) => format!(
+            "(
+                SELECT team_id, division_id, points, MAX(at)
+                FROM rhombus_points_snapshot
+                WHERE at <= {}
+                GROUP BY team_id, division_id
+            ) AS rhombus_team_division_points",
+            frozen_at.timestamp()
+        ),
+    }
+}
+
+fn db_announcement(row: &libsql::Row) -> Announcement {
+    #[derive(Debug, Deserialize)]
+    struct DbAnnouncement {
+        id: i64,
+        title: String,
+        content: String,
+        content_html: String,
+        banner: bool,
+        created_at: i64,
+        updated_at: Option<i64>,
+    }
+
+    let announcement = de::from_row::<DbAnnouncement>(row).unwrap();
+    Announcement {
+        id: announcement.id,
+        title: announcement.title,
+        content: announcement.content,
+        content_html: announcement.content_html,
+        banner: announcement.banner,
+        created_at: Utc.timestamp_opt(announcement.created_at, 0).unwrap(),
+        updated_at: announcement
+            .updated_at
+            .map(|updated_at| Utc.timestamp_opt(updated_at, 0).unwrap()),
+    }
+}
+
+async fn upsert_challenge(tx: &Transaction, challenge: &ChallengeDefinition) -> Result<()> {
+    tx.execute(
+        "
+        INSERT INTO rhombus_challenge (id, name, description, flag, category_id, author_id, ticket_template, score_type, static_points, healthscript, hidden, flag_mode, dynamic_initial, dynamic_minimum, dynamic_decay, dynamic_curve, release_at, description_html, instance_spec, instance_timeout, healthcheck_interval, admin_created)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22)
+        ON CONFLICT (id) DO UPDATE SET
+            name = ?2,
+            description = ?3,
+            flag = ?4,
+            category_id = ?5,
+            author_id = ?6,
+            ticket_template = ?7,
+            score_type = ?8,
+            static_points = ?9,
+            healthscript = ?10,
+            hidden = ?11,
+            flag_mode = ?12,
+            dynamic_initial = ?13,
+            dynamic_minimum = ?14,
+            dynamic_decay = ?15,
+            dynamic_curve = ?16,
+            release_at = ?17,
+            released = CASE WHEN release_at IS ?17 THEN released ELSE FALSE END,
+            description_html = ?18,
+            instance_spec = ?19,
+            instance_timeout = ?20,
+            healthcheck_interval = ?21,
+            admin_created = admin_created AND ?22
+        ",
+        params!(
+            challenge.id,
+            challenge.name.as_str(),
+            challenge.description.as_str(),
+            challenge.flag.as_str(),
+            challenge.category_id,
+            challenge.author_id,
+            challenge.ticket_template.as_deref(),
+            i64::from(&challenge.scoring_type),
+            challenge.static_points,
+            challenge.healthscript.as_deref(),
+            challenge.hidden,
+            i64::from(&challenge.flag_mode),
+            challenge.dynamic_scoring.initial,
+            challenge.dynamic_scoring.minimum,
+            challenge.dynamic_scoring.decay,
+            i64::from(&challenge.dynamic_scoring.curve),
+            challenge.release_at.map(|t| t.timestamp()),
+            challenge.description_html(),
+            challenge.instance.as_ref().map(|i| i.spec.as_str()),
+            challenge.instance.as_ref().map(|i| i.timeout),
+            challenge.healthcheck_interval,
+            challenge.admin_created
+        ),
+    )
+    .await?;
+
+    let mut attachment_urls = tx
+        .query(
+            "SELECT url FROM rhombus_file_attachment WHERE challenge_id = ?1",
+            [challenge.id],
+        )
+        .await?
+        .into_stream()
+        .map(|row| row.unwrap().get::<String>(0).unwrap())
+        .collect::<BTreeSet<_>>()
+        .await;
+
+    for attachment in &challenge.attachments {
+        tx.execute(
+            "
+            INSERT OR REPLACE INTO rhombus_file_attachment (challenge_id, name, url)
+            VALUES (?1, ?2, ?3)
+            ",
+            params!(
+                challenge.id,
+                attachment.name.as_str(),
+                attachment.url.as_str()
+            ),
+        )
+        .await?;
+        attachment_urls.remove(&attachment.url);
+    }
+
+    for url in attachment_urls {
+        tx.execute(
+            "DELETE FROM rhombus_file_attachment WHERE challenge_id = ?1 AND url = ?2",
+            params!(challenge.id, url),
+        )
+        .await?;
+    }
+
+    for hint in &challenge.hints {
+        tx.execute(
+            "
+            INSERT OR REPLACE INTO rhombus_challenge_hint (challenge_id, id, content, cost)
+            VALUES (?1, ?2, ?3, ?4)
+            ",
+            params!(challenge.id, hint.id, hint.content.as_str(), hint.cost),
+        )
+        .await?;
+    }
+
+    tx.execute(
+        "DELETE FROM rhombus_challenge_hint WHERE challenge_id = ?1 AND id >= ?2",
+        params!(challenge.id, challenge.hints.len() as i64),
+    )
+    .await?;
+
+    for (i, prerequisite) in challenge.prerequisites.iter().enumerate() {
+        let (required_challenge_id, category_id, points) = match prerequisite {
+            ChallengePrerequisite::Solve { challenge_id } => (Some(*challenge_id), None, None),
+            ChallengePrerequisite::CategoryPoints {
+                category_id,
+                points,
+            } => (None, Some(*category_id), Some(*points)),
+        };
+        tx.execute(
+            "
+            INSERT OR REPLACE INTO rhombus_challenge_prerequisite (challenge_id, id, required_challenge_id, category_id, points)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            ",
+            params!(challenge.id, i as i64, required_challenge_id, category_id, points),
+        )
+        .await?;
     }
 
-    async fn create_email_signin_callback_code(&self, email: &str) -> Result<String> {
-        let code = generate_email_callback_code();
+    tx.execute(
+        "DELETE FROM rhombus_challenge_prerequisite WHERE challenge_id = ?1 AND id >= ?2",
+        params!(challenge.id, challenge.prerequisites.len() as i64),
+    )
+    .await?;
 
-        self.connect()?
-            .execute(
-                "INSERT INTO rhombus_email_signin (email, code) VALUES (?1, ?2)",
-                params!(email, code.as_str()),
-            )
-            .await?;
+    Ok(())
+}
 
-        Ok(code)
-    }
+pub async fn create_team(tx: &Transaction) -> Result<i64> {
+    loop {
+        let team_invite_token = create_team_invite_token();
 
-    async fn verify_email_signin_callback_code(&self, code: &str) -> Result<String> {
-        let email = self
-            .connect()?
+        let team_name = to_title_case(&to_plural(&petname::petname(3, " ").unwrap()));
+
+        if let Ok(team_id) = tx
             .query(
-                "
-                DELETE FROM rhombus_email_signin
-                WHERE code = ?1
-                RETURNING email
-            ",
-                [code],
+                "INSERT INTO rhombus_team (name, invite_token) VALUES (?1, ?2) RETURNING id",
+                [team_name, team_invite_token],
             )
             .await?
             .next()
-            .await?
-            .ok_or(libsql::Error::QueryReturnedNoRows)?
-            .get::<String>(0)
-            .unwrap();
-
-        Ok(email)
+            .await
+        {
+            break Ok(team_id.unwrap().get::<i64>(0).unwrap());
+        }
     }
+}
 
-    async fn delete_email(&self, user_id: i64, email: &str) -> Result<()> {
-        self.connect()?
-            .execute(
-                "DELETE FROM rhombus_email WHERE user_id = ?1 AND email = ?2",
-                params!(user_id, email),
-            )
-            .await?;
+#[cfg(test)]
+mod test {
+    use std::net::IpAddr;
 
-        Ok(())
-    }
+    use chrono::{TimeDelta, Utc};
+    use futures::StreamExt;
 
-    async fn get_user_divisions(&self, user_id: i64) -> Result<Vec<i64>> {
-        let divisions = self
-            .connect()?
-            .query(
-                "SELECT division_id FROM rhombus_user_division WHERE user_id = ?1",
-                [user_id],
-            )
-            .await?
-            .into_stream()
-            .map(|row| row.unwrap().get::<i64>(0).unwrap())
-            .collect::<Vec<_>>()
-            .await;
+    use crate::internal::database::{
+        libsql::{LibSQLConnection, LocalLibSQL},
+        provider::{
+            AnnouncementDefinition, AuthorDefinition, CategoryDefinition, ChallengeDefinition,
+            ChallengeDefinitions, ChallengeHint, Database, DynamicScoring, FlagMode, Healthcheck,
+            ScoringCurve, ScoringType,
+        },
+    };
 
-        Ok(divisions)
+    #[tokio::test]
+    async fn migrate_libsql() {
+        let database = LocalLibSQL::new_memory().await.unwrap();
+        database.migrate().await.unwrap();
     }
 
-    async fn set_user_division(
-        &self,
-        user_id: i64,
-        team_id: i64,
-        division_id: i64,
-        join: bool,
-    ) -> Result<()> {
-        let tx = self.connect()?.transaction().await?;
-
-        if join {
-            let num_users_in_team = tx
-                .query(
-                    "SELECT COUNT(*) FROM rhombus_user WHERE team_id = ?1",
-                    [team_id],
-                )
-                .await?
-                .next()
-                .await?
-                .unwrap()
-                .get::<i64>(0)
-                .unwrap();
+    #[tokio::test]
+    async fn migrate_libsql_twice() {
+        let database = LocalLibSQL::new_memory().await.unwrap();
+        database.migrate().await.unwrap();
+        database.migrate().await.unwrap();
+    }
 
-            tx
-                .execute(
-                    "INSERT OR IGNORE INTO rhombus_user_division (user_id, division_id) VALUES (?1, ?2)",
-                    [user_id, division_id],
-                )
-                .await?;
+    #[tokio::test]
+    async fn track_load() {
+        let database = LocalLibSQL::new_memory().await.unwrap();
+        database.migrate().await.unwrap();
 
-            if num_users_in_team == 1 {
-                tx
-                    .execute(
-                        "INSERT OR IGNORE INTO rhombus_team_division (team_id, division_id) VALUES (?1, ?2)",
-                        [team_id, division_id],
-                    )
-                    .await?;
-            }
-        } else {
-            _ = tx
-                .execute(
-                    "DELETE FROM rhombus_user_division WHERE user_id = ?1 AND division_id = ?2",
-                    [user_id, division_id],
-                )
-                .await;
-            _ = tx
-                .execute(
-                    "DELETE FROM rhombus_team_division WHERE team_id = ?1 AND division_id = ?2",
-                    [team_id, division_id],
-                )
-                .await;
+        let ip: IpAddr = "1.2.3.4".parse().unwrap();
+        for i in 0..1000 {
+            let user_agent = i.to_string();
+            database
+                .insert_track(ip, Some(user_agent.as_str()), None, 1)
+                .await
+                .unwrap();
         }
 
-        tx.commit().await?;
+        let num_tracks = database
+            .conn
+            .query("SELECT COUNT(*) FROM rhombus_track", ())
+            .await
+            .unwrap()
+            .next()
+            .await
+            .unwrap()
+            .unwrap()
+            .get::<i64>(0)
+            .unwrap();
 
-        Ok(())
+        assert_eq!(32, num_tracks);
     }
 
-    async fn insert_divisions(&self, divisions: &[Division]) -> Result<()> {
-        let tx = self.connect()?.transaction().await?;
-
-        let current_division_ids = tx
-            .query("SELECT id FROM rhombus_division", ())
-            .await?
-            .into_stream()
-            .map(|row| row.unwrap().get::<i64>(0).unwrap())
-            .collect::<Vec<_>>()
-            .await;
-
-        for division_id in current_division_ids {
-            if !divisions.iter().any(|d| d.id == division_id) {
-                _ = tx
-                    .execute("DELETE FROM rhombus_division WHERE id = ?1", [division_id])
-                    .await;
-            }
-        }
+    #[tokio::test]
+    async fn release_challenges() {
+        let database = LocalLibSQL::new_memory().await.unwrap();
+        database.migrate().await.unwrap();
 
-        for division in divisions {
-            tx.execute(
-                "INSERT OR REPLACE INTO rhombus_division (id, name, description) VALUES (?1, ?2, ?3)",
-                params!(division.id, division.name.as_str(), division.description.as_str()),
+        database
+            .conn
+            .execute_batch(
+                "
+                INSERT INTO rhombus_category (id, name, color) VALUES (1, 'misc', '#ffffff');
+                INSERT INTO rhombus_author (id, name, avatar, discord_id) VALUES (1, 'author', '', 1);
+                ",
             )
-            .await?;
-        }
+            .await
+            .unwrap();
 
-        tx.commit().await?;
+        let now = Utc::now();
+        database
+            .conn
+            .execute(
+                "
+                INSERT INTO rhombus_challenge (id, name, description, flag, category_id, author_id, score_type, release_at)
+                VALUES (1, 'released', '', 'flag', 1, 1, 0, ?1), (2, 'upcoming', '', 'flag', 1, 1, 0, ?2), (3, 'always', '', 'flag', 1, 1, 0, NULL)
+                ",
+                [(now - TimeDelta::minutes(1)).timestamp(), (now + TimeDelta::minutes(1)).timestamp()],
+            )
+            .await
+            .unwrap();
 
-        Ok(())
+        assert_eq!(vec![1], database.release_challenges(now).await.unwrap());
+        assert!(database.release_challenges(now).await.unwrap().is_empty());
+        assert_eq!(
+            vec![2],
+            database
+                .release_challenges(now + TimeDelta::minutes(2))
+                .await
+                .unwrap()
+        );
     }
 
-    async fn get_team_divisions(&self, team_id: i64) -> Result<Vec<i64>> {
-        let divisions = self
-            .connect()?
-            .query(
-                "SELECT division_id FROM rhombus_team_division WHERE team_id = ?1",
-                [team_id],
+    #[tokio::test]
+    async fn team_ips() {
+        let database = LocalLibSQL::new_memory().await.unwrap();
+        database.migrate().await.unwrap();
+
+        database
+            .conn
+            .execute_batch(
+                "
+                INSERT INTO rhombus_team (id, name, invite_token) VALUES (1, 'one', 'a'), (2, 'two', 'b');
+                INSERT INTO rhombus_user (id, name, avatar, team_id, owner_team_id)
+                VALUES (1, 'alice', '', 1, 1), (2, 'bob', '', 2, 2);
+                ",
             )
-            .await?
-            .into_stream()
-            .map(|row| row.unwrap().get::<i64>(0).unwrap())
-            .collect::<Vec<_>>()
-            .await;
+            .await
+            .unwrap();
 
-        Ok(divisions)
+        let ip = IpAddr::from([10, 0, 0, 1]);
+        database
+            .insert_track(ip, Some("curl"), Some(1), 3)
+            .await
+            .unwrap();
+        database
+            .insert_track(IpAddr::from([10, 0, 0, 2]), Some("curl"), Some(2), 1)
+            .await
+            .unwrap();
+
+        let ips = database.get_team_ips(1).await.unwrap();
+        assert_eq!(1, ips.len());
+        assert_eq!(1, ips[0].user_id);
+        assert_eq!(ip, ips[0].ip);
+        assert_eq!("curl", ips[0].user_agent);
+        assert_eq!(3, ips[0].requests);
     }
 
-    async fn set_team_division(&self, team_id: i64, division_id: i64, join: bool) -> Result<()> {
-        if join {
-            self.connect()?
-                .execute(
-                    "INSERT OR IGNORE INTO rhombus_team_division (team_id, division_id) VALUES (?1, ?2)",
-                    [team_id, division_id],
-                )
-                .await?;
-        } else {
-            _ = self
-                .connect()?
-                .execute(
-                    "DELETE FROM rhombus_team_division WHERE team_id = ?1 AND division_id = ?2",
-                    [team_id, division_id],
-                )
-                .await;
-        }
+    #[tokio::test]
+    async fn announcements() {
+        let database = LocalLibSQL::new_memory().await.unwrap();
+        database.migrate().await.unwrap();
 
-        Ok(())
+        let first = database
+            .create_announcement(&AnnouncementDefinition {
+                title: "Welcome".to_owned(),
+                content: "Good **luck**".to_owned(),
+                banner: true,
+            })
+            .await
+            .unwrap();
+        assert!(first.content_html.contains("<strong>luck</strong>"));
+        assert!(first.updated_at.is_none());
+
+        let second = database
+            .create_announcement(&AnnouncementDefinition {
+                title: "Hint".to_owned(),
+                content: "Look closer".to_owned(),
+                banner: false,
+            })
+            .await
+            .unwrap();
+
+        let announcements = database.get_announcements().await.unwrap();
+        assert_eq!(
+            vec![second.id, first.id],
+            announcements
+                .iter()
+                .map(|announcement| announcement.id)
+                .collect::<Vec<_>>()
+        );
+
+        let updated = database
+            .update_announcement(
+                first.id,
+                &AnnouncementDefinition {
+                    title: "Welcome".to_owned(),
+                    content: "Good luck".to_owned(),
+                    banner: false,
+                },
+            )
+            .await
+            .unwrap();
+        assert!(!updated.banner);
+        assert!(updated.updated_at.is_some());
+        assert_eq!(first.created_at, updated.created_at);
+
+        database.delete_announcement(second.id).await.unwrap();
+        let announcements = database.get_announcements().await.unwrap();
+        assert_eq!(1, announcements.len());
+        assert_eq!(first.id, announcements[0].id);
     }
 
-    async fn get_team_standings(&self, team_id: i64) -> Result<TeamStandings> {
-        #[derive(Debug, Deserialize)]
-        struct DbTeamDivisionPoints {
-            division_id: i64,
-            points: f64,
-            rank: i64,
-        }
+    #[tokio::test]
+    async fn mailing_list() {
+        let database = LocalLibSQL::new_memory().await.unwrap();
+        database.migrate().await.unwrap();
 
-        let mut standings = BTreeMap::new();
-        let mut standing_rows = self
-            .connect()?
-            .query(
+        database
+            .conn
+            .execute_batch(
                 "
-                WITH ranked_teams AS (
-                    SELECT
-                        team_id,
-                        division_id,
-                        points,
-                        RANK() OVER (PARTITION BY division_id ORDER BY points DESC) AS rank
-                    FROM
-                        rhombus_team_division_points
-                )
-                SELECT
-                    division_id,
-                    points,
-                    rank
-                FROM
-                    ranked_teams
-                WHERE
-                    team_id = ?1
-            ",
-                [team_id],
+                INSERT INTO rhombus_team (id, name, invite_token) VALUES (1, 'one', 'a'), (2, 'two', 'b'), (3, 'three', 'c');
+                INSERT INTO rhombus_user (id, name, avatar, team_id, owner_team_id, disabled)
+                VALUES (1, 'alice', '', 1, 1, FALSE), (2, 'bob', '', 2, 2, FALSE), (3, 'eve', '', 3, 3, TRUE);
+                INSERT INTO rhombus_email (email, user_id, code)
+                VALUES ('a@example.com', 1, NULL), ('b@example.com', 1, NULL), ('bob@example.com', 2, 'code'), ('eve@example.com', 3, NULL);
+                ",
             )
-            .await?;
-        while let Some(row) = standing_rows.next().await? {
-            let standing = de::from_row::<DbTeamDivisionPoints>(&row).unwrap();
-            standings.insert(
-                standing.division_id,
-                TeamStandingEntry {
-                    points: standing.points as u64,
-                    rank: standing.rank as u64,
-                },
-            );
-        }
+            .await
+            .unwrap();
 
-        Ok(TeamStandings { standings })
+        assert_eq!(
+            vec!["a@example.com".to_owned()],
+            database.get_mailing_list().await.unwrap()
+        );
     }
 
-    async fn upload_file(&self, hash: &str, filename: &str, bytes: &[u8]) -> Result<()> {
-        self.connect()?
-            .query(
-                "INSERT INTO rhombus_file (hash, filename, contents) VALUES (?1, ?2, ?3)",
-                params!(hash, filename, bytes),
+    #[tokio::test]
+    async fn issued_flags() {
+        let database = LocalLibSQL::new_memory().await.unwrap();
+        database.migrate().await.unwrap();
+
+        database
+            .conn
+            .execute_batch(
+                "
+                INSERT INTO rhombus_category (id, name, color) VALUES (1, 'misc', '#ffffff');
+                INSERT INTO rhombus_author (id, name, avatar, discord_id) VALUES (1, 'author', '', 1);
+                INSERT INTO rhombus_challenge (id, name, description, flag, category_id, author_id, score_type, flag_mode)
+                VALUES (1, 'issued', '', 'flag{random}', 1, 1, 0, 6);
+                INSERT INTO rhombus_team (id, name, invite_token) VALUES (1, 'one', 'a'), (2, 'two', 'b');
+                ",
             )
-            .await?;
+            .await
+            .unwrap();
 
-        Ok(())
+        database.add_issued_flag(1, 1, "flag{one}").await.unwrap();
+        database.add_issued_flag(1, 2, "flag{two}").await.unwrap();
+
+        assert_eq!(
+            Some(1),
+            database
+                .get_issued_flag_team_id(1, "flag{one}")
+                .await
+                .unwrap()
+        );
+        assert_eq!(
+            Some(2),
+            database
+                .get_issued_flag_team_id(1, "flag{two}")
+                .await
+                .unwrap()
+        );
+        assert_eq!(
+            None,
+            database
+                .get_issued_flag_team_id(1, "flag{three}")
+                .await
+                .unwrap()
+        );
     }
 
-    async fn download_file(&self, hash: &str) -> Result<(Bytes, String)> {
-        #[derive(Debug, Deserialize)]
-        struct DbFile {
-            filename: String,
-            contents: Vec<u8>,
-        }
+    #[tokio::test]
+    async fn healthchecks() {
+        let database = LocalLibSQL::new_memory().await.unwrap();
+        database.migrate().await.unwrap();
 
-        let row = self
-            .connect()?
-            .query(
-                "SELECT filename, contents FROM rhombus_file WHERE hash = ?1",
-                [hash],
+        database
+            .conn
+            .execute_batch(
+                "
+                INSERT INTO rhombus_category (id, name, color) VALUES (1, 'misc', '#ffffff');
+                INSERT INTO rhombus_author (id, name, avatar, discord_id) VALUES (1, 'author', '', 1);
+                INSERT INTO rhombus_challenge (id, name, description, flag, category_id, author_id, score_type)
+                VALUES (1, 'healthy', '', 'flag', 1, 1, 0);
+                ",
             )
-            .await?
-            .next()
-            .await?
-            .ok_or(libsql::Error::QueryReturnedNoRows)?;
+            .await
+            .unwrap();
 
-        let file = de::from_row::<DbFile>(&row).unwrap();
+        let now = Utc::now();
+        for (minutes_ago, healthy, error) in [(90, true, None), (30, false, Some("Timed out"))] {
+            database
+                .add_healthcheck(&Healthcheck {
+                    challenge_id: 1,
+                    checked_at: now - TimeDelta::minutes(minutes_ago),
+                    healthy: Some(healthy),
+                    latency: 20,
+                    error: error.map(str::to_owned),
+                })
+                .await
+                .unwrap();
+        }
 
-        Ok((Bytes::from(file.contents), file.filename))
+        let healthchecks = database
+            .get_healthchecks(now - TimeDelta::hours(2))
+            .await
+            .unwrap();
+        assert_eq!(2, healthchecks.len());
+        assert_eq!(Some(true), healthchecks[0].healthy);
+        assert_eq!(Some("Timed out"), healthchecks[1].error.as_deref());
+
+        let healthchecks = database
+            .get_healthchecks(now - TimeDelta::hours(1))
+            .await
+            .unwrap();
+        assert_eq!(1, healthchecks.len());
+        assert_eq!(Some(false), healthchecks[0].healthy);
     }
-}
 
-pub async fn create_team(tx: &Transaction) -> Result<i64> {
-    loop {
-        let team_invite_token = create_team_invite_token();
+    #[tokio::test]
+    async fn sync_keeps_admin_created_challenges() {
+        let database = LocalLibSQL::new_memory().await.unwrap();
+        database.migrate().await.unwrap();
 
-        let team_name = to_title_case(&to_plural(&petname::petname(3, " ").unwrap()));
+        database
+            .upsert_author(&AuthorDefinition {
+                id: 1,
+                name: "Author".to_owned(),
+                avatar: "".to_owned(),
+                discord_id: 1.try_into().unwrap(),
+            })
+            .await
+            .unwrap();
+        database
+            .upsert_category(&CategoryDefinition {
+                id: 1,
+                name: "Misc".to_owned(),
+                color: "#ffffff".to_owned(),
+            })
+            .await
+            .unwrap();
+        database
+            .upsert_challenge(&ChallengeDefinition {
+                id: 1,
+                name: "Admin".to_owned(),
+                description: "".to_owned(),
+                flag: "flag{admin}".to_owned(),
+                flag_mode: FlagMode::Exact,
+                category_id: 1,
+                author_id: 1,
+                ticket_template: None,
+                scoring_type: ScoringType::Static,
+                static_points: Some(100),
+                dynamic_scoring: DynamicScoring::default(),
+                healthscript: None,
+                healthcheck_interval: None,
+                attachments: vec![],
+                hints: vec![],
+                prerequisites: vec![],
+                instance: None,
+                release_at: None,
+                hidden: false,
+                admin_created: true,
+            })
+            .await
+            .unwrap();
 
-        if let Ok(team_id) = tx
-            .query(
-                "INSERT INTO rhombus_team (name, invite_token) VALUES (?1, ?2) RETURNING id",
-                [team_name, team_invite_token],
-            )
-            .await?
-            .next()
+        database
+            .sync_challenges(&ChallengeDefinitions::default())
             .await
-        {
-            break Ok(team_id.unwrap().get::<i64>(0).unwrap());
+            .unwrap();
+
+        let conn = database.connect().unwrap();
+        for table in ["rhombus_challenge", "rhombus_category", "rhombus_author"] {
+            let count = conn
+                .query(&format!("SELECT COUNT(*) FROM {table}"), ())
+                .await
+                .unwrap()
+                .next()
+                .await
+                .unwrap()
+                .unwrap()
+                .get::<i64>(0)
+                .unwrap();
+            assert_eq!(1, count, "{table}");
         }
+
+        assert!(!database.delete_author(1).await.unwrap());
+        assert!(!database.delete_category(1).await.unwrap());
+        database.delete_challenge(1).await.unwrap();
+        assert!(database.delete_author(1).await.unwrap());
+        assert!(database.delete_category(1).await.unwrap());
     }
-}
 
-#[cfg(test)]
-mod test {
-    use std::net::IpAddr;
+    #[tokio::test]
+    async fn hidden_and_admin_created_challenges() {
+        let database = LocalLibSQL::new_memory().await.unwrap();
+        database.migrate().await.unwrap();
+
+        let author = AuthorDefinition {
+            id: 1,
+            name: "Author".to_owned(),
+            avatar: "".to_owned(),
+            discord_id: 1.try_into().unwrap(),
+        };
+        let category = CategoryDefinition {
+            id: 1,
+            name: "Misc".to_owned(),
+            color: "#ffffff".to_owned(),
+        };
+        database.upsert_author(&author).await.unwrap();
+        database.upsert_category(&category).await.unwrap();
+
+        let challenge = |id: i64, hidden: bool, admin_created: bool| ChallengeDefinition {
+            id,
+            name: id.to_string(),
+            description: "".to_owned(),
+            flag: "flag{test}".to_owned(),
+            flag_mode: FlagMode::Exact,
+            category_id: 1,
+            author_id: 1,
+            ticket_template: None,
+            scoring_type: ScoringType::Static,
+            static_points: Some(100),
+            dynamic_scoring: DynamicScoring::default(),
+            healthscript: None,
+            healthcheck_interval: None,
+            attachments: vec![],
+            hints: vec![],
+            prerequisites: vec![],
+            instance: None,
+            release_at: None,
+            hidden,
+            admin_created,
+        };
+        let challenge_ids = || async {
+            database
+                .get_challenges()
+                .await
+                .unwrap()
+                .challenges
+                .iter()
+                .map(|challenge| (challenge.id, challenge.hidden))
+                .collect::<Vec<_>>()
+        };
+
+        database
+            .upsert_challenge(&challenge(1, true, true))
+            .await
+            .unwrap();
+        database
+            .upsert_challenge(&challenge(2, false, false))
+            .await
+            .unwrap();
+        assert_eq!(vec![(1, true), (2, false)], challenge_ids().await);
+
+        // Only challenges the loader no longer defines are removed
+        database
+            .sync_challenges(&ChallengeDefinitions::default())
+            .await
+            .unwrap();
+        assert_eq!(vec![(1, true)], challenge_ids().await);
+
+        database
+            .upsert_challenge(&challenge(1, false, true))
+            .await
+            .unwrap();
+        assert_eq!(vec![(1, false)], challenge_ids().await);
+
+        // Once the loader defines an admin created challenge, it owns it
+        database
+            .sync_challenges(&ChallengeDefinitions {
+                authors: vec![author],
+                categories: vec![category],
+                challenges: vec![challenge(1, true, false)],
+            })
+            .await
+            .unwrap();
+        assert_eq!(vec![(1, true)], challenge_ids().await);
 
-    use crate::internal::database::{libsql::LocalLibSQL, provider::Database};
+        database
+            .sync_challenges(&ChallengeDefinitions::default())
+            .await
+            .unwrap();
+        assert!(challenge_ids().await.is_empty());
+    }
 
     #[tokio::test]
-    async fn migrate_libsql() {
+    async fn set_taken_team_name() {
         let database = LocalLibSQL::new_memory().await.unwrap();
         database.migrate().await.unwrap();
+
+        let (_, first_team_id, _) = database
+            .upsert_user_by_email("first", "first@example.com", "")
+            .await
+            .unwrap();
+        let (_, second_team_id, _) = database
+            .upsert_user_by_email("second", "second@example.com", "")
+            .await
+            .unwrap();
+
+        database
+            .set_team_name(first_team_id, "taken")
+            .await
+            .unwrap();
+        let error = database
+            .set_team_name(second_team_id, "taken")
+            .await
+            .unwrap_err();
+        assert!(error.is_unique_violation());
     }
 
     #[tokio::test]
-    async fn track_load() {
+    async fn hint_unlocks() {
         let database = LocalLibSQL::new_memory().await.unwrap();
         database.migrate().await.unwrap();
 
-        let ip: IpAddr = "1.2.3.4".parse().unwrap();
-        for i in 0..1000 {
-            let user_agent = i.to_string();
+        database
+            .conn
+            .execute_batch(
+                "
+                INSERT INTO rhombus_category (id, name, color) VALUES (1, 'misc', '#ffffff');
+                INSERT INTO rhombus_author (id, name, avatar, discord_id) VALUES (1, 'author', '', 1);
+                INSERT INTO rhombus_challenge (id, name, description, flag, category_id, author_id, score_type, static_points)
+                VALUES (1, 'hinted', '', 'flag', 1, 1, 1, 100);
+                INSERT INTO rhombus_challenge_hint (challenge_id, id, content, cost) VALUES (1, 1, 'look closer', 30), (1, 2, 'the answer', 500);
+                INSERT INTO rhombus_division (id, name, description) VALUES (1, 'open', '');
+                INSERT INTO rhombus_team (id, name, invite_token) VALUES (1, 'one', 'a'), (2, 'two', 'b');
+                INSERT INTO rhombus_user (id, name, avatar, team_id, owner_team_id)
+                VALUES (1, 'alice', '', 1, 1), (2, 'bob', '', 2, 2);
+                INSERT INTO rhombus_team_division (team_id, division_id) VALUES (1, 1), (2, 1);
+                INSERT INTO rhombus_solve (challenge_id, user_id) VALUES (1, 1);
+                ",
+            )
+            .await
+            .unwrap();
+
+        let hint = |id, cost| ChallengeHint {
+            id,
+            content: String::new(),
+            cost,
+        };
+        let points = || async {
             database
-                .insert_track(ip, Some(user_agent.as_str()), None, 1)
+                .conn
+                .query(
+                    "SELECT team_id, points FROM rhombus_team_division_points ORDER BY team_id",
+                    (),
+                )
                 .await
-                .unwrap();
-        }
+                .unwrap()
+                .into_stream()
+                .map(|row| {
+                    let row = row.unwrap();
+                    (row.get::<i64>(0).unwrap(), row.get::<i64>(1).unwrap())
+                })
+                .collect::<Vec<_>>()
+                .await
+        };
 
-        let num_tracks = database
+        assert_eq!(vec![(1, 100)], points().await);
+
+        // unlocking the same hint twice only costs once
+        database.unlock_hint(1, 1, 1, &hint(1, 30)).await.unwrap();
+        database.unlock_hint(1, 1, 1, &hint(1, 30)).await.unwrap();
+        assert_eq!(vec![(1, 70)], points().await);
+
+        // hints cannot take a team below zero, or onto the scoreboard without
+        // a solve
+        database.unlock_hint(1, 1, 1, &hint(2, 500)).await.unwrap();
+        database.unlock_hint(2, 2, 1, &hint(1, 30)).await.unwrap();
+        assert_eq!(vec![(1, 0)], points().await);
+
+        let team = database.get_team_from_id(1).await.unwrap();
+        assert_eq!(Some(&vec![1, 2]), team.unlocked_hints.get(&1));
+    }
+
+    #[tokio::test]
+    async fn dynamic_points_view() {
+        let database = LocalLibSQL::new_memory().await.unwrap();
+        database.migrate().await.unwrap();
+
+        database
             .conn
-            .query("SELECT COUNT(*) FROM rhombus_track", ())
-            .await
-            .unwrap()
-            .next()
+            .execute_batch(
+                "
+                INSERT INTO rhombus_category (id, name, color) VALUES (1, 'misc', '#ffffff');
+                INSERT INTO rhombus_author (id, name, avatar, discord_id) VALUES (1, 'author', '', 1);
+                INSERT INTO rhombus_division (id, name, description) VALUES (1, 'open', '');
+                ",
+            )
             .await
-            .unwrap()
-            .unwrap()
-            .get::<i64>(0)
             .unwrap();
 
-        assert_eq!(32, num_tracks);
+        let curves = [
+            ScoringCurve::Quadratic,
+            ScoringCurve::Logarithmic,
+            ScoringCurve::Linear,
+        ]
+        .map(|curve| DynamicScoring {
+            initial: 500,
+            minimum: 100,
+            decay: 10,
+            curve,
+        });
+        for (id, scoring) in curves.iter().enumerate() {
+            database
+                .conn
+                .execute(
+                    "
+                    INSERT INTO rhombus_challenge (id, name, description, flag, category_id, author_id, score_type, dynamic_initial, dynamic_minimum, dynamic_decay, dynamic_curve)
+                    VALUES (?1, ?1, '', 'flag', 1, 1, 0, ?2, ?3, ?4, ?5)
+                    ",
+                    [
+                        id as i64,
+                        scoring.initial,
+                        scoring.minimum,
+                        scoring.decay,
+                        i64::from(&scoring.curve),
+                    ],
+                )
+                .await
+                .unwrap();
+        }
+
+        let mut solves = 0;
+        for checked_solves in [0, 1, 2, 3, 5, 7, 10, 12] {
+            while solves < checked_solves {
+                solves += 1;
+                database
+                    .conn
+                    .execute_batch(&format!(
+                        "
+                        INSERT INTO rhombus_team (id, name, invite_token) VALUES ({solves}, '{solves}', '{solves}');
+                        INSERT INTO rhombus_user (id, name, avatar, team_id, owner_team_id) VALUES ({solves}, '{solves}', '', {solves}, {solves});
+                        INSERT INTO rhombus_team_division (team_id, division_id) VALUES ({solves}, 1);
+                        INSERT INTO rhombus_solve (challenge_id, user_id) SELECT id, {solves} FROM rhombus_challenge;
+                        "
+                    ))
+                    .await
+                    .unwrap();
+            }
+
+            for (id, scoring) in curves.iter().enumerate() {
+                let points = database
+                    .conn
+                    .query(
+                        "SELECT CAST(points AS INTEGER) FROM rhombus_challenge_division_points WHERE challenge_id = ?1",
+                        [id as i64],
+                    )
+                    .await
+                    .unwrap()
+                    .next()
+                    .await
+                    .unwrap()
+                    .unwrap()
+                    .get::<i64>(0)
+                    .unwrap();
+                assert_eq!(
+                    scoring.points(solves),
+                    points as u64,
+                    "{:?} {}",
+                    scoring.curve,
+                    solves
+                );
+            }
+        }
     }
 }
@@ -2,11 +2,16 @@ use std::{collections::BTreeMap, net::IpAddr, num::NonZeroU64, sync::Arc};
 
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use serde::Serialize;
+use fancy_regex::Regex;
+use ring::hmac;
+use serde::{Deserialize, Serialize};
 use tokio_util::bytes::Bytes;
 
 use crate::{
-    internal::{auth::User, database::cache::Writeups, division::Division, settings::Settings},
+    internal::{
+        auth::User, database::cache::Writeups, division::Division,
+        local_upload_provider::slice_to_hex_string, markdown::render_markdown, settings::Settings,
+    },
     Result,
 };
 
@@ -27,6 +32,176 @@ impl From<i64> for ScoringType {
     }
 }
 
+impl From<&ScoringType> for i64 {
+    fn from(value: &ScoringType) -> Self {
+        match value {
+            ScoringType::Dynamic => 0,
+            ScoringType::Static => 1,
+        }
+    }
+}
+
+/// The curve along which a dynamic challenge's points decay as it is solved
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum ScoringCurve {
+    /// Falls slowly at first, then faster until it reaches the minimum. This is
+    /// the curve CTFd calls logarithmic.
+    #[default]
+    Quadratic,
+    /// Falls quickly for the first solves, then flattens out
+    Logarithmic,
+    /// Falls by the same amount with every solve
+    Linear,
+}
+
+impl From<i64> for ScoringCurve {
+    fn from(value: i64) -> Self {
+        match value {
+            1 => ScoringCurve::Logarithmic,
+            2 => ScoringCurve::Linear,
+            _ => ScoringCurve::Quadratic,
+        }
+    }
+}
+
+impl From<&ScoringCurve> for i64 {
+    fn from(value: &ScoringCurve) -> Self {
+        match value {
+            ScoringCurve::Quadratic => 0,
+            ScoringCurve::Logarithmic => 1,
+            ScoringCurve::Linear => 2,
+        }
+    }
+}
+
+/// Points of a dynamic challenge start at `initial` and reach `minimum` after
+/// `decay` solves, following `curve`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct DynamicScoring {
+    pub initial: i64,
+    pub minimum: i64,
+    pub decay: i64,
+    pub curve: ScoringCurve,
+}
+
+impl Default for DynamicScoring {
+    fn default() -> Self {
+        DynamicScoring {
+            initial: 500,
+            minimum: 100,
+            decay: 50,
+            curve: ScoringCurve::Quadratic,
+        }
+    }
+}
+
+impl DynamicScoring {
+    pub fn is_valid(&self) -> bool {
+        self.minimum >= 0 && self.initial >= self.minimum && self.decay > 0
+    }
+
+    /// Points after `solves` teams solved the challenge, the same as the
+    /// `rhombus_challenge_division_points` view computes them
+    pub fn points(&self, solves: u64) -> u64 {
+        let initial = self.initial as f64;
+        let minimum = self.minimum as f64;
+        let decay = self.decay as f64;
+        let solves = solves as f64;
+
+        let points = match self.curve {
+            ScoringCurve::Quadratic => {
+                ((minimum - initial) / decay.powi(2)) * solves.powi(2) + initial
+            }
+            ScoringCurve::Logarithmic => {
+                initial - (initial - minimum) * (1.0 + solves).ln() / (1.0 + decay).ln()
+            }
+            ScoringCurve::Linear => initial - (initial - minimum) * solves / decay,
+        };
+
+        points.round().max(minimum) as u64
+    }
+}
+
+/// How a submitted flag is compared against the challenge's `flag`
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum FlagMode {
+    /// Byte for byte equality
+    #[default]
+    Exact,
+    /// Equality ignoring case
+    CaseInsensitive,
+    /// Equality ignoring leading and trailing whitespace
+    Trimmed,
+    /// `flag` is a regular expression which must match the entire submission
+    Regex,
+    /// `flag` holds several accepted flags, one per line
+    Multiple,
+    /// `flag` is a template in which `{hmac}` is replaced with a value unique
+    /// to each team, see [Challenge::team_flag]
+    PerTeam,
+    /// `flag` is a template in which `{random}` is replaced with a random value
+    /// each time a flag is issued to a team by the challenge's backend. Only
+    /// flags issued to the submitting team are accepted.
+    Issued,
+}
+
+/// Placeholder replaced in [FlagMode::PerTeam] flags
+pub const TEAM_FLAG_PLACEHOLDER: &str = "{hmac}";
+
+/// Placeholder replaced in [FlagMode::Issued] flags
+pub const ISSUED_FLAG_PLACEHOLDER: &str = "{random}";
+
+impl FlagMode {
+    pub fn matches(&self, flag: &str, submitted: &str) -> bool {
+        match self {
+            FlagMode::Exact => flag == submitted,
+            FlagMode::CaseInsensitive => flag.to_lowercase() == submitted.to_lowercase(),
+            FlagMode::Trimmed => flag.trim() == submitted.trim(),
+            FlagMode::Regex => match Regex::new(&format!("^(?:{})$", flag)) {
+                Ok(regex) => regex.is_match(submitted).unwrap_or(false),
+                Err(error) => {
+                    tracing::error!(flag, "Invalid flag regex: {}", error);
+                    false
+                }
+            },
+            FlagMode::Multiple => flag.lines().any(|flag| flag == submitted),
+            FlagMode::PerTeam => flag == submitted,
+            // Issued flags are looked up in the database instead
+            FlagMode::Issued => false,
+        }
+    }
+}
+
+impl From<i64> for FlagMode {
+    fn from(value: i64) -> Self {
+        match value {
+            1 => FlagMode::CaseInsensitive,
+            2 => FlagMode::Trimmed,
+            3 => FlagMode::Regex,
+            4 => FlagMode::Multiple,
+            5 => FlagMode::PerTeam,
+            6 => FlagMode::Issued,
+            _ => FlagMode::Exact,
+        }
+    }
+}
+
+impl From<&FlagMode> for i64 {
+    fn from(value: &FlagMode) -> Self {
+        match value {
+            FlagMode::Exact => 0,
+            FlagMode::CaseInsensitive => 1,
+            FlagMode::Trimmed => 2,
+            FlagMode::Regex => 3,
+            FlagMode::Multiple => 4,
+            FlagMode::PerTeam => 5,
+            FlagMode::Issued => 6,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Clone)]
 pub struct ChallengeDivisionPoints {
     pub division_id: i64,
@@ -40,21 +215,132 @@ pub struct ChallengeAttachment {
     pub url: String,
 }
 
+/// A hint a team can unlock for a challenge. `id` is the position of the hint
+/// in the challenge, and unlocking it deducts `cost` from the team's points.
+#[derive(Debug, Serialize, Clone)]
+pub struct ChallengeHint {
+    pub id: i64,
+    pub content: String,
+    pub cost: i64,
+}
+
+/// Something a team has to achieve before a challenge is shown to it
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChallengePrerequisite {
+    /// The team has solved the challenge with id `challenge_id`
+    Solve { challenge_id: i64 },
+    /// The team has at least `points` points from solves in the category
+    CategoryPoints { category_id: i64, points: i64 },
+}
+
+/// How to run an isolated instance of a challenge for each team
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ChallengeInstance {
+    /// Passed as is to the [InstanceProvider](crate::InstanceProvider), for
+    /// example the command to run or the image to deploy
+    pub spec: String,
+
+    /// Seconds an instance runs for before it is stopped. Extending an
+    /// instance resets it to this many seconds from now.
+    #[serde(default = "default_instance_timeout")]
+    pub timeout: i64,
+}
+
+fn default_instance_timeout() -> i64 {
+    1800
+}
+
 #[derive(Debug, Serialize, Clone)]
 pub struct Challenge {
     pub id: i64,
     pub name: String,
     pub description: String,
+    pub description_html: String,
     pub category_id: i64,
     pub author_id: i64,
     pub healthscript: Option<String>,
     pub healthy: Option<bool>,
     pub last_healthcheck: Option<DateTime<Utc>>,
+    /// Seconds between healthchecks, or the default if `None`
+    pub healthcheck_interval: Option<i64>,
     pub division_points: Vec<ChallengeDivisionPoints>,
     pub scoring_type: ScoringType,
+    pub static_points: Option<i64>,
+    pub dynamic_scoring: DynamicScoring,
     pub flag: String,
+    pub flag_mode: FlagMode,
     pub ticket_template: Option<String>,
     pub attachments: Vec<ChallengeAttachment>,
+    pub hints: Vec<ChallengeHint>,
+    pub prerequisites: Vec<ChallengePrerequisite>,
+    pub instance: Option<ChallengeInstance>,
+    pub release_at: Option<DateTime<Utc>>,
+    pub hidden: bool,
+}
+
+impl Challenge {
+    /// Points for a solve once `solves` teams of a division solved it
+    pub fn points(&self, solves: u64) -> u64 {
+        match self.scoring_type {
+            ScoringType::Dynamic => self.dynamic_scoring.points(solves),
+            ScoringType::Static => self.static_points.unwrap_or_default() as u64,
+        }
+    }
+
+    /// Whether the challenge is out, meaning it has no `release_at` or its
+    /// release time has passed
+    pub fn is_released(&self) -> bool {
+        self.release_at
+            .map_or(true, |release_at| release_at <= Utc::now())
+    }
+
+    /// The flag `team_id` has to submit. In [FlagMode::PerTeam] this is
+    /// `flag` with the placeholder replaced by an HMAC of the team id, keyed
+    /// with a per challenge secret derived from `secret`. Every other mode
+    /// returns `flag` unchanged.
+    pub fn team_flag(&self, secret: &str, team_id: i64) -> String {
+        if self.flag_mode != FlagMode::PerTeam {
+            return self.flag.clone();
+        }
+
+        let challenge_key = hmac::sign(
+            &hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes()),
+            format!("challenge-flag:{}", self.id).as_bytes(),
+        );
+        let tag = hmac::sign(
+            &hmac::Key::new(hmac::HMAC_SHA256, challenge_key.as_ref()),
+            team_id.to_string().as_bytes(),
+        );
+        let hex = slice_to_hex_string(&tag.as_ref()[..16]);
+
+        self.flag.replace(TEAM_FLAG_PLACEHOLDER, &hex)
+    }
+
+    /// Whether `flag` has the shape of a [FlagMode::PerTeam] flag of this
+    /// challenge, which is `flag` with its placeholder replaced by 32
+    /// lowercase hex digits
+    pub fn is_team_flag_format(&self, flag: &str) -> bool {
+        let Some((prefix, suffix)) = self.flag.split_once(TEAM_FLAG_PLACEHOLDER) else {
+            return false;
+        };
+
+        flag.strip_prefix(prefix)
+            .and_then(|flag| flag.strip_suffix(suffix))
+            .is_some_and(|hex| {
+                hex.len() == 32
+                    && hex
+                        .bytes()
+                        .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+            })
+    }
+
+    /// A fresh [FlagMode::Issued] flag, with the placeholder in `flag`
+    /// replaced by a random value
+    pub fn random_flag(&self) -> String {
+        let random = slice_to_hex_string(&rand::random::<[u8; 16]>());
+        self.flag.replace(ISSUED_FLAG_PLACEHOLDER, &random)
+    }
 }
 
 #[derive(Debug, Serialize, Clone)]
@@ -84,6 +370,47 @@ pub struct ChallengeData {
     pub divisions: BTreeMap<i64, ChallengeDivision>,
 }
 
+impl ChallengeData {
+    /// Whether `team` has met every prerequisite of `challenge`. Category
+    /// points count each solved challenge at its highest value across
+    /// divisions. A challenge the team has already solved stays unlocked, even
+    /// if dynamic scoring has since dropped it below a points requirement.
+    pub fn is_unlocked(&self, challenge: &Challenge, team: &TeamInner) -> bool {
+        if team.solves.contains_key(&challenge.id) {
+            return true;
+        }
+
+        challenge
+            .prerequisites
+            .iter()
+            .all(|prerequisite| match prerequisite {
+                ChallengePrerequisite::Solve { challenge_id } => {
+                    team.solves.contains_key(challenge_id)
+                }
+                ChallengePrerequisite::CategoryPoints {
+                    category_id,
+                    points,
+                } => {
+                    let category_points = self
+                        .challenges
+                        .iter()
+                        .filter(|c| {
+                            c.category_id == *category_id && team.solves.contains_key(&c.id)
+                        })
+                        .map(|c| {
+                            c.division_points
+                                .iter()
+                                .map(|dp| dp.points)
+                                .max()
+                                .unwrap_or(0)
+                        })
+                        .sum::<u64>();
+                    category_points as i64 >= *points
+                }
+            })
+    }
+}
+
 pub type Challenges = Arc<ChallengeData>;
 
 #[derive(Debug, Serialize, Clone, PartialEq, PartialOrd)]
@@ -100,6 +427,35 @@ pub struct TeamUser {
     pub discord_id: Option<NonZeroU64>,
 }
 
+/// An IP address and user agent a user has made requests from
+#[derive(Debug, Serialize, Clone)]
+pub struct UserIp {
+    pub user_id: i64,
+    pub ip: IpAddr,
+    pub user_agent: String,
+    pub last_seen_at: DateTime<Utc>,
+    pub requests: u64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct UserListEntry {
+    pub id: i64,
+    pub name: String,
+    pub avatar_url: String,
+    pub discord_id: Option<NonZeroU64>,
+    pub team_id: i64,
+    pub team_name: String,
+    pub is_team_owner: bool,
+    pub disabled: bool,
+    pub is_admin: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct UserList {
+    pub num_pages: u64,
+    pub users: Vec<UserListEntry>,
+}
+
 #[derive(Debug, Serialize, Clone)]
 pub struct Writeup {
     pub user_id: i64,
@@ -114,6 +470,7 @@ pub struct TeamInner {
     pub users: BTreeMap<i64, TeamUser>,
     pub solves: BTreeMap<i64, ChallengeSolve>,
     pub writeups: BTreeMap<i64, Vec<Writeup>>,
+    pub unlocked_hints: BTreeMap<i64, Vec<i64>>,
 }
 
 pub type Team = Arc<TeamInner>;
@@ -129,6 +486,80 @@ pub type TeamMeta = Arc<TeamMetaInner>;
 #[derive(Debug, Serialize, Clone)]
 pub struct FirstBloods {
     pub division_ids: Vec<i64>,
+
+    /// Whether nobody on the team had solved the challenge before
+    pub first_team_solve: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct WrongSubmission {
+    pub user_id: i64,
+    pub user_name: String,
+    pub team_id: i64,
+    pub team_name: String,
+    pub challenge_id: i64,
+    pub flag: String,
+    pub submitted_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct WrongSubmissionList {
+    pub num_pages: u64,
+    pub submissions: Vec<WrongSubmission>,
+}
+
+/// The same wrong flag submitted to a challenge by more than one team, which
+/// usually means the teams are sharing answers.
+#[derive(Debug, Serialize, Clone)]
+pub struct SharedWrongFlag {
+    pub challenge_id: i64,
+    pub flag: String,
+    pub teams: BTreeMap<i64, String>,
+}
+
+/// The result of running a challenge's healthscript once
+#[derive(Debug, Serialize, Clone)]
+pub struct Healthcheck {
+    pub challenge_id: i64,
+    pub checked_at: DateTime<Utc>,
+    /// `None` if the healthscript could not be parsed
+    pub healthy: Option<bool>,
+    /// How long the healthscript took to run, in milliseconds
+    pub latency: i64,
+    pub error: Option<String>,
+}
+
+/// A message from the organizers, listed on the announcements page
+#[derive(Debug, Serialize, Clone)]
+pub struct Announcement {
+    pub id: i64,
+    pub title: String,
+    pub content: String,
+    pub content_html: String,
+    /// Shown at the top of every page while it is the newest such announcement
+    pub banner: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// A team submitted the per team flag belonging to another team
+#[derive(Debug, Serialize, Clone)]
+pub struct CheatingIncident {
+    pub user_id: i64,
+    pub user_name: String,
+    pub team_id: i64,
+    pub team_name: String,
+    pub flag_team_id: i64,
+    pub flag_team_name: String,
+    pub challenge_id: i64,
+    pub flag: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct CheatingIncidentList {
+    pub num_pages: u64,
+    pub incidents: Vec<CheatingIncident>,
 }
 
 #[derive(Debug, Serialize, Clone)]
@@ -168,6 +599,13 @@ pub struct Email {
     pub verified: bool,
 }
 
+#[derive(Debug, Serialize, Clone)]
+pub struct ApiToken {
+    pub id: i64,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Serialize, Clone)]
 pub struct TeamStandingEntry {
     pub points: u64,
@@ -190,16 +628,115 @@ pub struct Ticket {
     pub email_in_reply_to: Option<String>,
 }
 
+/// An announcement as it should exist after [Database::create_announcement]
+/// or [Database::update_announcement].
+#[derive(Debug, Clone)]
+pub struct AnnouncementDefinition {
+    pub title: String,
+    pub content: String,
+    pub banner: bool,
+}
+
+impl AnnouncementDefinition {
+    /// The Markdown `content` rendered to HTML. Rendered once when the
+    /// announcement is saved.
+    pub fn content_html(&self) -> String {
+        render_markdown(&self.content, &[])
+    }
+}
+
+/// An author as it should exist after [Database::sync_challenges].
+#[derive(Debug, Clone)]
+pub struct AuthorDefinition {
+    pub id: i64,
+    pub name: String,
+    pub avatar: String,
+    pub discord_id: NonZeroU64,
+}
+
+/// A category as it should exist after [Database::sync_challenges].
+#[derive(Debug, Clone)]
+pub struct CategoryDefinition {
+    pub id: i64,
+    pub name: String,
+    pub color: String,
+}
+
+/// A challenge as it should exist after [Database::sync_challenges].
+#[derive(Debug, Clone)]
+pub struct ChallengeDefinition {
+    pub id: i64,
+    pub name: String,
+    pub description: String,
+    pub flag: String,
+    pub flag_mode: FlagMode,
+    pub category_id: i64,
+    pub author_id: i64,
+    pub ticket_template: Option<String>,
+    pub scoring_type: ScoringType,
+    pub static_points: Option<i64>,
+    pub dynamic_scoring: DynamicScoring,
+    pub healthscript: Option<String>,
+    pub healthcheck_interval: Option<i64>,
+    pub attachments: Vec<ChallengeAttachment>,
+    pub hints: Vec<ChallengeHint>,
+    pub prerequisites: Vec<ChallengePrerequisite>,
+    pub instance: Option<ChallengeInstance>,
+    pub release_at: Option<DateTime<Utc>>,
+    pub hidden: bool,
+    /// Created from the admin panel rather than by a challenge loader, so
+    /// [Database::sync_challenges] leaves it alone. A challenge which a loader
+    /// also defines is never treated as admin created.
+    pub admin_created: bool,
+}
+
+impl ChallengeDefinition {
+    /// The Markdown `description` rendered to HTML. Rendered once when the
+    /// challenge is saved rather than on every request.
+    pub fn description_html(&self) -> String {
+        render_markdown(&self.description, &self.attachments)
+    }
+}
+
+/// The complete set of challenges, categories and authors. Anything in the database
+/// which is not part of the set is deleted when it is synced, except for admin
+/// created challenges and the categories and authors they still use.
+#[derive(Debug, Clone, Default)]
+pub struct ChallengeDefinitions {
+    pub authors: Vec<AuthorDefinition>,
+    pub categories: Vec<CategoryDefinition>,
+    pub challenges: Vec<ChallengeDefinition>,
+}
+
 #[async_trait]
 pub trait Database {
     async fn migrate(&self) -> Result<()>;
     async fn get_challenges(&self) -> Result<Challenges>;
+    async fn sync_challenges(&self, definitions: &ChallengeDefinitions) -> Result<()>;
+    async fn upsert_challenge(&self, challenge: &ChallengeDefinition) -> Result<()>;
+    async fn delete_challenge(&self, challenge_id: i64) -> Result<()>;
+    async fn upsert_category(&self, category: &CategoryDefinition) -> Result<()>;
+    /// Deletes a category unless a challenge is still in it, returning whether
+    /// it was deleted
+    async fn delete_category(&self, category_id: i64) -> Result<bool>;
+    async fn upsert_author(&self, author: &AuthorDefinition) -> Result<()>;
+    /// Deletes an author unless a challenge is still credited to them,
+    /// returning whether they were deleted
+    async fn delete_author(&self, author_id: i64) -> Result<bool>;
     async fn set_challenge_health(
         &self,
         challenge_id: i64,
         healthy: Option<bool>,
         checked_at: DateTime<Utc>,
     ) -> Result<()>;
+    async fn add_healthcheck(&self, healthcheck: &Healthcheck) -> Result<()>;
+    /// Every healthcheck of every challenge since `since`, oldest first
+    async fn get_healthchecks(&self, since: DateTime<Utc>) -> Result<Vec<Healthcheck>>;
+    /// Marks every challenge whose `release_at` is at or before `now` as
+    /// released, returning the ids of the challenges this call released
+    async fn release_challenges(&self, now: DateTime<Utc>) -> Result<Vec<i64>>;
+    /// Returns the user's id, their team's id, and whether the user was just
+    /// created along with a new team of their own
     async fn upsert_user_by_discord_id(
         &self,
         name: &str,
@@ -207,19 +744,19 @@ pub trait Database {
         avatar: &str,
         discord_id: NonZeroU64,
         user_id: Option<i64>,
-    ) -> Result<(i64, i64)>;
+    ) -> Result<(i64, i64, bool)>;
     async fn upsert_user_by_email(
         &self,
         name: &str,
         email: &str,
         avatar: &str,
-    ) -> Result<(i64, i64)>;
+    ) -> Result<(i64, i64, bool)>;
     async fn upsert_user_by_credentials(
         &self,
         username: &str,
         avatar: &str,
         password: &str,
-    ) -> Result<Option<(i64, i64)>>;
+    ) -> Result<Option<(i64, i64, bool)>>;
     async fn insert_track(
         &self,
         ip: IpAddr,
@@ -242,9 +779,55 @@ pub trait Database {
         team_id: i64,
         challenge: &Challenge,
     ) -> Result<FirstBloods>;
+    async fn unlock_hint(
+        &self,
+        user_id: i64,
+        team_id: i64,
+        challenge_id: i64,
+        hint: &ChallengeHint,
+    ) -> Result<()>;
+    async fn add_wrong_submission(
+        &self,
+        user_id: i64,
+        team_id: i64,
+        challenge_id: i64,
+        flag: &str,
+    ) -> Result<()>;
+    async fn get_team_wrong_submission_times(
+        &self,
+        team_id: i64,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<(i64, DateTime<Utc>)>>;
+    async fn get_wrong_submissions(
+        &self,
+        team_id: Option<i64>,
+        challenge_id: Option<i64>,
+        page: u64,
+    ) -> Result<WrongSubmissionList>;
+    async fn get_shared_wrong_flags(&self) -> Result<Vec<SharedWrongFlag>>;
+    async fn get_team_ids(&self) -> Result<Vec<i64>>;
+    async fn add_cheating_incident(
+        &self,
+        user_id: i64,
+        team_id: i64,
+        flag_team_id: i64,
+        challenge_id: i64,
+        flag: &str,
+    ) -> Result<()>;
+    async fn get_cheating_incidents(&self, page: u64) -> Result<CheatingIncidentList>;
+    /// Records that `flag` was issued to `team_id` for [FlagMode::Issued] challenge `challenge_id`
+    async fn add_issued_flag(&self, challenge_id: i64, team_id: i64, flag: &str) -> Result<()>;
+    /// The team `flag` was issued to for `challenge_id`, if it was ever issued
+    async fn get_issued_flag_team_id(&self, challenge_id: i64, flag: &str) -> Result<Option<i64>>;
     async fn get_user_from_id(&self, user_id: i64) -> Result<User>;
     async fn get_user_from_discord_id(&self, discord_id: NonZeroU64) -> Result<User>;
     async fn kick_user(&self, user_id: i64, team_id: i64) -> Result<()>;
+    async fn get_users(&self, search: Option<&str>, page: u64) -> Result<UserList>;
+    /// Every IP address the members of `team_id` made requests from, most
+    /// recently seen first
+    async fn get_team_ips(&self, team_id: i64) -> Result<Vec<UserIp>>;
+    async fn set_user_disabled(&self, user_id: i64, disabled: bool) -> Result<()>;
+    async fn set_user_admin(&self, user_id: i64, is_admin: bool) -> Result<()>;
     async fn roll_invite_token(&self, team_id: i64) -> Result<String>;
     async fn set_team_name(&self, team_id: i64, new_team_name: &str) -> Result<()>;
     async fn add_writeup(
@@ -280,9 +863,21 @@ pub trait Database {
     async fn get_ticket_number_by_message_id(&self, message_id: &str) -> Result<u64>;
     async fn save_settings(&self, settings: &Settings) -> Result<()>;
     async fn load_settings(&self, settings: &mut Settings) -> Result<()>;
-    async fn get_scoreboard(&self, division_id: i64) -> Result<Scoreboard>;
-    async fn get_leaderboard(&self, division_id: i64, page: Option<u64>) -> Result<Leaderboard>;
+    /// `frozen_at` limits the standings to points earned before that time
+    async fn get_scoreboard(
+        &self,
+        division_id: i64,
+        frozen_at: Option<DateTime<Utc>>,
+    ) -> Result<Scoreboard>;
+    async fn get_leaderboard(
+        &self,
+        division_id: i64,
+        page: Option<u64>,
+        frozen_at: Option<DateTime<Utc>>,
+    ) -> Result<Leaderboard>;
     async fn get_emails_for_user_id(&self, user_id: i64) -> Result<Vec<Email>>;
+    /// One verified email address of every user who is not banned
+    async fn get_mailing_list(&self) -> Result<Vec<String>>;
     async fn create_email_verification_callback_code(
         &self,
         user_id: i64,
@@ -292,6 +887,10 @@ pub trait Database {
     async fn create_email_signin_callback_code(&self, email: &str) -> Result<String>;
     async fn verify_email_signin_callback_code(&self, code: &str) -> Result<String>;
     async fn delete_email(&self, user_id: i64, email: &str) -> Result<()>;
+    async fn create_api_token(&self, user_id: i64, name: &str) -> Result<String>;
+    async fn get_api_tokens(&self, user_id: i64) -> Result<Vec<ApiToken>>;
+    async fn delete_api_token(&self, user_id: i64, token_id: i64) -> Result<()>;
+    async fn get_user_id_from_api_token(&self, token: &str) -> Result<Option<i64>>;
     async fn get_user_divisions(&self, user_id: i64) -> Result<Vec<i64>>;
     async fn set_user_division(
         &self,
@@ -303,7 +902,271 @@ pub trait Database {
     async fn insert_divisions(&self, divisions: &[Division]) -> Result<()>;
     async fn get_team_divisions(&self, team_id: i64) -> Result<Vec<i64>>;
     async fn set_team_division(&self, team_id: i64, division_id: i64, join: bool) -> Result<()>;
-    async fn get_team_standings(&self, team_id: i64) -> Result<TeamStandings>;
+    /// `frozen_at` limits the standings to points earned before that time
+    async fn get_team_standings(
+        &self,
+        team_id: i64,
+        frozen_at: Option<DateTime<Utc>>,
+    ) -> Result<TeamStandings>;
+    /// How many teams of each division had solved each challenge by `at`,
+    /// keyed by challenge and division id
+    async fn get_division_solve_counts(
+        &self,
+        at: DateTime<Utc>,
+    ) -> Result<BTreeMap<(i64, i64), u64>>;
+    /// Every announcement, newest first
+    async fn get_announcements(&self) -> Result<Vec<Announcement>>;
+    async fn create_announcement(
+        &self,
+        announcement: &AnnouncementDefinition,
+    ) -> Result<Announcement>;
+    async fn update_announcement(
+        &self,
+        announcement_id: i64,
+        announcement: &AnnouncementDefinition,
+    ) -> Result<Announcement>;
+    async fn delete_announcement(&self, announcement_id: i64) -> Result<()>;
     async fn upload_file(&self, hash: &str, filename: &str, bytes: &[u8]) -> Result<()>;
     async fn download_file(&self, hash: &str) -> Result<(Bytes, String)>;
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::collections::BTreeMap;
+
+    use chrono::Utc;
+
+    use super::{
+        Challenge, ChallengeData, ChallengeDivisionPoints, ChallengePrerequisite, ChallengeSolve,
+        DynamicScoring, FlagMode, ScoringCurve, ScoringType, TeamInner,
+    };
+
+    /// A released, visible challenge worth 100 static points with no
+    /// prerequisites
+    pub fn challenge(id: i64) -> Challenge {
+        Challenge {
+            id,
+            name: format!("challenge {}", id),
+            description: String::new(),
+            description_html: String::new(),
+            category_id: 1,
+            author_id: 1,
+            healthscript: None,
+            healthy: None,
+            last_healthcheck: None,
+            healthcheck_interval: None,
+            division_points: vec![],
+            scoring_type: ScoringType::Static,
+            static_points: Some(100),
+            dynamic_scoring: DynamicScoring::default(),
+            flag: "flag{test}".to_owned(),
+            flag_mode: FlagMode::Exact,
+            ticket_template: None,
+            attachments: vec![],
+            hints: vec![],
+            prerequisites: vec![],
+            instance: None,
+            release_at: None,
+            hidden: false,
+        }
+    }
+
+    /// A team which has solved each of `solves`
+    pub fn team(id: i64, solves: &[i64]) -> TeamInner {
+        TeamInner {
+            id,
+            name: format!("team {}", id),
+            invite_token: String::new(),
+            users: BTreeMap::new(),
+            solves: solves
+                .iter()
+                .map(|challenge_id| {
+                    (
+                        *challenge_id,
+                        ChallengeSolve {
+                            solved_at: Utc::now(),
+                            user_id: id,
+                        },
+                    )
+                })
+                .collect(),
+            writeups: BTreeMap::new(),
+            unlocked_hints: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn flag_modes() {
+        assert!(FlagMode::Exact.matches("flag{a}", "flag{a}"));
+        assert!(!FlagMode::Exact.matches("flag{a}", "FLAG{A}"));
+        assert!(FlagMode::CaseInsensitive.matches("flag{a}", "FLAG{A}"));
+        assert!(FlagMode::Trimmed.matches("flag{a}", " flag{a}\n"));
+        assert!(FlagMode::Regex.matches(r"flag\{\d+\}", "flag{123}"));
+        assert!(!FlagMode::Regex.matches(r"flag\{\d+\}", "xflag{123}"));
+        assert!(!FlagMode::Regex.matches("a|b", "ab"));
+        assert!(FlagMode::Multiple.matches("flag{a}\nflag{b}", "flag{b}"));
+        assert!(!FlagMode::Multiple.matches("flag{a}\nflag{b}", "flag{a}\nflag{b}"));
+        assert!(FlagMode::PerTeam.matches("flag{0123}", "flag{0123}"));
+    }
+
+    #[test]
+    fn dynamic_points() {
+        for curve in [
+            ScoringCurve::Quadratic,
+            ScoringCurve::Logarithmic,
+            ScoringCurve::Linear,
+        ] {
+            let scoring = DynamicScoring {
+                curve,
+                ..Default::default()
+            };
+            assert_eq!(500, scoring.points(0));
+            assert_eq!(100, scoring.points(50));
+            assert_eq!(100, scoring.points(500));
+        }
+
+        let linear = DynamicScoring {
+            curve: ScoringCurve::Linear,
+            ..Default::default()
+        };
+        assert_eq!(300, linear.points(25));
+    }
+
+    #[test]
+    fn dynamic_points_edges() {
+        for curve in [
+            ScoringCurve::Quadratic,
+            ScoringCurve::Logarithmic,
+            ScoringCurve::Linear,
+        ] {
+            let instant = DynamicScoring {
+                initial: 500,
+                minimum: 100,
+                decay: 1,
+                curve,
+            };
+            assert_eq!(500, instant.points(0));
+            assert_eq!(100, instant.points(1));
+            assert_eq!(100, instant.points(2));
+
+            let scoring = DynamicScoring {
+                curve,
+                ..Default::default()
+            };
+            for solves in [51, 100, 10_000] {
+                assert_eq!(100, scoring.points(solves), "{curve:?} {solves}");
+            }
+            for solves in 1..50 {
+                assert!(scoring.points(solves) <= scoring.points(solves - 1));
+            }
+        }
+    }
+
+    #[test]
+    fn unlocked_challenges() {
+        let division_points = |points| {
+            vec![
+                ChallengeDivisionPoints {
+                    division_id: 1,
+                    points,
+                    solves: 1,
+                },
+                ChallengeDivisionPoints {
+                    division_id: 2,
+                    points: points / 2,
+                    solves: 3,
+                },
+            ]
+        };
+        let mut first = challenge(1);
+        first.division_points = division_points(100);
+        let mut second = challenge(2);
+        second.division_points = division_points(150);
+        let mut other_category = challenge(3);
+        other_category.category_id = 2;
+        other_category.division_points = division_points(500);
+
+        let mut after_first = challenge(4);
+        after_first.prerequisites = vec![ChallengePrerequisite::Solve { challenge_id: 1 }];
+        let mut after_points = challenge(5);
+        after_points.prerequisites = vec![ChallengePrerequisite::CategoryPoints {
+            category_id: 1,
+            points: 250,
+        }];
+
+        let challenge_data = ChallengeData {
+            challenges: vec![first, second, other_category],
+            categories: vec![],
+            authors: Default::default(),
+            divisions: Default::default(),
+        };
+        let unlocked = |challenge: &Challenge, solves: &[i64]| {
+            challenge_data.is_unlocked(challenge, &team(1, solves))
+        };
+
+        assert!(unlocked(&challenge(1), &[]));
+        assert!(!unlocked(&after_first, &[]));
+        assert!(!unlocked(&after_first, &[2]));
+        assert!(unlocked(&after_first, &[1]));
+
+        // solves count at their highest points across divisions, and only
+        // within the category
+        assert!(!unlocked(&after_points, &[1, 3]));
+        assert!(unlocked(&after_points, &[1, 2]));
+
+        // a challenge stays unlocked once solved
+        assert!(unlocked(&after_first, &[4]));
+        assert!(unlocked(&after_points, &[5]));
+    }
+
+    #[test]
+    fn team_flags() {
+        let mut per_team = challenge(1);
+        per_team.flag = "flag{{hmac}}".to_owned();
+        per_team.flag_mode = FlagMode::PerTeam;
+
+        let flag = per_team.team_flag("secret", 1);
+        assert_eq!(flag, per_team.team_flag("secret", 1));
+        assert_ne!(flag, per_team.team_flag("secret", 2));
+        assert_ne!(flag, per_team.team_flag("other secret", 1));
+        let other_challenge = Challenge {
+            id: 2,
+            ..per_team.clone()
+        };
+        assert_ne!(flag, other_challenge.team_flag("secret", 1));
+        assert!(per_team.is_team_flag_format(&flag));
+        assert!(!per_team.is_team_flag_format("flag{0123}"));
+        assert!(!per_team.is_team_flag_format(&flag.to_uppercase()));
+        assert!(!per_team.is_team_flag_format(&format!("x{}", flag)));
+
+        assert_eq!("flag{test}", challenge(1).team_flag("secret", 1));
+        assert!(!challenge(1).is_team_flag_format("flag{test}"));
+    }
+
+    #[test]
+    fn dynamic_scoring_validity() {
+        assert!(DynamicScoring::default().is_valid());
+        assert!(DynamicScoring {
+            initial: 100,
+            minimum: 100,
+            decay: 1,
+            curve: ScoringCurve::Linear,
+        }
+        .is_valid());
+        assert!(!DynamicScoring {
+            decay: 0,
+            ..Default::default()
+        }
+        .is_valid());
+        assert!(!DynamicScoring {
+            minimum: -1,
+            ..Default::default()
+        }
+        .is_valid());
+        assert!(!DynamicScoring {
+            initial: 50,
+            ..Default::default()
+        }
+        .is_valid());
+    }
+}
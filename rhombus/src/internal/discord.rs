@@ -1,5 +1,6 @@
 use std::{
     collections::{btree_map, BTreeMap},
+    net::IpAddr,
     num::NonZeroU64,
     sync::Arc,
 };
@@ -12,35 +13,61 @@ use serenity::{
     all::{
         ButtonStyle, ChannelId, ChannelType, CreateActionRow, CreateAttachment, CreateButton,
         CreateEmbed, CreateEmbedAuthor, CreateMessage, CreateThread, EditMessage, EditThread,
-        GatewayIntents, GetMessages, Http, UserId,
+        GatewayIntents, GetMessages, GuildId, Http, Member, RoleId, UserId,
     },
     Client,
 };
 use tokio::sync::{Mutex, RwLock};
+use unicode_segmentation::UnicodeSegmentation;
 
 use crate::{
     errors::RhombusError,
     internal::{
+        announcements,
         auth::User,
         database::provider::{
-            Author, Category, Challenge, ChallengeDivision, Connection, FirstBloods, Team, Ticket,
+            Announcement, AnnouncementDefinition, Author, Category, Challenge, ChallengeData,
+            ChallengeDivision, Connection, FirstBloods, Healthcheck, Team, TeamInner, Ticket,
         },
+        division::Division,
         email::outbound_mailer::OutboundMailer,
+        events::{EventBus, RhombusEvent},
+        health::check_challenge,
+        routes::{challenges::challenge_unlocked, scoreboard::freeze_challenges},
         settings::Settings,
     },
     Result,
 };
 
+/// Most members Discord returns per page when listing a guild
+const GUILD_MEMBERS_PAGE_SIZE: u64 = 1000;
+
 pub struct Bot {
     http: Arc<Http>,
     db: Connection,
     settings: &'static RwLock<Settings>,
+    events: &'static EventBus,
 }
 
 pub struct Data {
     settings: &'static RwLock<Settings>,
     db: Connection,
     outbound_mailer: Option<&'static OutboundMailer>,
+    events: &'static EventBus,
+    divisions: &'static [Division],
+}
+
+impl Data {
+    /// A handle for the bot's own actions, such as opening support threads,
+    /// from within a command
+    fn bot(&self, http: Arc<Http>) -> Bot {
+        Bot {
+            http,
+            db: self.db,
+            settings: self.settings,
+            events: self.events,
+        }
+    }
 }
 pub type DiscordError = Box<dyn std::error::Error + Send + Sync>;
 pub type Context<'a> = poise::Context<'a, Data, DiscordError>;
@@ -75,9 +102,526 @@ pub async fn whois(
     Ok(())
 }
 
+/// The CTF user signed in with the Discord account running a command, or
+/// `None` once they have been told why they can not use it
+async fn linked_user(ctx: Context<'_>) -> std::result::Result<Option<User>, DiscordError> {
+    match ctx
+        .data()
+        .db
+        .get_user_from_discord_id(ctx.author().id.into())
+        .await
+    {
+        Ok(user) if user.disabled => {
+            ctx.reply("Your account is disabled").await?;
+            Ok(None)
+        }
+        Ok(user) => Ok(Some(user)),
+        Err(_) => {
+            let location_url = ctx.data().settings.read().await.location_url.clone();
+            ctx.reply(format!(
+                "Sign in with Discord on [the CTF website]({}/signin) to use this command",
+                location_url
+            ))
+            .await?;
+            Ok(None)
+        }
+    }
+}
+
+/// The challenges `user` can see on the website
+async fn visible_challenges<'a>(
+    data: &Data,
+    user: &User,
+    team: &TeamInner,
+    challenge_data: &'a ChallengeData,
+) -> Vec<&'a Challenge> {
+    if !user.is_admin && !data.settings.read().await.has_started() {
+        return vec![];
+    }
+
+    challenge_data
+        .challenges
+        .iter()
+        .filter(|challenge| {
+            !challenge.hidden && challenge_unlocked(user, team, challenge_data, challenge)
+        })
+        .collect()
+}
+
+/// The first division of the CTF which `team_divisions` contains
+fn primary_division<'a>(divisions: &'a [Division], team_divisions: &[i64]) -> &'a Division {
+    divisions
+        .iter()
+        .find(|division| team_divisions.contains(&division.id))
+        .unwrap_or(&divisions[0])
+}
+
+/// When the standings shown to `user` were frozen, which is never for admins
+async fn standings_frozen_at(data: &Data, user: &User) -> Option<DateTime<Utc>> {
+    if user.is_admin {
+        return None;
+    }
+    data.settings.read().await.frozen_at()
+}
+
+/// The points of `challenge_id` in `division_id` as listed in `challenge_data`,
+/// which may be frozen
+fn division_points(challenge_data: &ChallengeData, challenge_id: i64, division_id: i64) -> u64 {
+    challenge_data
+        .challenges
+        .iter()
+        .find(|challenge| challenge.id == challenge_id)
+        .and_then(|challenge| {
+            challenge
+                .division_points
+                .iter()
+                .find(|points| points.division_id == division_id)
+        })
+        .map_or(0, |points| points.points)
+}
+
+/// The division roles to add (`true`) or remove (`false`) for a member with
+/// `roles`, whose team is in `team_divisions`
+fn division_role_changes(
+    divisions: &[Division],
+    roles: &[RoleId],
+    team_divisions: &[i64],
+) -> Vec<(RoleId, bool)> {
+    divisions
+        .iter()
+        .filter_map(|division| {
+            let role_id = RoleId::from(division.discord_role_id?);
+            let has_role = roles.contains(&role_id);
+            let in_division = team_divisions.contains(&division.id);
+            (in_division != has_role).then_some((role_id, in_division))
+        })
+        .collect()
+}
+
+/// Embed field values are limited to 1024 characters
+fn truncate_field(value: String) -> String {
+    if value.chars().count() > 1024 {
+        format!("{}…", value.chars().take(1023).collect::<String>())
+    } else {
+        value
+    }
+}
+
+async fn autocomplete_division<'a>(
+    ctx: Context<'_>,
+    partial: &'a str,
+) -> impl Iterator<Item = String> + 'a {
+    let partial = partial.to_lowercase();
+    ctx.data()
+        .divisions
+        .iter()
+        .filter(move |division| division.name.to_lowercase().contains(&partial))
+        .map(|division| division.name.clone())
+}
+
+async fn autocomplete_challenge(ctx: Context<'_>, partial: &str) -> Vec<String> {
+    let data = ctx.data();
+    let Ok(user) = data
+        .db
+        .get_user_from_discord_id(ctx.author().id.into())
+        .await
+    else {
+        return vec![];
+    };
+    let (Ok(challenge_data), Ok(team)) = tokio::join!(
+        data.db.get_challenges(),
+        data.db.get_team_from_id(user.team_id)
+    ) else {
+        return vec![];
+    };
+
+    let partial = partial.to_lowercase();
+    visible_challenges(data, &user, &team, &challenge_data)
+        .await
+        .into_iter()
+        .filter(|challenge| challenge.name.to_lowercase().contains(&partial))
+        // Discord shows at most 25 choices
+        .take(25)
+        .map(|challenge| challenge.name.clone())
+        .collect()
+}
+
+/// Show the top 10 teams of a division
+#[poise::command(slash_command)]
+pub async fn scoreboard(
+    ctx: Context<'_>,
+    #[description = "Division to show, the first one if not given"]
+    #[autocomplete = "autocomplete_division"]
+    division: Option<String>,
+) -> std::result::Result<(), DiscordError> {
+    let divisions = ctx.data().divisions;
+    let Some(division) = (match &division {
+        Some(name) => divisions
+            .iter()
+            .find(|division| division.name.eq_ignore_ascii_case(name)),
+        None => divisions.first(),
+    }) else {
+        ctx.reply("Could not find division").await?;
+        return Ok(());
+    };
+
+    let (location_url, frozen_at) = {
+        let settings = ctx.data().settings.read().await;
+        (settings.location_url.clone(), settings.frozen_at())
+    };
+
+    let leaderboard = ctx
+        .data()
+        .db
+        .get_leaderboard(division.id, Some(0), frozen_at)
+        .await?;
+
+    let mut description = leaderboard
+        .entries
+        .iter()
+        .take(10)
+        .map(|entry| {
+            format!(
+                "**{}.** [{}]({}/team/{}) {} points",
+                entry.rank, entry.team_name, location_url, entry.team_id, entry.score
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    if description.is_empty() {
+        description = "No team has scored yet".to_owned();
+    }
+    if let Some(frozen_at) = frozen_at {
+        description.push_str(&format!(
+            "\n\n:snowflake: Frozen since <t:{}:f>",
+            frozen_at.timestamp()
+        ));
+    }
+
+    ctx.send(
+        poise::CreateReply::default().embed(
+            CreateEmbed::new()
+                .color((0x00, 0x99, 0xff))
+                .title(format!("{} Scoreboard", division.name))
+                .url(format!("{}/scoreboard/{}", location_url, division.id))
+                .description(description),
+        ),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Show your team, its members and standings
+#[poise::command(slash_command, ephemeral)]
+pub async fn team(ctx: Context<'_>) -> std::result::Result<(), DiscordError> {
+    let Some(user) = linked_user(ctx).await? else {
+        return Ok(());
+    };
+
+    let frozen_at = standings_frozen_at(ctx.data(), &user).await;
+    let team = ctx.data().db.get_team_from_id(user.team_id);
+    let standings = ctx.data().db.get_team_standings(user.team_id, frozen_at);
+    let (team, standings) = futures::future::try_join(team, standings).await?;
+
+    let location_url = ctx.data().settings.read().await.location_url.clone();
+
+    let members = team
+        .users
+        .values()
+        .map(|member| {
+            let name = if let Some(discord_id) = member.discord_id {
+                format!("<@{}>", discord_id)
+            } else {
+                member.name.clone()
+            };
+            if member.is_team_owner {
+                format!("{} :crown:", name)
+            } else {
+                name
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let standings = ctx
+        .data()
+        .divisions
+        .iter()
+        .filter_map(|division| {
+            standings.standings.get(&division.id).map(|standing| {
+                format!(
+                    "{}: **#{}** with {} points",
+                    division.name, standing.rank, standing.points
+                )
+            })
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    ctx.send(
+        poise::CreateReply::default().embed(
+            CreateEmbed::new()
+                .color((0x00, 0x99, 0xff))
+                .title(&team.name)
+                .url(format!("{}/team/{}", location_url, team.id))
+                .field(
+                    ":busts_in_silhouette: Members",
+                    truncate_field(members),
+                    true,
+                )
+                .field(
+                    ":trophy: Standings",
+                    if standings.is_empty() {
+                        "Not ranked yet".to_owned()
+                    } else {
+                        standings
+                    },
+                    true,
+                )
+                .field(
+                    ":triangular_flag_on_post: Solves",
+                    team.solves.len().to_string(),
+                    true,
+                ),
+        ),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// List the challenges you can see, marking the ones your team solved
+#[poise::command(slash_command, ephemeral)]
+pub async fn challenges(ctx: Context<'_>) -> std::result::Result<(), DiscordError> {
+    let Some(user) = linked_user(ctx).await? else {
+        return Ok(());
+    };
+
+    let challenge_data = ctx.data().db.get_challenges();
+    let team = ctx.data().db.get_team_from_id(user.team_id);
+    let team_divisions = ctx.data().db.get_team_divisions(user.team_id);
+    let (challenge_data, team, team_divisions) =
+        futures::future::try_join3(challenge_data, team, team_divisions).await?;
+
+    let location_url = ctx.data().settings.read().await.location_url.clone();
+    let division = primary_division(ctx.data().divisions, &team_divisions);
+    let challenges = visible_challenges(ctx.data(), &user, &team, &challenge_data).await;
+
+    // unlocking goes by live points, only what is shown is frozen
+    let frozen_at = standings_frozen_at(ctx.data(), &user).await;
+    let frozen_challenge_data =
+        freeze_challenges(ctx.data().db, challenge_data.clone(), frozen_at).await?;
+
+    let mut embed = CreateEmbed::new()
+        .color((0x00, 0x99, 0xff))
+        .title("Challenges")
+        .url(format!("{}/challenges", location_url));
+
+    // Discord allows at most 25 fields per embed
+    let mut num_challenges = 0;
+    for category in challenge_data.categories.iter().take(25) {
+        let lines = challenges
+            .iter()
+            .filter(|challenge| challenge.category_id == category.id)
+            .map(|challenge| {
+                format!(
+                    "{} {} ({} points)",
+                    if team.solves.contains_key(&challenge.id) {
+                        ":white_check_mark:"
+                    } else {
+                        ":black_large_square:"
+                    },
+                    challenge.name,
+                    division_points(&frozen_challenge_data, challenge.id, division.id)
+                )
+            })
+            .collect::<Vec<_>>();
+        if lines.is_empty() {
+            continue;
+        }
+        num_challenges += lines.len();
+        embed = embed.field(&category.name, truncate_field(lines.join("\n")), false);
+    }
+
+    if num_challenges == 0 {
+        embed = embed.description("No challenges are available yet");
+    } else {
+        embed = embed.description(format!(
+            "Your team solved {} of {} challenges. Points are for the {} division.",
+            challenges
+                .iter()
+                .filter(|challenge| team.solves.contains_key(&challenge.id))
+                .count(),
+            num_challenges,
+            division.name
+        ));
+    }
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+
+    Ok(())
+}
+
+/// Show how many teams solved a challenge, and whether your team did
+#[poise::command(slash_command, ephemeral)]
+pub async fn solves(
+    ctx: Context<'_>,
+    #[description = "Challenge to look up"]
+    #[autocomplete = "autocomplete_challenge"]
+    challenge: String,
+) -> std::result::Result<(), DiscordError> {
+    let Some(user) = linked_user(ctx).await? else {
+        return Ok(());
+    };
+
+    let challenge_data = ctx.data().db.get_challenges();
+    let team = ctx.data().db.get_team_from_id(user.team_id);
+    let (challenge_data, team) = futures::future::try_join(challenge_data, team).await?;
+
+    let Some(challenge) = visible_challenges(ctx.data(), &user, &team, &challenge_data)
+        .await
+        .into_iter()
+        .find(|c| c.name.eq_ignore_ascii_case(&challenge))
+    else {
+        ctx.reply("Could not find challenge").await?;
+        return Ok(());
+    };
+
+    let location_url = ctx.data().settings.read().await.location_url.clone();
+
+    let mut embed = CreateEmbed::new()
+        .color((0x00, 0x99, 0xff))
+        .title(&challenge.name)
+        .url(format!("{}/challenges#{}", location_url, challenge.name));
+
+    let frozen_at = standings_frozen_at(ctx.data(), &user).await;
+    let frozen_challenge_data =
+        freeze_challenges(ctx.data().db, challenge_data.clone(), frozen_at).await?;
+    let division_points = frozen_challenge_data
+        .challenges
+        .iter()
+        .find(|c| c.id == challenge.id)
+        .map_or(&[][..], |c| &c.division_points);
+
+    for division in ctx.data().divisions {
+        if let Some(points) = division_points
+            .iter()
+            .find(|points| points.division_id == division.id)
+        {
+            embed = embed.field(
+                &division.name,
+                format!("{} solves, {} points", points.solves, points.points),
+                true,
+            );
+        }
+    }
+
+    let team_solve = if let Some(solve) = team.solves.get(&challenge.id) {
+        let solver = team
+            .users
+            .get(&solve.user_id)
+            .map(|solver| {
+                if let Some(discord_id) = solver.discord_id {
+                    format!("<@{}>", discord_id)
+                } else {
+                    solver.name.clone()
+                }
+            })
+            .unwrap_or_else(|| "a former member".to_owned());
+        format!("Solved by {} <t:{}:R>", solver, solve.solved_at.timestamp())
+    } else {
+        "Not solved yet".to_owned()
+    };
+
+    ctx.send(poise::CreateReply::default().embed(embed.field(
+        ":red_square: Your Team",
+        team_solve,
+        false,
+    )))
+    .await?;
+
+    Ok(())
+}
+
+/// Open a support ticket with the author of a challenge
+#[poise::command(slash_command, ephemeral)]
+pub async fn ticket(
+    ctx: Context<'_>,
+    #[description = "Challenge you need help with"]
+    #[autocomplete = "autocomplete_challenge"]
+    challenge: String,
+    #[description = "What you need help with"]
+    #[max_length = 1000]
+    message: String,
+) -> std::result::Result<(), DiscordError> {
+    let Some(user) = linked_user(ctx).await? else {
+        return Ok(());
+    };
+
+    let ticket_enabled = ctx
+        .data()
+        .settings
+        .read()
+        .await
+        .discord
+        .as_ref()
+        .unwrap()
+        .support_channel_id
+        .is_some();
+    if !ticket_enabled {
+        ctx.reply("Tickets are not enabled").await?;
+        return Ok(());
+    }
+
+    let challenge_data = ctx.data().db.get_challenges();
+    let team = ctx.data().db.get_team_from_id(user.team_id);
+    let (challenge_data, team) = futures::future::try_join(challenge_data, team).await?;
+
+    let Some(challenge) = visible_challenges(ctx.data(), &user, &team, &challenge_data)
+        .await
+        .into_iter()
+        .find(|c| c.name.eq_ignore_ascii_case(&challenge))
+    else {
+        ctx.reply("Could not find challenge").await?;
+        return Ok(());
+    };
+
+    let author = challenge_data.authors.get(&challenge.author_id).unwrap();
+
+    ctx.data()
+        .bot(ctx.serenity_context().http.clone())
+        .create_support_thread(&user, &team, challenge, author, &message)
+        .await?;
+
+    ctx.reply(format!(
+        "Opened a ticket for {}. You have been added to its thread.",
+        challenge.name
+    ))
+    .await?;
+
+    Ok(())
+}
+
 #[poise::command(
     slash_command,
-    subcommands("firstbloods", "support", "author", "verified", "status"),
+    subcommands(
+        "firstbloods",
+        "releases",
+        "announcements",
+        "support",
+        "author",
+        "verified",
+        "status",
+        "broadcast",
+        "clear_banner",
+        "ban",
+        "unban",
+        "kick",
+        "rename",
+        "recheck",
+        "lookup"
+    ),
     subcommand_required,
     default_member_permissions = "ADMINISTRATOR"
 )]
@@ -112,6 +656,61 @@ pub async fn firstbloods(
     Ok(())
 }
 
+/// Set the challenge release channel
+#[poise::command(slash_command, ephemeral)]
+pub async fn releases(
+    ctx: Context<'_>,
+    #[description = "Channel to announce challenge releases in"]
+    channel: serenity::all::GuildChannel,
+) -> std::result::Result<(), DiscordError> {
+    if ctx.data().settings.read().await.immutable_config {
+        ctx.reply("Can not set releases channel because configuration is immutable")
+            .await?;
+        return Ok(());
+    }
+
+    {
+        let mut settings = ctx.data().settings.write().await;
+        settings.discord.as_mut().unwrap().release_channel_id = Some(channel.id.into());
+        ctx.data().db.save_settings(&settings).await?;
+    }
+
+    ctx.reply(format!(
+        "Successfully bound <#{}> as the release channel",
+        channel.id
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// Set the announcement channel
+#[poise::command(slash_command, ephemeral)]
+pub async fn announcements(
+    ctx: Context<'_>,
+    #[description = "Channel to broadcast announcements in"] channel: serenity::all::GuildChannel,
+) -> std::result::Result<(), DiscordError> {
+    if ctx.data().settings.read().await.immutable_config {
+        ctx.reply("Can not set announcement channel because configuration is immutable")
+            .await?;
+        return Ok(());
+    }
+
+    {
+        let mut settings = ctx.data().settings.write().await;
+        settings.discord.as_mut().unwrap().announcement_channel_id = Some(channel.id.into());
+        ctx.data().db.save_settings(&settings).await?;
+    }
+
+    ctx.reply(format!(
+        "Successfully bound <#{}> as the announcement channel",
+        channel.id
+    ))
+    .await?;
+
+    Ok(())
+}
+
 #[poise::command(
     slash_command,
     subcommands("support_link", "support_panel"),
@@ -258,46 +857,441 @@ pub fn format_channel(channel: Option<NonZeroU64>) -> String {
     }
 }
 
-pub fn format_bool(b: bool) -> &'static str {
-    if b {
-        "✅"
-    } else {
-        "❌"
+pub fn format_bool(b: bool) -> &'static str {
+    if b {
+        "✅"
+    } else {
+        "❌"
+    }
+}
+
+/// Show the current configuration
+#[poise::command(slash_command, ephemeral)]
+pub async fn status(ctx: Context<'_>) -> std::result::Result<(), DiscordError> {
+    let message = {
+        let settings = ctx.data().settings.read().await;
+
+        format!(
+            "
+Immutable Configuration: {}
+Location URL: {}
+
+Verified Role {}
+Author Role {}
+First Blood Channel {}
+Release Channel {}
+Announcement Channel {}
+Support Channel {}
+
+Default Ticket Template
+```
+{}
+```
+            ",
+            format_bool(settings.immutable_config),
+            settings.location_url,
+            format_role(settings.discord.as_ref().unwrap().verified_role_id),
+            format_role(settings.discord.as_ref().unwrap().author_role_id),
+            format_channel(settings.discord.as_ref().unwrap().first_blood_channel_id),
+            format_channel(settings.discord.as_ref().unwrap().release_channel_id),
+            format_channel(settings.discord.as_ref().unwrap().announcement_channel_id),
+            format_channel(settings.discord.as_ref().unwrap().support_channel_id),
+            settings.default_ticket_template
+        )
+    };
+
+    ctx.reply(message).await?;
+
+    Ok(())
+}
+
+/// Post an announcement on the site and in the announcement channel
+#[poise::command(slash_command, ephemeral)]
+pub async fn broadcast(
+    ctx: Context<'_>,
+    #[description = "Title of the announcement"]
+    #[max_length = 256]
+    title: String,
+    #[description = "Announcement to broadcast, in Markdown"]
+    #[max_length = 2000]
+    message: String,
+    #[description = "Show it as the site banner too, defaults to true"] banner: Option<bool>,
+    #[description = "Email it to every user too, defaults to false"] email: Option<bool>,
+) -> std::result::Result<(), DiscordError> {
+    let data = ctx.data();
+    let bot = data.bot(ctx.serenity_context().http.clone());
+
+    let announcement = announcements::create_announcement(
+        data.db,
+        data.events,
+        Some(&bot),
+        data.outbound_mailer,
+        &AnnouncementDefinition {
+            title,
+            content: message,
+            banner: banner.unwrap_or(true),
+        },
+        true,
+        email.unwrap_or(false),
+    )
+    .await?;
+
+    let location_url = { data.settings.read().await.location_url.clone() };
+    ctx.reply(format!(
+        "Posted [{}]({}/announcements)",
+        announcement.title, location_url
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// Remove the site banner, keeping its announcement
+#[poise::command(slash_command, ephemeral, rename = "clearbanner")]
+pub async fn clear_banner(ctx: Context<'_>) -> std::result::Result<(), DiscordError> {
+    let data = ctx.data();
+
+    let mut cleared = 0;
+    for announcement in data.db.get_announcements().await? {
+        if !announcement.banner {
+            continue;
+        }
+        announcements::update_announcement(
+            data.db,
+            data.events,
+            announcement.id,
+            &AnnouncementDefinition {
+                title: announcement.title,
+                content: announcement.content,
+                banner: false,
+            },
+        )
+        .await?;
+        cleared += 1;
+    }
+
+    if cleared == 0 {
+        ctx.reply("There is no site banner to clear").await?;
+    } else {
+        ctx.reply("Cleared the site banner").await?;
+    }
+
+    Ok(())
+}
+
+/// The CTF user signed in with `user`, or `None` once the admin has been told
+/// there is none
+async fn find_user(
+    ctx: Context<'_>,
+    user: &serenity::all::User,
+) -> std::result::Result<Option<User>, DiscordError> {
+    match ctx.data().db.get_user_from_discord_id(user.id.into()).await {
+        Ok(user) => Ok(Some(user)),
+        Err(_) => {
+            ctx.reply(format!("<@{}> has not signed in to the CTF", user.id))
+                .await?;
+            Ok(None)
+        }
+    }
+}
+
+async fn set_disabled(
+    ctx: Context<'_>,
+    user: &serenity::all::User,
+    disabled: bool,
+) -> std::result::Result<(), DiscordError> {
+    let Some(target_user) = find_user(ctx, user).await? else {
+        return Ok(());
+    };
+
+    ctx.data()
+        .db
+        .set_user_disabled(target_user.id, disabled)
+        .await?;
+
+    tracing::info!(
+        discord_admin_id = ctx.author().id.get(),
+        user_id = target_user.id,
+        disabled,
+        "Set user disabled"
+    );
+
+    ctx.reply(format!(
+        "{} <@{}>",
+        if disabled { "Banned" } else { "Unbanned" },
+        user.id
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// Ban a user from the CTF
+#[poise::command(slash_command, ephemeral)]
+pub async fn ban(
+    ctx: Context<'_>,
+    #[description = "User to ban"] user: serenity::all::User,
+) -> std::result::Result<(), DiscordError> {
+    set_disabled(ctx, &user, true).await
+}
+
+/// Lift the ban of a user
+#[poise::command(slash_command, ephemeral)]
+pub async fn unban(
+    ctx: Context<'_>,
+    #[description = "User to unban"] user: serenity::all::User,
+) -> std::result::Result<(), DiscordError> {
+    set_disabled(ctx, &user, false).await
+}
+
+/// Remove a user from their team, leaving them in a team of their own
+#[poise::command(slash_command, ephemeral)]
+pub async fn kick(
+    ctx: Context<'_>,
+    #[description = "User to kick"] user: serenity::all::User,
+) -> std::result::Result<(), DiscordError> {
+    let Some(target_user) = find_user(ctx, &user).await? else {
+        return Ok(());
+    };
+
+    if target_user.is_team_owner {
+        ctx.reply("Can not kick the owner of a team").await?;
+        return Ok(());
+    }
+
+    ctx.data()
+        .db
+        .kick_user(target_user.id, target_user.team_id)
+        .await?;
+    ctx.data()
+        .bot(ctx.serenity_context().http.clone())
+        .sync_user_division_roles_now(ctx.data().divisions, target_user.id)
+        .await?;
+
+    tracing::info!(
+        discord_admin_id = ctx.author().id.get(),
+        user_id = target_user.id,
+        team_id = target_user.team_id,
+        "Kicked user from team"
+    );
+
+    ctx.reply(format!("Kicked <@{}> from their team", user.id))
+        .await?;
+
+    Ok(())
+}
+
+/// Rename the team of a user
+#[poise::command(slash_command, ephemeral)]
+pub async fn rename(
+    ctx: Context<'_>,
+    #[description = "Member of the team to rename"] user: serenity::all::User,
+    #[description = "New name of the team"] name: String,
+) -> std::result::Result<(), DiscordError> {
+    let Some(target_user) = find_user(ctx, &user).await? else {
+        return Ok(());
+    };
+
+    if !(3..=30).contains(&name.graphemes(true).count()) {
+        ctx.reply("Team names must be between 3 and 30 characters long")
+            .await?;
+        return Ok(());
+    }
+
+    match ctx
+        .data()
+        .db
+        .set_team_name(target_user.team_id, &name)
+        .await
+    {
+        Ok(()) => {}
+        Err(error) if error.is_unique_violation() => {
+            ctx.reply("Team name is already taken").await?;
+            return Ok(());
+        }
+        Err(error) => return Err(error.into()),
+    }
+
+    tracing::info!(
+        discord_admin_id = ctx.author().id.get(),
+        team_id = target_user.team_id,
+        name,
+        "Renamed team"
+    );
+
+    ctx.reply(format!("Renamed the team of <@{}> to {}", user.id, name))
+        .await?;
+
+    Ok(())
+}
+
+async fn autocomplete_healthchecked_challenge(ctx: Context<'_>, partial: &str) -> Vec<String> {
+    let Ok(challenge_data) = ctx.data().db.get_challenges().await else {
+        return vec![];
+    };
+
+    let partial = partial.to_lowercase();
+    challenge_data
+        .challenges
+        .iter()
+        .filter(|challenge| {
+            challenge.healthscript.is_some() && challenge.name.to_lowercase().contains(&partial)
+        })
+        // Discord shows at most 25 choices
+        .take(25)
+        .map(|challenge| challenge.name.clone())
+        .collect()
+}
+
+/// Run the healthcheck of a challenge now
+#[poise::command(slash_command, ephemeral)]
+pub async fn recheck(
+    ctx: Context<'_>,
+    #[description = "Challenge to check"]
+    #[autocomplete = "autocomplete_healthchecked_challenge"]
+    challenge: String,
+) -> std::result::Result<(), DiscordError> {
+    let challenge_data = ctx.data().db.get_challenges().await?;
+    let Some(challenge) = challenge_data
+        .challenges
+        .iter()
+        .find(|c| c.healthscript.is_some() && c.name.eq_ignore_ascii_case(&challenge))
+    else {
+        ctx.reply("Could not find a challenge with a healthscript")
+            .await?;
+        return Ok(());
+    };
+
+    // healthscripts can take longer than the 3 seconds Discord waits for a reply
+    ctx.defer_ephemeral().await?;
+
+    let bot = ctx.data().bot(ctx.serenity_context().http.clone());
+    let healthcheck = check_challenge(
+        ctx.data().db,
+        ctx.data().events,
+        Some(&bot),
+        ctx.data().outbound_mailer,
+        challenge,
+        challenge_data.authors.get(&challenge.author_id),
+    )
+    .await;
+
+    let mut message = format!(
+        "**{}** is {} ({} ms)",
+        challenge.name,
+        match healthcheck.healthy {
+            Some(true) => "healthy",
+            Some(false) => "unhealthy",
+            None => "unknown, its healthscript could not be parsed",
+        },
+        healthcheck.latency
+    );
+    if let Some(error) = &healthcheck.error {
+        message.push_str(&format!("\n```\n{}\n```", error));
     }
+
+    ctx.reply(message).await?;
+
+    Ok(())
 }
 
-/// Show the current configuration
+/// Look up the solves and IP addresses of a user's team
 #[poise::command(slash_command, ephemeral)]
-pub async fn status(ctx: Context<'_>) -> std::result::Result<(), DiscordError> {
-    let message = {
-        let settings = ctx.data().settings.read().await;
+pub async fn lookup(
+    ctx: Context<'_>,
+    #[description = "Member of the team to look up"] user: serenity::all::User,
+) -> std::result::Result<(), DiscordError> {
+    let Some(target_user) = find_user(ctx, &user).await? else {
+        return Ok(());
+    };
 
-        format!(
-            "
-Immutable Configuration: {}
-Location URL: {}
+    let team = ctx.data().db.get_team_from_id(target_user.team_id);
+    let ips = ctx.data().db.get_team_ips(target_user.team_id);
+    let challenge_data = ctx.data().db.get_challenges();
+    let (team, ips, challenge_data) = futures::future::try_join3(team, ips, challenge_data).await?;
 
-Verified Role {}
-Author Role {}
-First Blood Channel {}
-Support Channel {}
+    let location_url = ctx.data().settings.read().await.location_url.clone();
 
-Default Ticket Template
-```
-{}
-```
-            ",
-            format_bool(settings.immutable_config),
-            settings.location_url,
-            format_role(settings.discord.as_ref().unwrap().verified_role_id),
-            format_role(settings.discord.as_ref().unwrap().author_role_id),
-            format_channel(settings.discord.as_ref().unwrap().first_blood_channel_id),
-            format_channel(settings.discord.as_ref().unwrap().support_channel_id),
-            settings.default_ticket_template
-        )
+    let member_name = |user_id: i64| {
+        team.users
+            .get(&user_id)
+            .map_or("a former member", |member| member.name.as_str())
     };
 
-    ctx.reply(message).await?;
+    let mut solves = team.solves.iter().collect::<Vec<_>>();
+    solves.sort_by_key(|(_, solve)| solve.solved_at);
+    let solves = solves
+        .iter()
+        .filter_map(|(challenge_id, solve)| {
+            challenge_data
+                .challenges
+                .iter()
+                .find(|challenge| challenge.id == **challenge_id)
+                .map(|challenge| {
+                    format!(
+                        "{} by {} <t:{}:R>",
+                        challenge.name,
+                        member_name(solve.user_id),
+                        solve.solved_at.timestamp()
+                    )
+                })
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    // the same address is tracked once per user agent
+    let mut addresses: Vec<(i64, IpAddr, u64, DateTime<Utc>)> = vec![];
+    for ip in &ips {
+        if let Some(address) = addresses
+            .iter_mut()
+            .find(|address| address.0 == ip.user_id && address.1 == ip.ip)
+        {
+            address.2 += ip.requests;
+        } else {
+            addresses.push((ip.user_id, ip.ip, ip.requests, ip.last_seen_at));
+        }
+    }
+    let addresses = addresses
+        .iter()
+        .map(|(user_id, ip, requests, last_seen_at)| {
+            format!(
+                "`{}` {} ({} requests, <t:{}:R>)",
+                ip,
+                member_name(*user_id),
+                requests,
+                last_seen_at.timestamp()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    ctx.send(
+        poise::CreateReply::default().embed(
+            CreateEmbed::new()
+                .color((0x00, 0x99, 0xff))
+                .title(&team.name)
+                .url(format!("{}/team/{}", location_url, team.id))
+                .field(
+                    ":triangular_flag_on_post: Solves",
+                    if solves.is_empty() {
+                        "No solves".to_owned()
+                    } else {
+                        truncate_field(solves)
+                    },
+                    false,
+                )
+                .field(
+                    ":globe_with_meridians: IP Addresses",
+                    if addresses.is_empty() {
+                        "No requests tracked".to_owned()
+                    } else {
+                        truncate_field(addresses)
+                    },
+                    false,
+                ),
+        ),
+    )
+    .await?;
 
     Ok(())
 }
@@ -555,6 +1549,8 @@ impl Bot {
         settings: &'static RwLock<Settings>,
         db: Connection,
         outbound_mailer: Option<&'static OutboundMailer>,
+        events: &'static EventBus,
+        divisions: &'static [Division],
     ) -> Self {
         let bot_token = {
             settings
@@ -569,7 +1565,15 @@ impl Bot {
 
         let framework = poise::Framework::builder()
             .options(poise::FrameworkOptions {
-                commands: vec![admin(), whois()],
+                commands: vec![
+                    admin(),
+                    whois(),
+                    scoreboard(),
+                    team(),
+                    challenges(),
+                    solves(),
+                    ticket(),
+                ],
                 event_handler: |ctx, event, framework, data| {
                     Box::pin(event_handler(ctx, event, framework, data))
                 },
@@ -582,6 +1586,8 @@ impl Bot {
                         settings,
                         db,
                         outbound_mailer,
+                        events,
+                        divisions,
                     })
                 })
             })
@@ -602,6 +1608,7 @@ impl Bot {
             http: h,
             db,
             settings,
+            events,
         }
     }
 
@@ -699,6 +1706,35 @@ impl Bot {
             .create_ticket(ticket_number, user.id, challenge.id, thread.id.into())
             .await?;
 
+        self.events.publish(RhombusEvent::TicketOpened {
+            ticket_number,
+            user: user.clone(),
+            team: team.clone(),
+            challenge: challenge.clone(),
+        });
+
+        // so the author knows what the team has already seen. embed field values
+        // are limited to 1024 characters
+        let unlocked_hints = team
+            .unlocked_hints
+            .get(&challenge.id)
+            .map(|hint_ids| {
+                let hints = challenge
+                    .hints
+                    .iter()
+                    .filter(|hint| hint_ids.contains(&hint.id))
+                    .map(|hint| format!("**Hint {}** {}", hint.id + 1, hint.content))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                if hints.chars().count() > 1024 {
+                    format!("{}…", hints.chars().take(1023).collect::<String>())
+                } else {
+                    hints
+                }
+            })
+            .filter(|hints| !hints.is_empty())
+            .map(|hints| (":bulb: Hints Unlocked", hints, false));
+
         thread
             .send_message(
                 &self.http,
@@ -745,7 +1781,8 @@ impl Bot {
                                 format!("<@{}>", author.discord_id),
                                 true,
                             )
-                            .field("", "", true),
+                            .field("", "", true)
+                            .fields(unlocked_hints),
                     )
                     .components(vec![CreateActionRow::Buttons(vec![CreateButton::new(
                         format!("close-ticket-{}", ticket_number),
@@ -856,6 +1893,139 @@ impl Bot {
         Ok(())
     }
 
+    /// Announce newly released challenges in the release channel, if one is
+    /// configured
+    pub async fn send_release(
+        &self,
+        challenges: &[&Challenge],
+        categories: &[Category],
+    ) -> Result<()> {
+        let (release_channel_id, location_url) = {
+            let settings = self.settings.read().await;
+            (
+                settings.discord.as_ref().unwrap().release_channel_id,
+                settings.location_url.clone(),
+            )
+        };
+
+        let Some(release_channel_id) = release_channel_id else {
+            return Ok(());
+        };
+
+        let names = challenges
+            .iter()
+            .map(|challenge| {
+                let category = categories
+                    .iter()
+                    .find(|category| category.id == challenge.category_id)
+                    .unwrap();
+                format!("**{} / {}**", category.name, challenge.name)
+            })
+            .collect::<Vec<String>>();
+
+        ChannelId::from(release_channel_id)
+            .send_message(
+                &self.http,
+                CreateMessage::new()
+                    .content(format!(
+                        "{} {} now available! :triangular_flag_on_post:",
+                        names.join(", "),
+                        if names.len() == 1 { "is" } else { "are" },
+                    ))
+                    .components(vec![CreateActionRow::Buttons(vec![
+                        CreateButton::new_link(format!("{}/challenges", location_url))
+                            .label("Go to Challenges"),
+                    ])]),
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Post an announcement in the announcement channel, if one is set
+    pub async fn send_announcement(&self, announcement: &Announcement) -> Result<()> {
+        let (announcement_channel_id, location_url) = {
+            let settings = self.settings.read().await;
+            (
+                settings.discord.as_ref().unwrap().announcement_channel_id,
+                settings.location_url.clone(),
+            )
+        };
+
+        let Some(announcement_channel_id) = announcement_channel_id else {
+            return Ok(());
+        };
+
+        ChannelId::from(announcement_channel_id)
+            .send_message(
+                &self.http,
+                CreateMessage::new()
+                    .embed(
+                        CreateEmbed::new()
+                            .color((0x00, 0x99, 0xff))
+                            .title(format!(":loudspeaker: {}", announcement.title))
+                            .description(&announcement.content)
+                            .timestamp(announcement.created_at),
+                    )
+                    .components(vec![CreateActionRow::Buttons(vec![
+                        CreateButton::new_link(format!("{}/announcements", location_url))
+                            .label("All Announcements"),
+                    ])]),
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Direct message the author of a challenge which just turned unhealthy
+    pub async fn send_health_alert(
+        &self,
+        challenge: &Challenge,
+        author: &Author,
+        healthcheck: &Healthcheck,
+    ) -> Result<()> {
+        let location_url = { self.settings.read().await.location_url.clone() };
+
+        // embed field values are limited to 1024 characters
+        let error = healthcheck
+            .error
+            .as_deref()
+            .map(|error| {
+                if error.chars().count() > 1000 {
+                    format!("{}…", error.chars().take(999).collect::<String>())
+                } else {
+                    error.to_owned()
+                }
+            })
+            .unwrap_or_else(|| "No error output".to_owned());
+
+        UserId::from(author.discord_id)
+            .direct_message(
+                &self.http,
+                CreateMessage::new()
+                    .embed(
+                        CreateEmbed::new()
+                            .color((0xff, 0x00, 0x00))
+                            .title(format!(":rotating_light: {} is unhealthy", challenge.name))
+                            .field(
+                                ":watch: Checked",
+                                format!("<t:{}:F>", healthcheck.checked_at.timestamp()),
+                                true,
+                            )
+                            .field(
+                                ":stopwatch: Latency",
+                                format!("{} ms", healthcheck.latency),
+                                true,
+                            )
+                            .field(":x: Error", format!("```\n{}\n```", error), false),
+                    )
+                    .components(vec![CreateActionRow::Buttons(vec![
+                        CreateButton::new_link(format!("{}/admin/health", location_url))
+                            .label("Health Dashboard"),
+                    ])]),
+            )
+            .await?;
+        Ok(())
+    }
+
     pub async fn verify_user(&self, discord_id: NonZeroU64) -> Result<()> {
         let (verified_role_id, guild_id) = {
             let settings = self.settings.read().await;
@@ -883,6 +2053,185 @@ impl Bot {
         Ok(())
     }
 
+    /// Grants the members of `team_id` the Discord role of every division the
+    /// team is in, and revokes the roles of the divisions it is not in. Runs in
+    /// the background so requests do not wait on Discord.
+    pub fn sync_team_division_roles(&'static self, divisions: &'static [Division], team_id: i64) {
+        tokio::task::spawn(async move {
+            if let Err(error) = self.sync_team_division_roles_now(divisions, team_id).await {
+                tracing::error!(team_id, ?error, "Failed to sync division roles");
+            }
+        });
+    }
+
+    /// Grants `user_id` the Discord role of every division their team is in,
+    /// and revokes the others, for when they move to another team. Runs in the
+    /// background so requests do not wait on Discord.
+    pub fn sync_user_division_roles(&'static self, divisions: &'static [Division], user_id: i64) {
+        tokio::task::spawn(async move {
+            if let Err(error) = self.sync_user_division_roles_now(divisions, user_id).await {
+                tracing::error!(user_id, ?error, "Failed to sync division roles");
+            }
+        });
+    }
+
+    async fn sync_user_division_roles_now(
+        &self,
+        divisions: &[Division],
+        user_id: i64,
+    ) -> Result<()> {
+        if divisions
+            .iter()
+            .all(|division| division.discord_role_id.is_none())
+        {
+            return Ok(());
+        }
+
+        let guild_id: GuildId = self
+            .settings
+            .read()
+            .await
+            .discord
+            .as_ref()
+            .unwrap()
+            .guild_id
+            .into();
+
+        let user = self.db.get_user_from_id(user_id).await?;
+        let Some(discord_id) = user.discord_id else {
+            return Ok(());
+        };
+        // members who have not joined the guild yet are picked up by the reconciler
+        let Ok(member) = self.http.get_member(guild_id, discord_id.into()).await else {
+            return Ok(());
+        };
+        let team_divisions = self.db.get_team_divisions(user.team_id).await?;
+        self.set_division_roles(guild_id, divisions, &member, &team_divisions)
+            .await
+    }
+
+    async fn sync_team_division_roles_now(
+        &self,
+        divisions: &[Division],
+        team_id: i64,
+    ) -> Result<()> {
+        if divisions
+            .iter()
+            .all(|division| division.discord_role_id.is_none())
+        {
+            return Ok(());
+        }
+
+        let guild_id: GuildId = self
+            .settings
+            .read()
+            .await
+            .discord
+            .as_ref()
+            .unwrap()
+            .guild_id
+            .into();
+
+        let team = self.db.get_team_from_id(team_id).await?;
+        let team_divisions = self.db.get_team_divisions(team_id).await?;
+
+        for discord_id in team.users.values().filter_map(|user| user.discord_id) {
+            // members who have not joined the guild yet are picked up by the reconciler
+            let Ok(member) = self.http.get_member(guild_id, discord_id.into()).await else {
+                continue;
+            };
+            self.set_division_roles(guild_id, divisions, &member, &team_divisions)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Brings the division roles of every guild member who signed in with
+    /// Discord in line with the divisions of their team. Listing guild members
+    /// requires the bot to have the privileged server members intent.
+    pub async fn reconcile_division_roles(&self, divisions: &[Division]) -> Result<()> {
+        if divisions
+            .iter()
+            .all(|division| division.discord_role_id.is_none())
+        {
+            return Ok(());
+        }
+
+        let guild_id: GuildId = self
+            .settings
+            .read()
+            .await
+            .discord
+            .as_ref()
+            .unwrap()
+            .guild_id
+            .into();
+
+        let mut user_divisions = BTreeMap::new();
+        for team_id in self.db.get_team_ids().await? {
+            let team = self.db.get_team_from_id(team_id).await?;
+            let team_divisions = self.db.get_team_divisions(team_id).await?;
+            for discord_id in team.users.values().filter_map(|user| user.discord_id) {
+                user_divisions.insert(UserId::from(discord_id), team_divisions.clone());
+            }
+        }
+
+        let mut after = None;
+        loop {
+            let members = self
+                .http
+                .get_guild_members(guild_id, Some(GUILD_MEMBERS_PAGE_SIZE), after)
+                .await?;
+
+            for member in &members {
+                if let Some(team_divisions) = user_divisions.get(&member.user.id) {
+                    self.set_division_roles(guild_id, divisions, member, team_divisions)
+                        .await?;
+                }
+            }
+
+            if (members.len() as u64) < GUILD_MEMBERS_PAGE_SIZE {
+                break;
+            }
+            after = members.last().map(|member| member.user.id.get());
+        }
+
+        Ok(())
+    }
+
+    async fn set_division_roles(
+        &self,
+        guild_id: GuildId,
+        divisions: &[Division],
+        member: &Member,
+        team_divisions: &[i64],
+    ) -> Result<()> {
+        for (role_id, add) in division_role_changes(divisions, &member.roles, team_divisions) {
+            if add {
+                self.http
+                    .add_member_role(
+                        guild_id,
+                        member.user.id,
+                        role_id,
+                        Some("Team joined division"),
+                    )
+                    .await?;
+            } else {
+                self.http
+                    .remove_member_role(
+                        guild_id,
+                        member.user.id,
+                        role_id,
+                        Some("Team left division"),
+                    )
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
     pub async fn send_external_ticket_message(
         &self,
         channel_id: NonZeroU64,
@@ -970,3 +2319,145 @@ fn format_list_en(items: &[&str]) -> String {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serenity::all::RoleId;
+
+    use chrono::{TimeDelta, Utc};
+
+    use super::{division_points, division_role_changes};
+    use crate::internal::{
+        database::{
+            libsql::LocalLibSQL,
+            provider::{
+                tests::challenge, ChallengeData, ChallengeDivisionPoints, Database, DynamicScoring,
+                ScoringCurve,
+            },
+        },
+        division::{Division, MaxDivisionPlayers, OpenDivisionEligibilityProvider},
+        routes::scoreboard::freeze_challenges,
+    };
+
+    fn division(id: i64, discord_role_id: Option<u64>) -> Division {
+        Division {
+            id,
+            name: id.to_string(),
+            description: String::new(),
+            max_players: MaxDivisionPlayers::Unlimited,
+            discord_role_id: discord_role_id.and_then(|id| id.try_into().ok()),
+            division_eligibility: Box::leak(Box::new(OpenDivisionEligibilityProvider)),
+        }
+    }
+
+    #[test]
+    fn division_roles() {
+        let divisions = [
+            division(1, Some(10)),
+            division(2, Some(20)),
+            division(3, Some(30)),
+            division(4, None),
+        ];
+        let roles = [RoleId::new(20), RoleId::new(30), RoleId::new(99)];
+
+        assert_eq!(
+            vec![(RoleId::new(10), true), (RoleId::new(30), false)],
+            division_role_changes(&divisions, &roles, &[1, 2, 4])
+        );
+        assert!(division_role_changes(&divisions, &roles, &[2, 3]).is_empty());
+        assert_eq!(
+            vec![(RoleId::new(20), false), (RoleId::new(30), false)],
+            division_role_changes(&divisions, &roles, &[])
+        );
+    }
+
+    #[test]
+    fn challenge_division_points() {
+        let mut scored = challenge(1);
+        scored.division_points = vec![
+            ChallengeDivisionPoints {
+                division_id: 1,
+                points: 500,
+                solves: 0,
+            },
+            ChallengeDivisionPoints {
+                division_id: 2,
+                points: 421,
+                solves: 3,
+            },
+        ];
+        let challenge_data = ChallengeData {
+            challenges: vec![scored, challenge(2)],
+            categories: vec![],
+            authors: Default::default(),
+            divisions: Default::default(),
+        };
+
+        assert_eq!(500, division_points(&challenge_data, 1, 1));
+        assert_eq!(421, division_points(&challenge_data, 1, 2));
+        assert_eq!(0, division_points(&challenge_data, 1, 3));
+        assert_eq!(0, division_points(&challenge_data, 2, 1));
+        assert_eq!(0, division_points(&challenge_data, 3, 1));
+    }
+
+    #[tokio::test]
+    async fn frozen_division_points() {
+        let database = Box::leak(Box::new(LocalLibSQL::new_memory().await.unwrap()));
+        database.migrate().await.unwrap();
+
+        let scoring = DynamicScoring {
+            initial: 500,
+            minimum: 100,
+            decay: 10,
+            curve: ScoringCurve::Linear,
+        };
+        database
+            .conn
+            .execute_batch(
+                "
+                INSERT INTO rhombus_category (id, name, color) VALUES (1, 'misc', '#ffffff');
+                INSERT INTO rhombus_author (id, name, avatar, discord_id) VALUES (1, 'author', '', 1);
+                INSERT INTO rhombus_division (id, name, description) VALUES (1, 'open', '');
+                INSERT INTO rhombus_challenge (id, name, description, flag, category_id, author_id, score_type, dynamic_initial, dynamic_minimum, dynamic_decay, dynamic_curve)
+                VALUES (1, 'dynamic', '', 'flag', 1, 1, 0, 500, 100, 10, 2);
+                ",
+            )
+            .await
+            .unwrap();
+
+        let frozen_at = Utc::now() - TimeDelta::hours(1);
+        let solved_at = [
+            frozen_at - TimeDelta::minutes(2),
+            frozen_at - TimeDelta::minutes(1),
+            frozen_at + TimeDelta::minutes(1),
+        ];
+        for (id, solved_at) in solved_at.iter().enumerate() {
+            database
+                .conn
+                .execute_batch(&format!(
+                    "
+                    INSERT INTO rhombus_team (id, name, invite_token) VALUES ({id}, '{id}', '{id}');
+                    INSERT INTO rhombus_user (id, name, avatar, team_id, owner_team_id) VALUES ({id}, '{id}', '', {id}, {id});
+                    INSERT INTO rhombus_team_division (team_id, division_id) VALUES ({id}, 1);
+                    INSERT INTO rhombus_solve (challenge_id, user_id, solved_at) VALUES (1, {id}, {});
+                    ",
+                    solved_at.timestamp()
+                ))
+                .await
+                .unwrap();
+        }
+
+        let challenge_data = database.get_challenges().await.unwrap();
+        assert_eq!(scoring.points(3), division_points(&challenge_data, 1, 1));
+
+        let frozen = freeze_challenges(database, challenge_data.clone(), Some(frozen_at))
+            .await
+            .unwrap();
+        assert_eq!(scoring.points(2), division_points(&frozen, 1, 1));
+
+        let live = freeze_challenges(database, challenge_data, None)
+            .await
+            .unwrap();
+        assert_eq!(scoring.points(3), division_points(&live, 1, 1));
+    }
+}
@@ -1,10 +1,13 @@
-use std::num::NonZeroU32;
+use std::{
+    num::{NonZeroU32, NonZeroU64},
+    time::Duration,
+};
 
 use async_trait::async_trait;
 use fancy_regex::Regex;
 use serde::Serialize;
 
-use crate::internal::database::provider::Connection;
+use crate::internal::{database::provider::Connection, discord::Bot};
 
 pub type DivisionEligibilityProvider = &'static (dyn DivisionEligible + Send + Sync);
 
@@ -62,6 +65,7 @@ pub struct Division {
     pub name: String,
     pub description: String,
     pub max_players: MaxDivisionPlayers,
+    pub discord_role_id: Option<NonZeroU64>,
 
     #[serde(skip)]
     pub division_eligibility: DivisionEligibilityProvider,
@@ -72,3 +76,24 @@ pub enum MaxDivisionPlayers {
     Unlimited,
     Limited(NonZeroU32),
 }
+
+/// Periodically fixes the Discord division roles of members who joined the
+/// guild after their team joined a division, or whose roles were changed by hand
+pub fn division_role_reconciler(bot: &'static Bot, divisions: &'static [Division]) {
+    if divisions
+        .iter()
+        .all(|division| division.discord_role_id.is_none())
+    {
+        return;
+    }
+
+    tokio::task::spawn(async move {
+        loop {
+            if let Err(error) = bot.reconcile_division_roles(divisions).await {
+                tracing::error!(?error, "Failed to reconcile division roles");
+            }
+
+            tokio::time::sleep(Duration::from_secs(600)).await;
+        }
+    });
+}
@@ -2,7 +2,7 @@ use std::fmt::Write;
 
 use crate::{
     internal::{
-        database::provider::{Connection, Ticket},
+        database::provider::{Announcement, Challenge, Connection, Healthcheck, Ticket},
         discord::DigestMessage,
         email::provider::OutboundEmailProvider,
         settings::Settings,
@@ -135,6 +135,92 @@ impl OutboundMailer {
         Ok(())
     }
 
+    pub async fn send_health_alert(
+        &self,
+        to: &str,
+        challenge: &Challenge,
+        healthcheck: &Healthcheck,
+    ) -> Result<()> {
+        let (title, location_url) = {
+            let settings = self.settings.read().await;
+            (settings.title.clone(), settings.location_url.clone())
+        };
+
+        let context = context! {
+            title,
+            challenge,
+            healthcheck,
+            dashboard_url => format!("{}/admin/health", location_url),
+        };
+
+        let plaintext = self
+            .jinja
+            .get_template("emails/health-alert.txt")
+            .unwrap()
+            .render(&context)
+            .unwrap();
+
+        let html = self
+            .jinja
+            .get_template("emails/health-alert.html")
+            .unwrap()
+            .render(&context)
+            .unwrap();
+
+        self.inner
+            .send_email(
+                to,
+                &format!("{} Unhealthy Challenge: {}", title, challenge.name),
+                &plaintext,
+                &html,
+                None,
+                &[],
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn send_announcement(&self, to: &str, announcement: &Announcement) -> Result<()> {
+        let (title, location_url) = {
+            let settings = self.settings.read().await;
+            (settings.title.clone(), settings.location_url.clone())
+        };
+
+        let context = context! {
+            title,
+            announcement,
+            announcements_url => format!("{}/announcements", location_url),
+        };
+
+        let plaintext = self
+            .jinja
+            .get_template("emails/announcement.txt")
+            .unwrap()
+            .render(&context)
+            .unwrap();
+
+        let html = self
+            .jinja
+            .get_template("emails/announcement.html")
+            .unwrap()
+            .render(&context)
+            .unwrap();
+
+        self.inner
+            .send_email(
+                to,
+                &format!("{}: {}", title, announcement.title),
+                &plaintext,
+                &html,
+                None,
+                &[],
+            )
+            .await?;
+
+        Ok(())
+    }
+
     pub async fn send_digest(&self, ticket: &Ticket, messages: &[DigestMessage<'_>]) -> Result<()> {
         let user_emails = self.db.get_emails_for_user_id(ticket.user_id).await?;
         let to = if let Some(email) = user_emails.iter().find(|e| e.verified) {
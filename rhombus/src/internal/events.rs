@@ -0,0 +1,151 @@
+use tokio::sync::broadcast;
+
+use crate::internal::{
+    auth::User,
+    database::provider::{Announcement, Challenge, Team},
+};
+
+/// How many events a slow subscriber can fall behind before it starts missing them
+const CAPACITY: usize = 1024;
+
+/// Something which happened in the CTF. Published on the [EventBus] after the
+/// change has been written to the database.
+#[derive(Debug, Clone)]
+pub enum RhombusEvent {
+    Solve {
+        user: User,
+        team: Team,
+        challenge: Challenge,
+    },
+    /// Published right after the [Solve](RhombusEvent::Solve) which got first
+    /// blood in each of `division_ids`
+    FirstBlood {
+        user: User,
+        team: Team,
+        challenge: Challenge,
+        division_ids: Vec<i64>,
+    },
+    /// A new user signed up, and with them their own team
+    TeamCreated {
+        user: User,
+        team: Team,
+    },
+    /// A user left their team to join `team`
+    TeamJoined {
+        user: User,
+        team: Team,
+    },
+    TicketOpened {
+        ticket_number: u64,
+        user: User,
+        team: Team,
+        challenge: Challenge,
+    },
+    HealthChanged {
+        challenge: Challenge,
+        healthy: Option<bool>,
+    },
+    AnnouncementCreated {
+        announcement: Announcement,
+    },
+    AnnouncementUpdated {
+        announcement: Announcement,
+    },
+    AnnouncementDeleted {
+        announcement_id: i64,
+    },
+}
+
+/// Broadcasts [RhombusEvent]s to every subscriber, so plugins can react to
+/// what happens in the CTF without changes to core.
+pub struct EventBus {
+    sender: broadcast::Sender<RhombusEvent>,
+}
+
+impl EventBus {
+    pub fn new() -> EventBus {
+        EventBus {
+            sender: broadcast::channel(CAPACITY).0,
+        }
+    }
+
+    pub fn publish(&self, event: RhombusEvent) {
+        // an error only means nobody is subscribed
+        _ = self.sender.send(event);
+    }
+
+    /// Receive every event published from now on. Receivers which fall more
+    /// than 1024 events behind get [RecvError::Lagged](broadcast::error::RecvError::Lagged)
+    /// and skip ahead.
+    pub fn subscribe(&self) -> broadcast::Receiver<RhombusEvent> {
+        self.sender.subscribe()
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::broadcast::error::TryRecvError;
+
+    use super::{EventBus, RhombusEvent};
+    use crate::internal::{
+        announcements::{create_announcement, delete_announcement, update_announcement},
+        database::{
+            libsql::LocalLibSQL,
+            provider::{AnnouncementDefinition, Database},
+        },
+    };
+
+    #[tokio::test]
+    async fn announcement_events() {
+        let database = Box::leak(Box::new(LocalLibSQL::new_memory().await.unwrap()));
+        database.migrate().await.unwrap();
+        let events = EventBus::new();
+
+        let definition = AnnouncementDefinition {
+            title: "Title".to_owned(),
+            content: "Content".to_owned(),
+            banner: false,
+        };
+
+        // nobody is subscribed yet, so this event is dropped
+        let unseen = create_announcement(database, &events, None, None, &definition, false, false)
+            .await
+            .unwrap();
+
+        let mut receiver = events.subscribe();
+        let announcement =
+            create_announcement(database, &events, None, None, &definition, false, false)
+                .await
+                .unwrap();
+        update_announcement(database, &events, announcement.id, &definition)
+            .await
+            .unwrap();
+        delete_announcement(database, &events, unseen.id)
+            .await
+            .unwrap();
+
+        let mut late_receiver = events.subscribe();
+
+        assert!(matches!(
+            receiver.recv().await.unwrap(),
+            RhombusEvent::AnnouncementCreated { announcement: created } if created.id == announcement.id
+        ));
+        assert!(matches!(
+            receiver.recv().await.unwrap(),
+            RhombusEvent::AnnouncementUpdated { announcement: updated } if updated.id == announcement.id
+        ));
+        assert!(matches!(
+            receiver.recv().await.unwrap(),
+            RhombusEvent::AnnouncementDeleted { announcement_id } if announcement_id == unseen.id
+        ));
+        assert!(matches!(receiver.try_recv(), Err(TryRecvError::Empty)));
+
+        assert!(matches!(late_receiver.try_recv(), Err(TryRecvError::Empty)));
+    }
+}
@@ -1,70 +1,184 @@
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use chrono::{TimeZone, Utc};
+use chrono::{TimeDelta, Utc};
+use tokio::task::JoinSet;
 
-use crate::internal::database::provider::Connection;
+use crate::internal::{
+    database::provider::{Author, Challenge, Connection, Healthcheck},
+    discord::Bot,
+    email::outbound_mailer::OutboundMailer,
+    events::{EventBus, RhombusEvent},
+};
 
-pub async fn healthcheck_catch_up(db: Connection) {
-    tracing::info!("Running healthcheck catch-up");
-    let challenges = db.get_challenges().await.unwrap();
+/// Seconds between healthchecks of a challenge without a `healthcheck_interval`
+pub const DEFAULT_HEALTHCHECK_INTERVAL: i64 = 300;
 
-    for challenge in &challenges.challenges {
-        tracing::info!(hs=?challenge.healthscript, healthy=?challenge.healthy, "Healthcheck");
-        if challenge.healthscript.is_none() {
-            continue;
-        }
-
-        if challenge.healthy.is_some() {
-            continue;
-        }
+/// Runs the healthscript of `challenge`, which must have one
+pub async fn run_healthcheck(challenge: &Challenge) -> Healthcheck {
+    let start = Instant::now();
 
-        let (expr, _) = healthscript::parse(challenge.healthscript.as_ref().unwrap());
-        let healthy = if let Some(expr) = expr {
-            Some(expr.execute().await.0)
-        } else {
-            tracing::error!(challenge_id = challenge.id, "Failed to parse healthscript");
-            None
-        };
+    let (expr, parse_errors) = healthscript::parse(challenge.healthscript.as_ref().unwrap());
+    let (healthy, error) = if let Some(expr) = expr {
+        let (healthy, errors) = expr.execute().await;
+        let error = errors
+            .iter()
+            .flatten()
+            .map(|error| error.to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+        (Some(healthy), Some(error).filter(|error| !error.is_empty()))
+    } else {
+        tracing::error!(challenge_id = challenge.id, "Failed to parse healthscript");
+        (None, Some(parse_errors.join("\n")))
+    };
 
-        _ = db
-            .set_challenge_health(challenge.id, healthy, chrono::Utc::now())
-            .await;
-
-        tracing::trace!(challenge_id = challenge.id, challenge_name = challenge.name, healthy = ?healthy, "Healthcheck");
+    Healthcheck {
+        challenge_id: challenge.id,
+        checked_at: Utc::now(),
+        healthy,
+        latency: start.elapsed().as_millis() as i64,
+        error,
     }
 }
 
-pub fn healthcheck_runner(db: Connection) {
+/// Checks every challenge whose healthcheck interval has passed, starting with
+/// the ones which have never been checked. Authors are alerted on Discord and
+/// by email when their challenge turns unhealthy.
+pub fn healthcheck_runner(
+    db: Connection,
+    events: &'static EventBus,
+    bot: Option<&'static Bot>,
+    outbound_mailer: Option<&'static OutboundMailer>,
+) {
     tokio::task::spawn(async move {
         loop {
-            tokio::time::sleep(Duration::from_secs(60)).await;
-
-            let challenges = db.get_challenges().await.unwrap();
-
-            if let Some(challenge) = challenges
-                .challenges
-                .iter()
-                .filter(|challenge| challenge.healthscript.is_some())
-                .min_by_key(|challenge| {
-                    challenge
-                        .last_healthcheck
-                        .unwrap_or(Utc.timestamp_opt(0, 0).unwrap())
-                })
-            {
-                let (expr, _) = healthscript::parse(challenge.healthscript.as_ref().unwrap());
-                let healthy = if let Some(expr) = expr {
-                    Some(expr.execute().await.0)
-                } else {
-                    tracing::error!(challenge_id = challenge.id, "Failed to parse healthscript");
-                    None
-                };
-
-                _ = db
-                    .set_challenge_health(challenge.id, healthy, chrono::Utc::now())
-                    .await;
-
-                tracing::trace!(challenge_id = challenge.id, challenge_name = challenge.name, healthy = ?healthy, "Healthcheck");
+            match db.get_challenges().await {
+                Ok(challenge_data) => {
+                    let now = Utc::now();
+                    let mut checks = JoinSet::new();
+                    for challenge in challenge_data
+                        .challenges
+                        .iter()
+                        .filter(|challenge| challenge.healthscript.is_some())
+                        .filter(|challenge| {
+                            let interval = TimeDelta::seconds(
+                                challenge
+                                    .healthcheck_interval
+                                    .unwrap_or(DEFAULT_HEALTHCHECK_INTERVAL),
+                            );
+                            challenge
+                                .last_healthcheck
+                                .map_or(true, |last_healthcheck| now - last_healthcheck >= interval)
+                        })
+                    {
+                        let challenge = challenge.clone();
+                        let author = challenge_data.authors.get(&challenge.author_id).cloned();
+                        checks.spawn(async move {
+                            check_challenge(
+                                db,
+                                events,
+                                bot,
+                                outbound_mailer,
+                                &challenge,
+                                author.as_ref(),
+                            )
+                            .await
+                        });
+                    }
+                    while checks.join_next().await.is_some() {}
+                }
+                Err(error) => tracing::error!(?error, "Failed to get challenges to healthcheck"),
             }
+
+            tokio::time::sleep(Duration::from_secs(10)).await;
         }
     });
 }
+
+/// Runs the healthscript of `challenge` and records the result, announcing
+/// and alerting its author if its health changed
+pub async fn check_challenge(
+    db: Connection,
+    events: &EventBus,
+    bot: Option<&Bot>,
+    outbound_mailer: Option<&OutboundMailer>,
+    challenge: &Challenge,
+    author: Option<&Author>,
+) -> Healthcheck {
+    let healthcheck = run_healthcheck(challenge).await;
+
+    _ = db
+        .set_challenge_health(challenge.id, healthcheck.healthy, healthcheck.checked_at)
+        .await;
+    if let Err(error) = db.add_healthcheck(&healthcheck).await {
+        tracing::error!(
+            challenge_id = challenge.id,
+            ?error,
+            "Failed to record healthcheck"
+        );
+    }
+
+    tracing::trace!(challenge_id = challenge.id, challenge_name = challenge.name, healthy = ?healthcheck.healthy, latency = healthcheck.latency, "Healthcheck");
+
+    if challenge.healthy == healthcheck.healthy {
+        return healthcheck;
+    }
+
+    events.publish(RhombusEvent::HealthChanged {
+        challenge: challenge.clone(),
+        healthy: healthcheck.healthy,
+    });
+
+    if healthcheck.healthy == Some(false) {
+        if let Some(author) = author {
+            alert_author(db, bot, outbound_mailer, challenge, author, &healthcheck).await;
+        }
+    }
+
+    healthcheck
+}
+
+async fn alert_author(
+    db: Connection,
+    bot: Option<&Bot>,
+    outbound_mailer: Option<&OutboundMailer>,
+    challenge: &Challenge,
+    author: &Author,
+    healthcheck: &Healthcheck,
+) {
+    if let Some(bot) = bot {
+        if let Err(error) = bot.send_health_alert(challenge, author, healthcheck).await {
+            tracing::error!(
+                challenge_id = challenge.id,
+                ?error,
+                "Failed to alert author on Discord"
+            );
+        }
+    }
+
+    // authors are only known by their Discord id, so email whoever signed in with it
+    let Some(outbound_mailer) = outbound_mailer else {
+        return;
+    };
+    let Ok(user) = db.get_user_from_discord_id(author.discord_id).await else {
+        return;
+    };
+    let Some(email) = db
+        .get_emails_for_user_id(user.id)
+        .await
+        .ok()
+        .and_then(|emails| emails.into_iter().find(|email| email.verified))
+    else {
+        return;
+    };
+    if let Err(error) = outbound_mailer
+        .send_health_alert(&email.address, challenge, healthcheck)
+        .await
+    {
+        tracing::error!(
+            challenge_id = challenge.id,
+            ?error,
+            "Failed to alert author by email"
+        );
+    }
+}
@@ -0,0 +1,275 @@
+use std::{future::Future, time::Duration};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use dashmap::{mapref::entry::Entry, DashMap};
+use serde::Serialize;
+
+use crate::{
+    instance_provider::InstanceProviderRef, internal::database::provider::Challenge, Result,
+};
+
+/// A team's instance of a challenge
+#[derive(Debug, Serialize, Clone)]
+pub struct RunningInstance {
+    /// What players use to connect to the instance
+    pub connection: String,
+    pub started_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// How many timeouts an instance can be kept alive for by extending it,
+/// counted from when it started
+const MAX_LIFETIME_TIMEOUTS: i32 = 4;
+
+/// Keeps track of the instances started through an [InstanceProvider](crate::InstanceProvider),
+/// at most one per team and challenge, and stops them once they expire
+pub struct Instances {
+    provider: InstanceProviderRef,
+
+    /// `None` while the instance is still starting
+    running: DashMap<(i64, i64), Option<RunningInstance>>,
+}
+
+impl Instances {
+    pub fn new(provider: InstanceProviderRef) -> Instances {
+        Instances {
+            provider,
+            running: DashMap::new(),
+        }
+    }
+
+    pub fn get(&self, challenge_id: i64, team_id: i64) -> Option<RunningInstance> {
+        self.running
+            .get(&(challenge_id, team_id))
+            .and_then(|instance| instance.clone())
+    }
+
+    /// Start an instance of `challenge` for `team_id`, or return the one which
+    /// is already running. Returns `None` if the instance is already starting.
+    /// `flag` is only called when a new instance is actually started.
+    pub async fn start<F>(
+        &self,
+        challenge: &Challenge,
+        team_id: i64,
+        flag: impl FnOnce() -> F,
+    ) -> Result<Option<RunningInstance>>
+    where
+        F: Future<Output = Result<String>>,
+    {
+        let Some(timeout) = challenge.instance.as_ref().map(|i| i.timeout) else {
+            return Ok(None);
+        };
+
+        match self.running.entry((challenge.id, team_id)) {
+            Entry::Occupied(instance) => return Ok(instance.get().clone()),
+            Entry::Vacant(entry) => _ = entry.insert(None),
+        }
+        let starting = Starting {
+            running: &self.running,
+            key: (challenge.id, team_id),
+        };
+
+        let flag = flag().await?;
+        let connection = self.provider.start(challenge, team_id, &flag).await?;
+
+        let started_at = Utc::now();
+        let instance = RunningInstance {
+            connection,
+            started_at,
+            expires_at: started_at + TimeDelta::seconds(timeout),
+        };
+        self.running
+            .insert((challenge.id, team_id), Some(instance.clone()));
+        drop(starting);
+        Ok(Some(instance))
+    }
+
+    /// Push the expiry of a running instance of `challenge` back to its full
+    /// timeout from now, but no further than [MAX_LIFETIME_TIMEOUTS] timeouts
+    /// after it started
+    pub fn extend(&self, challenge: &Challenge, team_id: i64) -> Option<RunningInstance> {
+        let timeout = TimeDelta::seconds(challenge.instance.as_ref()?.timeout);
+        let mut instance = self.running.get_mut(&(challenge.id, team_id))?;
+        let instance = instance.as_mut()?;
+        instance.expires_at = (Utc::now() + timeout)
+            .min(instance.started_at + timeout * MAX_LIFETIME_TIMEOUTS)
+            .max(instance.expires_at);
+        Some(instance.clone())
+    }
+
+    pub async fn stop(&self, challenge_id: i64, team_id: i64) -> Result<()> {
+        if self
+            .running
+            .remove_if(&(challenge_id, team_id), |_, instance| instance.is_some())
+            .is_some()
+        {
+            self.provider.stop(challenge_id, team_id).await?;
+        }
+        Ok(())
+    }
+
+    async fn stop_expired(&self) {
+        let now = Utc::now();
+        let expired = self
+            .running
+            .iter()
+            .filter(|instance| {
+                instance
+                    .value()
+                    .as_ref()
+                    .is_some_and(|instance| instance.expires_at <= now)
+            })
+            .map(|instance| *instance.key())
+            .collect::<Vec<_>>();
+
+        for (challenge_id, team_id) in expired {
+            tracing::info!(challenge_id, team_id, "Stopping expired instance");
+            if let Err(error) = self.stop(challenge_id, team_id).await {
+                tracing::error!(?error, challenge_id, team_id, "Failed to stop instance");
+            }
+        }
+    }
+}
+
+/// Removes the placeholder of an instance which is starting if it is dropped
+/// before the instance started, whether starting failed or was cancelled
+struct Starting<'a> {
+    running: &'a DashMap<(i64, i64), Option<RunningInstance>>,
+    key: (i64, i64),
+}
+
+impl Drop for Starting<'_> {
+    fn drop(&mut self) {
+        self.running
+            .remove_if(&self.key, |_, instance| instance.is_none());
+    }
+}
+
+/// Stops instances once their timeout passes
+pub fn instance_reaper(instances: &'static Instances) {
+    tokio::task::spawn(async move {
+        loop {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            instances.stop_expired().await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use chrono::TimeDelta;
+
+    use super::Instances;
+    use crate::{
+        errors::RhombusError,
+        internal::{
+            database::provider::{
+                tests::challenge, Challenge, ChallengeInstance, DynamicScoring, ScoringType,
+            },
+            settings::ProcessInstanceProviderSettings,
+        },
+        process_instance_provider::ProcessInstanceProvider,
+    };
+
+    #[tokio::test]
+    async fn start_extend_stop() {
+        let provider = Box::leak(Box::new(ProcessInstanceProvider::new(
+            &ProcessInstanceProviderSettings {
+                host: "localhost".to_owned(),
+                port_start: 41337,
+                port_end: 41347,
+            },
+        )));
+        let instances = Instances::new(provider);
+
+        let challenge = Challenge {
+            id: 1,
+            name: "instanced".to_owned(),
+            description: "".to_owned(),
+            description_html: "".to_owned(),
+            category_id: 1,
+            author_id: 1,
+            healthscript: None,
+            healthy: None,
+            last_healthcheck: None,
+            healthcheck_interval: None,
+            division_points: vec![],
+            scoring_type: ScoringType::Static,
+            static_points: Some(100),
+            dynamic_scoring: DynamicScoring::default(),
+            flag: "flag{test}".to_owned(),
+            flag_mode: Default::default(),
+            ticket_template: None,
+            attachments: vec![],
+            hints: vec![],
+            prerequisites: vec![],
+            instance: Some(ChallengeInstance {
+                spec: "sleep 60".to_owned(),
+                timeout: 60,
+            }),
+            release_at: None,
+            hidden: false,
+        };
+
+        let started = instances
+            .start(&challenge, 7, || async { Ok(challenge.flag.clone()) })
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(started.connection.starts_with("localhost:"));
+
+        // no flag is needed for the instance which is already running
+        let again = instances
+            .start(&challenge, 7, || async {
+                Err(RhombusError::Instance("flag requested".to_owned()))
+            })
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(again.connection, started.connection);
+
+        let extended = instances.extend(&challenge, 7).unwrap();
+        assert!(extended.expires_at >= started.expires_at);
+        assert!(extended.expires_at <= started.started_at + TimeDelta::seconds(60 * 4));
+
+        instances.stop(challenge.id, 7).await.unwrap();
+        assert!(instances.get(challenge.id, 7).is_none());
+        assert!(instances.extend(&challenge, 7).is_none());
+    }
+
+    #[tokio::test]
+    async fn cancelled_start() {
+        let provider = Box::leak(Box::new(ProcessInstanceProvider::new(
+            &ProcessInstanceProviderSettings {
+                host: "localhost".to_owned(),
+                port_start: 41348,
+                port_end: 41358,
+            },
+        )));
+        let instances = Instances::new(provider);
+
+        let mut challenge = challenge(1);
+        challenge.instance = Some(ChallengeInstance {
+            spec: "sleep 60".to_owned(),
+            timeout: 60,
+        });
+
+        let cancelled = tokio::time::timeout(
+            Duration::from_millis(10),
+            instances.start(&challenge, 7, std::future::pending),
+        )
+        .await;
+        assert!(cancelled.is_err());
+
+        // the cancelled start does not block starting the instance again
+        let started = instances
+            .start(&challenge, 7, || async { Ok(challenge.flag.clone()) })
+            .await
+            .unwrap();
+        assert!(started.is_some());
+
+        instances.stop(challenge.id, 7).await.unwrap();
+    }
+}
@@ -0,0 +1,123 @@
+use fancy_regex::{Captures, Regex};
+use syntect::{
+    easy::HighlightLines,
+    highlighting::{Theme, ThemeSet},
+    html::{styled_line_to_highlighted_html, IncludeBackground},
+    parsing::{SyntaxReference, SyntaxSet},
+    util::LinesWithEndings,
+};
+
+use crate::internal::database::provider::ChallengeAttachment;
+
+lazy_static::lazy_static! {
+    static ref CODE_BLOCK: Regex =
+        Regex::new(r#"(?s)<pre><code class="language-([^"]+)">(.*?)</code></pre>"#).unwrap();
+    static ref SYNTAX_SET: SyntaxSet = SyntaxSet::load_defaults_newlines();
+    static ref THEME: Theme = ThemeSet::load_defaults()
+        .themes
+        .remove("base16-ocean.dark")
+        .unwrap();
+}
+
+/// Render Markdown to HTML which is safe to embed as is. Raw HTML in the source
+/// is escaped and links with dangerous protocols are dropped. Fenced code
+/// blocks with a known language are syntax highlighted, and attachments can be
+/// linked to by name, like `[main.py]` or `![statue.png]`.
+pub fn render_markdown(source: &str, attachments: &[ChallengeAttachment]) -> String {
+    let mut source = source.to_owned();
+    if !attachments.is_empty() {
+        source.push_str("\n\n");
+        for attachment in attachments {
+            source.push_str(&format!(
+                "[{}]: <{}>\n",
+                escape_label(&attachment.name),
+                attachment.url.replace('<', "%3C").replace('>', "%3E"),
+            ));
+        }
+    }
+
+    let html = markdown::to_html_with_options(&source, &markdown::Options::gfm()).unwrap();
+
+    CODE_BLOCK
+        .replace_all(&html, |captures: &Captures| {
+            let Some(html) = SYNTAX_SET
+                .find_syntax_by_token(&captures[1])
+                .and_then(|syntax| highlight(&unescape(&captures[2]), syntax))
+            else {
+                return captures[0].to_owned();
+            };
+            format!(
+                r#"<pre><code class="language-{}">{}</code></pre>"#,
+                &captures[1], html,
+            )
+        })
+        .into_owned()
+}
+
+fn escape_label(label: &str) -> String {
+    label
+        .replace('\\', "\\\\")
+        .replace('[', "\\[")
+        .replace(']', "\\]")
+        .replace('\n', " ")
+}
+
+fn unescape(html: &str) -> String {
+    html.replace("&quot;", "\"")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+/// Highlights `code` with inline styles, so no stylesheet is needed for it
+fn highlight(code: &str, syntax: &SyntaxReference) -> Option<String> {
+    let mut highlighter = HighlightLines::new(syntax, &THEME);
+    let mut html = String::with_capacity(code.len() * 2);
+    for line in LinesWithEndings::from(code) {
+        let regions = highlighter.highlight_line(line, &SYNTAX_SET).ok()?;
+        html.push_str(&styled_line_to_highlighted_html(&regions, IncludeBackground::No).ok()?);
+    }
+    Some(html)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::render_markdown;
+    use crate::internal::database::provider::ChallengeAttachment;
+
+    #[test]
+    fn sanitized() {
+        let html = render_markdown("<script>alert(1)</script>\n\n[x](javascript:alert(1))", &[]);
+        assert!(!html.contains("<script>"));
+        assert!(!html.contains("javascript:"));
+    }
+
+    #[test]
+    fn attachment_links() {
+        let html = render_markdown(
+            "Download [main.py]",
+            &[ChallengeAttachment {
+                name: "main.py".to_owned(),
+                url: "https://example.com/main.py".to_owned(),
+            }],
+        );
+        assert_eq!(
+            html,
+            "<p>Download <a href=\"https://example.com/main.py\">main.py</a></p>\n"
+        );
+    }
+
+    #[test]
+    fn highlighted_code() {
+        let html = render_markdown("```python\nx = \"<a>\" # 1\n```", &[]);
+        assert!(html.starts_with(r#"<pre><code class="language-python"><span style="color:"#));
+        assert!(html.contains("&lt;a&gt;"));
+        assert!(!html.contains("<a>"));
+
+        let html = render_markdown("```unknown\nx = 1\n```", &[]);
+        assert_eq!(
+            html,
+            "<pre><code class=\"language-unknown\">x = 1\n</code></pre>"
+        );
+    }
+}
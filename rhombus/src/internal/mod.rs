@@ -1,17 +1,23 @@
+pub mod announcements;
 pub mod auth;
 pub mod command_palette;
 pub mod database;
 pub mod discord;
 pub mod division;
 pub mod email;
+pub mod events;
 pub mod health;
+pub mod instance;
 pub mod ip;
 pub mod local_upload_provider;
 pub mod locales;
+pub mod markdown;
 pub mod open_graph;
+pub mod release;
 pub mod router;
 pub mod routes;
 pub mod settings;
 pub mod static_serve;
 pub mod templates;
 pub mod upload_provider;
+pub mod webhook;
@@ -0,0 +1,61 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use tokio::sync::RwLock;
+
+use crate::internal::{database::provider::Connection, discord::Bot, settings::Settings};
+
+/// Releases challenges as their `release_at` passes, and announces each wave
+/// on Discord. Wakes up at the next scheduled release, or at least once a
+/// minute so edited schedules are picked up.
+pub fn release_runner(
+    db: Connection,
+    settings: &'static RwLock<Settings>,
+    bot: Option<&'static Bot>,
+) {
+    tokio::task::spawn(async move {
+        loop {
+            match db.release_challenges(Utc::now()).await {
+                Ok(released) if !released.is_empty() => {
+                    tracing::info!(?released, "Released challenges");
+
+                    let challenge_data = db.get_challenges().await.unwrap();
+                    let challenges = challenge_data
+                        .challenges
+                        .iter()
+                        .filter(|challenge| released.contains(&challenge.id) && !challenge.hidden)
+                        .collect::<Vec<_>>();
+
+                    if let Some(bot) = bot {
+                        if !challenges.is_empty() && settings.read().await.has_started() {
+                            if let Err(error) = bot
+                                .send_release(&challenges, &challenge_data.categories)
+                                .await
+                            {
+                                tracing::error!(?error, "Failed to announce challenge release");
+                            }
+                        }
+                    }
+                }
+                Ok(_) => {}
+                Err(error) => tracing::error!(?error, "Failed to release challenges"),
+            }
+
+            let now = Utc::now();
+            let next_release = db.get_challenges().await.ok().and_then(|challenge_data| {
+                challenge_data
+                    .challenges
+                    .iter()
+                    .filter_map(|challenge| challenge.release_at)
+                    .filter(|release_at| *release_at > now)
+                    .min()
+            });
+            let sleep = next_release
+                .and_then(|release_at| (release_at - now).to_std().ok())
+                .unwrap_or(Duration::MAX)
+                .min(Duration::from_secs(60));
+
+            tokio::time::sleep(sleep).await;
+        }
+    });
+}
@@ -2,8 +2,8 @@ use tokio::sync::RwLock;
 
 use crate::internal::{
     database::provider::Connection, discord::Bot, division::Division,
-    email::outbound_mailer::OutboundMailer, ip::IpExtractorFn, locales::Localizations,
-    settings::Settings,
+    email::outbound_mailer::OutboundMailer, events::EventBus, instance::Instances,
+    ip::IpExtractorFn, locales::Localizations, settings::Settings,
 };
 
 pub type RouterState = &'static RouterStateInner;
@@ -17,4 +17,6 @@ pub struct RouterStateInner {
     pub ip_extractor: IpExtractorFn,
     pub outbound_mailer: Option<&'static OutboundMailer>,
     pub divisions: &'static Vec<Division>,
+    pub instances: Option<&'static Instances>,
+    pub events: &'static EventBus,
 }
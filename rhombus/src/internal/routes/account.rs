@@ -1,7 +1,9 @@
-use std::{collections::BTreeMap, net::IpAddr, num::NonZeroU64, time::Duration};
+use std::{collections::BTreeMap, fmt::Write, net::IpAddr, num::NonZeroU64, time::Duration};
+
+use async_hash::{Digest, Sha256};
 
 use axum::{
-    extract::{Query, State},
+    extract::{Path, Query, State},
     http::Uri,
     response::{Html, IntoResponse, Redirect, Response},
     Extension, Form,
@@ -16,13 +18,39 @@ use reqwest::{Client, StatusCode};
 use serde::{Deserialize, Serialize};
 
 use crate::internal::{
-    auth::User, database::cache::TimedCache, discord, locales::Languages, router::RouterState,
+    auth::User,
+    database::cache::TimedCache,
+    discord,
+    locales::Languages,
+    router::RouterState,
+    routes::scoreboard::{freeze_challenges, standings_frozen_at},
 };
 
 pub fn generate_email_callback_code() -> String {
     Alphanumeric.sample_string(&mut thread_rng(), 16)
 }
 
+/// Prefix of every personal API token, used to tell them apart from session JWTs
+pub const API_TOKEN_PREFIX: &str = "rhombus_";
+
+pub fn generate_api_token() -> String {
+    format!(
+        "{}{}",
+        API_TOKEN_PREFIX,
+        Alphanumeric.sample_string(&mut thread_rng(), 32)
+    )
+}
+
+/// API tokens are only ever stored as their SHA-256 hash
+pub fn hash_api_token(token: &str) -> String {
+    Sha256::digest(token.as_bytes())
+        .iter()
+        .fold(String::new(), |mut output, b| {
+            let _ = write!(output, "{:02x}", b);
+            output
+        })
+}
+
 lazy_static::lazy_static! {
     pub static ref IS_IN_SERVER_CACHE: DashMap<NonZeroU64, TimedCache<bool>> = DashMap::new();
 }
@@ -116,16 +144,27 @@ pub async fn route_account(
     let team = state.db.get_team_from_id(user.team_id);
     let emails = state.db.get_emails_for_user_id(user.id);
     let user_divisions = state.db.get_user_divisions(user.id);
-    let (challenge_data, team, emails, user_divisions) =
-        tokio::join!(challenge_data, team, emails, user_divisions);
-    let (challenge_data, team, emails, user_divisions) = (
-        challenge_data.unwrap(),
+    let api_tokens = state.db.get_api_tokens(user.id);
+    let (challenge_data, team, emails, user_divisions, api_tokens) =
+        tokio::join!(challenge_data, team, emails, user_divisions, api_tokens);
+    let frozen_at = standings_frozen_at(&state, Some(&user)).await;
+    let challenge_data = match freeze_challenges(state.db, challenge_data.unwrap(), frozen_at).await
+    {
+        Ok(challenge_data) => challenge_data,
+        Err(error) => {
+            tracing::error!(?error, "Failed to freeze challenges");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    let (team, emails, user_divisions, api_tokens) = (
         team.unwrap(),
         emails.unwrap(),
         user_divisions.unwrap(),
+        api_tokens.unwrap(),
     );
 
     let mut divisions = vec![];
+    let mut divisions_changed = false;
     for division in state.divisions {
         let eligible = division
             .division_eligibility
@@ -140,6 +179,7 @@ pub async fn route_account(
                 .set_user_division(user.id, team.id, division.id, eligible.is_ok())
                 .await
                 .unwrap();
+            divisions_changed = true;
         }
 
         divisions.push(UserDivision {
@@ -150,6 +190,11 @@ pub async fn route_account(
             requirement: eligible.err(),
         })
     }
+    if divisions_changed {
+        if let Some(bot) = state.bot {
+            bot.sync_team_division_roles(state.divisions, team.id);
+        }
+    }
 
     let mut challenges = BTreeMap::new();
     for challenge in &challenge_data.challenges {
@@ -179,9 +224,11 @@ pub async fn route_account(
                 categories,
                 emails,
                 divisions,
+                api_tokens,
             })
             .unwrap(),
     )
+    .into_response()
 }
 
 #[derive(Deserialize)]
@@ -189,83 +236,66 @@ pub struct EmailSubmit {
     email: String,
 }
 
-pub async fn route_account_add_email(
-    state: State<RouterState>,
-    Extension(user): Extension<User>,
-    Extension(ip): Extension<Option<IpAddr>>,
-    Extension(lang): Extension<Languages>,
-    Form(form): Form<EmailSubmit>,
-) -> impl IntoResponse {
-    if form.email.is_empty() || form.email.len() > 255 {
-        return Response::builder()
-            .body(format!(
-                r#"<div id="htmx-toaster" data-toast="error" hx-swap-oob="true">{}</div>"#,
-                state
-                    .localizer
-                    .localize(&lang, "account-error-email-length", None)
-                    .unwrap(),
-            ))
-            .unwrap();
+/// Adds an unverified email to the account and sends the verification
+/// email. Returns the locale key of the error on failure.
+pub async fn add_email(
+    state: &RouterState,
+    user: &User,
+    ip: Option<IpAddr>,
+    email: &str,
+) -> Result<(), &'static str> {
+    if email.is_empty() || email.len() > 255 {
+        return Err("account-error-email-length");
     }
 
     let emails = state.db.get_emails_for_user_id(user.id).await.unwrap();
-    if emails.iter().any(|email| email.address == form.email) {
-        return Response::builder()
-            .body(format!(
-                r#"<div id="htmx-toaster" data-toast="error" hx-swap-oob="true">{}</div>"#,
-                state
-                    .localizer
-                    .localize(&lang, "account-error-email-already-added", None)
-                    .unwrap(),
-            ))
-            .unwrap();
+    if emails.iter().any(|e| e.address == email) {
+        return Err("account-error-email-already-added");
     }
 
     if let Some(mailer) = state.outbound_mailer {
         let Ok(code) = state
             .db
-            .create_email_verification_callback_code(user.id, &form.email)
+            .create_email_verification_callback_code(user.id, email)
             .await
         else {
-            return Response::builder()
-                .body(format!(
-                    r#"<div id="htmx-toaster" data-toast="error" hx-swap-oob="true">{}</div>"#,
-                    state
-                        .localizer
-                        .localize(&lang, "account-error-verification-email", None)
-                        .unwrap(),
-                ))
-                .unwrap();
+            return Err("account-error-verification-email");
         };
 
         if mailer
             .send_email_confirmation(
                 &user.name,
                 ip.map(|ip| ip.to_string()).as_deref(),
-                &form.email,
+                email,
                 &code,
             )
             .await
             .is_err()
         {
-            state.db.delete_email(user.id, &form.email).await.unwrap();
-
-            return Response::builder()
-                .body(format!(
-                    r#"<div id="htmx-toaster" data-toast="error" hx-swap-oob="true">{}</div>"#,
-                    state
-                        .localizer
-                        .localize(&lang, "account-error-verification-email", None)
-                        .unwrap(),
-                ))
-                .unwrap();
+            state.db.delete_email(user.id, email).await.unwrap();
+            return Err("account-error-verification-email");
         }
 
-        tracing::trace!(
-            user_id = user.id,
-            email = form.email,
-            "Sent verification email"
-        );
+        tracing::trace!(user_id = user.id, email, "Sent verification email");
+    }
+
+    Ok(())
+}
+
+pub async fn route_account_add_email(
+    state: State<RouterState>,
+    Extension(user): Extension<User>,
+    Extension(ip): Extension<Option<IpAddr>>,
+    Extension(lang): Extension<Languages>,
+    Form(form): Form<EmailSubmit>,
+) -> impl IntoResponse {
+    if let Err(message_id) = add_email(&state, &user, ip, &form.email).await {
+        return Response::builder()
+            .body(format!(
+                r#"<div id="htmx-toaster" data-toast="error" hx-swap-oob="true">{}</div>"#,
+                state.localizer.localize(&lang, message_id, None).unwrap(),
+            ))
+            .unwrap();
     }
 
     Response::builder()
@@ -324,6 +354,91 @@ pub async fn route_account_delete_email(
         .unwrap()
 }
 
+const MAX_API_TOKENS: usize = 10;
+
+#[derive(Deserialize)]
+pub struct ApiTokenCreate {
+    name: String,
+}
+
+pub async fn route_account_create_api_token(
+    state: State<RouterState>,
+    Extension(user): Extension<User>,
+    Extension(lang): Extension<Languages>,
+    Form(form): Form<ApiTokenCreate>,
+) -> impl IntoResponse {
+    let name = form.name.trim();
+    if name.is_empty() || name.chars().count() > 64 {
+        return Response::builder()
+            .header("HX-Reswap", "none")
+            .body(format!(
+                r#"<div id="htmx-toaster" data-toast="error" hx-swap-oob="true">{}</div>"#,
+                state
+                    .localizer
+                    .localize(&lang, "account-error-api-token-name-length", None)
+                    .unwrap(),
+            ))
+            .unwrap();
+    }
+
+    let api_tokens = state.db.get_api_tokens(user.id).await.unwrap();
+    if api_tokens.len() >= MAX_API_TOKENS {
+        return Response::builder()
+            .header("HX-Reswap", "none")
+            .body(format!(
+                r#"<div id="htmx-toaster" data-toast="error" hx-swap-oob="true">{}</div>"#,
+                state
+                    .localizer
+                    .localize(&lang, "account-error-api-token-limit", None)
+                    .unwrap(),
+            ))
+            .unwrap();
+    }
+
+    let new_token = state.db.create_api_token(user.id, name).await.unwrap();
+    let api_tokens = state.db.get_api_tokens(user.id).await.unwrap();
+
+    tracing::info!(user_id = user.id, name, "Created API token");
+
+    let html = state
+        .jinja
+        .get_template("account-api-tokens.html")
+        .unwrap()
+        .render(context! {
+            lang,
+            api_tokens,
+            new_token,
+        })
+        .unwrap();
+
+    Response::builder()
+        .header("content-type", "text/html")
+        .body(html)
+        .unwrap()
+}
+
+pub async fn route_account_delete_api_token(
+    state: State<RouterState>,
+    Extension(user): Extension<User>,
+    Extension(lang): Extension<Languages>,
+    Path(token_id): Path<i64>,
+) -> impl IntoResponse {
+    state.db.delete_api_token(user.id, token_id).await.unwrap();
+    let api_tokens = state.db.get_api_tokens(user.id).await.unwrap();
+
+    Html(
+        state
+            .jinja
+            .get_template("account-api-tokens.html")
+            .unwrap()
+            .render(context! {
+                lang,
+                api_tokens,
+            })
+            .unwrap(),
+    )
+}
+
 pub fn discord_cache_evictor() {
     tokio::task::spawn(async {
         let interval = Duration::from_secs(10);
@@ -0,0 +1,1174 @@
+use std::{collections::BTreeMap, num::NonZeroU64};
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{StatusCode, Uri},
+    response::{Html, IntoResponse, Response},
+    Extension, Form,
+};
+use chrono::{DateTime, TimeDelta, Utc};
+use fancy_regex::Regex;
+use minijinja::context;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    builder::hash,
+    challenge_loader_plugin::get_color,
+    internal::{
+        announcements,
+        auth::User,
+        database::provider::{
+            AnnouncementDefinition, AuthorDefinition, CategoryDefinition, Challenge,
+            ChallengeAttachment, ChallengeDefinition, ChallengeHint, ChallengeInstance,
+            ChallengePrerequisite, DynamicScoring, FlagMode, Healthcheck, ScoringCurve,
+            ScoringType, ISSUED_FLAG_PLACEHOLDER, TEAM_FLAG_PLACEHOLDER,
+        },
+        locales::Languages,
+        router::RouterState,
+    },
+};
+
+fn toast_error(state: &RouterState, lang: &Languages, message_id: &str) -> Response<String> {
+    Response::builder()
+        .body(format!(
+            r#"<div id="htmx-toaster" data-toast="error" hx-swap-oob="true">{}</div>"#,
+            state.localizer.localize(lang, message_id, None).unwrap(),
+        ))
+        .unwrap()
+}
+
+fn redirect(location: &str) -> Response<String> {
+    Response::builder()
+        .header("HX-Redirect", location)
+        .body("".to_owned())
+        .unwrap()
+}
+
+fn refresh() -> Response<String> {
+    Response::builder()
+        .header("HX-Trigger", "pageRefresh")
+        .body("".to_owned())
+        .unwrap()
+}
+
+impl From<&Challenge> for ChallengeDefinition {
+    fn from(challenge: &Challenge) -> Self {
+        ChallengeDefinition {
+            id: challenge.id,
+            name: challenge.name.clone(),
+            description: challenge.description.clone(),
+            flag: challenge.flag.clone(),
+            flag_mode: challenge.flag_mode,
+            category_id: challenge.category_id,
+            author_id: challenge.author_id,
+            ticket_template: challenge.ticket_template.clone(),
+            scoring_type: challenge.scoring_type.clone(),
+            static_points: challenge.static_points,
+            dynamic_scoring: challenge.dynamic_scoring.clone(),
+            healthscript: challenge.healthscript.clone(),
+            healthcheck_interval: challenge.healthcheck_interval,
+            attachments: challenge.attachments.clone(),
+            hints: challenge.hints.clone(),
+            prerequisites: challenge.prerequisites.clone(),
+            instance: challenge.instance.clone(),
+            release_at: challenge.release_at,
+            hidden: challenge.hidden,
+            admin_created: true,
+        }
+    }
+}
+
+pub async fn route_admin(
+    state: State<RouterState>,
+    Extension(user): Extension<User>,
+    Extension(lang): Extension<Languages>,
+    uri: Uri,
+) -> impl IntoResponse {
+    let challenge_data = state.db.get_challenges().await.unwrap();
+    let title = { state.settings.read().await.title.clone() };
+
+    let mut categories = BTreeMap::new();
+    for category in &challenge_data.categories {
+        categories.insert(category.id, category);
+    }
+
+    Html(
+        state
+            .jinja
+            .get_template("admin.html")
+            .unwrap()
+            .render(context! {
+                lang,
+                user,
+                title,
+                uri => uri.to_string(),
+                challenges => challenge_data.challenges,
+                categories,
+                authors => challenge_data.authors,
+            })
+            .unwrap(),
+    )
+}
+
+pub async fn route_admin_challenge_view(
+    state: State<RouterState>,
+    Extension(user): Extension<User>,
+    Extension(lang): Extension<Languages>,
+    challenge_id: Option<Path<i64>>,
+    uri: Uri,
+) -> impl IntoResponse {
+    let challenge_data = state.db.get_challenges().await.unwrap();
+    let title = { state.settings.read().await.title.clone() };
+
+    let challenge = if let Some(challenge_id) = challenge_id {
+        let Some(challenge) = challenge_data
+            .challenges
+            .iter()
+            .find(|c| challenge_id.eq(&c.id))
+        else {
+            return StatusCode::NOT_FOUND.into_response();
+        };
+        Some(challenge)
+    } else {
+        None
+    };
+
+    Html(
+        state
+            .jinja
+            .get_template("admin-challenge.html")
+            .unwrap()
+            .render(context! {
+                lang,
+                user,
+                title,
+                uri => uri.to_string(),
+                challenge,
+                categories => challenge_data.categories,
+                authors => challenge_data.authors,
+            })
+            .unwrap(),
+    )
+    .into_response()
+}
+
+#[derive(Deserialize)]
+pub struct ChallengeForm {
+    name: String,
+    description: String,
+    flag: String,
+    flag_mode: FlagMode,
+    category_id: i64,
+    author_id: i64,
+    points: String,
+    dynamic_initial: i64,
+    dynamic_minimum: i64,
+    dynamic_decay: i64,
+    dynamic_curve: ScoringCurve,
+    ticket_template: String,
+    healthscript: String,
+    healthcheck_interval: String,
+    release_at: String,
+    instance_spec: String,
+    instance_timeout: i64,
+    hidden: Option<String>,
+}
+
+pub async fn route_admin_challenge_create(
+    state: State<RouterState>,
+    Extension(lang): Extension<Languages>,
+    Form(form): Form<ChallengeForm>,
+) -> impl IntoResponse {
+    let id = hash(&form.name);
+
+    let challenge_data = state.db.get_challenges().await.unwrap();
+    if challenge_data.challenges.iter().any(|c| c.id == id) {
+        return toast_error(&state, &lang, "admin-error-challenge-name-taken");
+    }
+
+    save_challenge(&state, &lang, id, vec![], vec![], vec![], form).await
+}
+
+pub async fn route_admin_challenge_update(
+    state: State<RouterState>,
+    Extension(lang): Extension<Languages>,
+    challenge_id: Path<i64>,
+    Form(form): Form<ChallengeForm>,
+) -> impl IntoResponse {
+    let challenge_data = state.db.get_challenges().await.unwrap();
+    let Some(challenge) = challenge_data
+        .challenges
+        .iter()
+        .find(|c| challenge_id.eq(&c.id))
+    else {
+        return Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body("".to_owned())
+            .unwrap();
+    };
+
+    save_challenge(
+        &state,
+        &lang,
+        challenge.id,
+        challenge.attachments.clone(),
+        challenge.hints.clone(),
+        challenge.prerequisites.clone(),
+        form,
+    )
+    .await
+}
+
+async fn save_challenge(
+    state: &RouterState,
+    lang: &Languages,
+    id: i64,
+    attachments: Vec<ChallengeAttachment>,
+    hints: Vec<ChallengeHint>,
+    prerequisites: Vec<ChallengePrerequisite>,
+    form: ChallengeForm,
+) -> Response<String> {
+    let name = form.name.trim();
+    if name.is_empty() || form.flag.is_empty() {
+        return toast_error(state, lang, "admin-error-challenge-required");
+    }
+
+    if form.flag_mode == FlagMode::Regex && Regex::new(&form.flag).is_err() {
+        return toast_error(state, lang, "admin-error-challenge-flag-regex");
+    }
+
+    if form.flag_mode == FlagMode::PerTeam && !form.flag.contains(TEAM_FLAG_PLACEHOLDER) {
+        return toast_error(state, lang, "admin-error-challenge-flag-per-team");
+    }
+
+    if form.flag_mode == FlagMode::Issued && !form.flag.contains(ISSUED_FLAG_PLACEHOLDER) {
+        return toast_error(state, lang, "admin-error-challenge-flag-issued");
+    }
+
+    let (scoring_type, static_points) = if form.points.trim() == "dynamic" {
+        (ScoringType::Dynamic, None)
+    } else if let Ok(points) = form.points.trim().parse::<i64>() {
+        (ScoringType::Static, Some(points))
+    } else {
+        return toast_error(state, lang, "admin-error-challenge-points");
+    };
+
+    let dynamic_scoring = DynamicScoring {
+        initial: form.dynamic_initial,
+        minimum: form.dynamic_minimum,
+        decay: form.dynamic_decay,
+        curve: form.dynamic_curve,
+    };
+    if !dynamic_scoring.is_valid() {
+        return toast_error(state, lang, "admin-error-challenge-dynamic");
+    }
+
+    let healthcheck_interval = if form.healthcheck_interval.trim().is_empty() {
+        None
+    } else if let Some(interval) = form
+        .healthcheck_interval
+        .trim()
+        .parse::<i64>()
+        .ok()
+        .filter(|interval| *interval > 0)
+    {
+        Some(interval)
+    } else {
+        return toast_error(state, lang, "admin-error-challenge-healthcheck-interval");
+    };
+
+    let release_at = if form.release_at.trim().is_empty() {
+        None
+    } else if let Ok(release_at) = DateTime::parse_from_rfc3339(form.release_at.trim()) {
+        Some(release_at.with_timezone(&Utc))
+    } else {
+        return toast_error(state, lang, "admin-error-challenge-release");
+    };
+
+    let instance = if form.instance_spec.trim().is_empty() {
+        None
+    } else if form.instance_timeout > 0 {
+        Some(ChallengeInstance {
+            spec: form.instance_spec.trim().to_owned(),
+            timeout: form.instance_timeout,
+        })
+    } else {
+        return toast_error(state, lang, "admin-error-challenge-instance");
+    };
+
+    let challenge_data = state.db.get_challenges().await.unwrap();
+    if !challenge_data
+        .categories
+        .iter()
+        .any(|category| category.id == form.category_id)
+        || !challenge_data.authors.contains_key(&form.author_id)
+    {
+        return toast_error(state, lang, "admin-error-challenge-category-author");
+    }
+
+    let challenge = ChallengeDefinition {
+        id,
+        name: name.to_owned(),
+        description: form.description,
+        flag: form.flag,
+        flag_mode: form.flag_mode,
+        category_id: form.category_id,
+        author_id: form.author_id,
+        ticket_template: Some(form.ticket_template).filter(|t| !t.trim().is_empty()),
+        scoring_type,
+        static_points,
+        dynamic_scoring,
+        healthscript: Some(form.healthscript).filter(|h| !h.trim().is_empty()),
+        healthcheck_interval,
+        attachments,
+        hints,
+        prerequisites,
+        instance,
+        release_at,
+        hidden: form.hidden.is_some(),
+        admin_created: true,
+    };
+
+    if let Err(error) = state.db.upsert_challenge(&challenge).await {
+        tracing::error!("{:#?}", error);
+        return toast_error(state, lang, "unknown-error");
+    }
+
+    redirect(&format!("/admin/challenges/{}", id))
+}
+
+pub async fn route_admin_challenge_delete(
+    state: State<RouterState>,
+    Extension(lang): Extension<Languages>,
+    challenge_id: Path<i64>,
+) -> impl IntoResponse {
+    if let Err(error) = state.db.delete_challenge(challenge_id.0).await {
+        tracing::error!("{:#?}", error);
+        return toast_error(&state, &lang, "unknown-error");
+    }
+
+    redirect("/admin")
+}
+
+#[derive(Deserialize)]
+pub struct AttachmentForm {
+    name: String,
+    url: String,
+}
+
+pub async fn route_admin_attachment_add(
+    state: State<RouterState>,
+    Extension(lang): Extension<Languages>,
+    challenge_id: Path<i64>,
+    Form(form): Form<AttachmentForm>,
+) -> impl IntoResponse {
+    if form.name.trim().is_empty() || form.url.trim().is_empty() {
+        return toast_error(&state, &lang, "admin-error-attachment-required");
+    }
+
+    update_attachments(&state, &lang, challenge_id.0, |attachments| {
+        attachments.retain(|attachment| attachment.url != form.url);
+        attachments.push(ChallengeAttachment {
+            name: form.name.trim().to_owned(),
+            url: form.url.trim().to_owned(),
+        });
+    })
+    .await
+}
+
+#[derive(Deserialize)]
+pub struct AttachmentDelete {
+    url: String,
+}
+
+pub async fn route_admin_attachment_delete(
+    state: State<RouterState>,
+    Extension(lang): Extension<Languages>,
+    challenge_id: Path<i64>,
+    Form(form): Form<AttachmentDelete>,
+) -> impl IntoResponse {
+    update_attachments(&state, &lang, challenge_id.0, |attachments| {
+        attachments.retain(|attachment| attachment.url != form.url);
+    })
+    .await
+}
+
+async fn update_attachments(
+    state: &RouterState,
+    lang: &Languages,
+    challenge_id: i64,
+    update: impl FnOnce(&mut Vec<ChallengeAttachment>),
+) -> Response<String> {
+    let challenge_data = state.db.get_challenges().await.unwrap();
+    let Some(challenge) = challenge_data
+        .challenges
+        .iter()
+        .find(|c| c.id == challenge_id)
+    else {
+        return Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body("".to_owned())
+            .unwrap();
+    };
+
+    let mut challenge = ChallengeDefinition::from(challenge);
+    update(&mut challenge.attachments);
+
+    if let Err(error) = state.db.upsert_challenge(&challenge).await {
+        tracing::error!("{:#?}", error);
+        return toast_error(state, lang, "unknown-error");
+    }
+
+    redirect(&format!("/admin/challenges/{}", challenge_id))
+}
+
+#[derive(Deserialize)]
+pub struct CategoryForm {
+    id: Option<i64>,
+    name: String,
+    color: String,
+}
+
+pub async fn route_admin_category_upsert(
+    state: State<RouterState>,
+    Extension(lang): Extension<Languages>,
+    Form(form): Form<CategoryForm>,
+) -> impl IntoResponse {
+    let name = form.name.trim();
+    if name.is_empty() {
+        return toast_error(&state, &lang, "admin-error-category-required");
+    }
+
+    let color = if form.color.trim().is_empty() {
+        get_color(hash(name) as usize).to_owned()
+    } else {
+        form.color.trim().to_owned()
+    };
+
+    let category = CategoryDefinition {
+        id: form.id.unwrap_or_else(|| hash(name)),
+        name: name.to_owned(),
+        color,
+    };
+
+    if let Err(error) = state.db.upsert_category(&category).await {
+        tracing::error!("{:#?}", error);
+        return toast_error(&state, &lang, "unknown-error");
+    }
+
+    redirect("/admin")
+}
+
+pub async fn route_admin_category_delete(
+    state: State<RouterState>,
+    Extension(lang): Extension<Languages>,
+    category_id: Path<i64>,
+) -> impl IntoResponse {
+    let challenge_data = state.db.get_challenges().await.unwrap();
+    if challenge_data
+        .challenges
+        .iter()
+        .any(|challenge| challenge.category_id == category_id.0)
+    {
+        return toast_error(&state, &lang, "admin-error-category-in-use");
+    }
+
+    match state.db.delete_category(category_id.0).await {
+        Ok(true) => {}
+        // a challenge was added to it since it was checked above
+        Ok(false) => return toast_error(&state, &lang, "admin-error-category-in-use"),
+        Err(error) => {
+            tracing::error!("{:#?}", error);
+            return toast_error(&state, &lang, "unknown-error");
+        }
+    }
+
+    redirect("/admin")
+}
+
+#[derive(Deserialize)]
+pub struct AuthorForm {
+    id: Option<i64>,
+    name: String,
+    avatar: String,
+    discord_id: String,
+}
+
+pub async fn route_admin_author_upsert(
+    state: State<RouterState>,
+    Extension(lang): Extension<Languages>,
+    Form(form): Form<AuthorForm>,
+) -> impl IntoResponse {
+    let name = form.name.trim();
+    if name.is_empty() {
+        return toast_error(&state, &lang, "admin-error-author-required");
+    }
+
+    let Ok(discord_id) = form.discord_id.trim().parse::<NonZeroU64>() else {
+        return toast_error(&state, &lang, "admin-error-author-discord-id");
+    };
+
+    let author = AuthorDefinition {
+        id: form.id.unwrap_or_else(|| hash(name)),
+        name: name.to_owned(),
+        avatar: form.avatar.trim().to_owned(),
+        discord_id,
+    };
+
+    if let Err(error) = state.db.upsert_author(&author).await {
+        tracing::error!("{:#?}", error);
+        return toast_error(&state, &lang, "unknown-error");
+    }
+
+    redirect("/admin")
+}
+
+pub async fn route_admin_author_delete(
+    state: State<RouterState>,
+    Extension(lang): Extension<Languages>,
+    author_id: Path<i64>,
+) -> impl IntoResponse {
+    let challenge_data = state.db.get_challenges().await.unwrap();
+    if challenge_data
+        .challenges
+        .iter()
+        .any(|challenge| challenge.author_id == author_id.0)
+    {
+        return toast_error(&state, &lang, "admin-error-author-in-use");
+    }
+
+    match state.db.delete_author(author_id.0).await {
+        Ok(true) => {}
+        // a challenge was added to it since it was checked above
+        Ok(false) => return toast_error(&state, &lang, "admin-error-author-in-use"),
+        Err(error) => {
+            tracing::error!("{:#?}", error);
+            return toast_error(&state, &lang, "unknown-error");
+        }
+    }
+
+    redirect("/admin")
+}
+
+#[derive(Deserialize)]
+pub struct UserSearchParams {
+    q: Option<String>,
+    page: Option<u64>,
+}
+
+pub async fn route_admin_users(
+    state: State<RouterState>,
+    Extension(user): Extension<User>,
+    Extension(lang): Extension<Languages>,
+    params: Query<UserSearchParams>,
+    uri: Uri,
+) -> impl IntoResponse {
+    let page = params.page.unwrap_or(1).saturating_sub(1);
+    let search = params.q.as_deref().map(str::trim).filter(|q| !q.is_empty());
+
+    let users = state.db.get_users(search, page).await.unwrap();
+    let title = { state.settings.read().await.title.clone() };
+
+    Html(
+        state
+            .jinja
+            .get_template("admin-users.html")
+            .unwrap()
+            .render(context! {
+                lang,
+                user,
+                title,
+                uri => uri.to_string(),
+                users,
+                search,
+                page,
+            })
+            .unwrap(),
+    )
+}
+
+pub async fn route_admin_user_view(
+    state: State<RouterState>,
+    Extension(user): Extension<User>,
+    Extension(lang): Extension<Languages>,
+    user_id: Path<i64>,
+    uri: Uri,
+) -> impl IntoResponse {
+    let Ok(target_user) = state.db.get_user_from_id(user_id.0).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    let team = state.db.get_team_from_id(target_user.team_id);
+    let emails = state.db.get_emails_for_user_id(target_user.id);
+    let user_divisions = state.db.get_user_divisions(target_user.id);
+    let (team, emails, user_divisions) = futures::future::try_join3(team, emails, user_divisions)
+        .await
+        .unwrap();
+
+    let divisions = state
+        .divisions
+        .iter()
+        .filter(|division| user_divisions.contains(&division.id))
+        .map(|division| &division.name)
+        .collect::<Vec<_>>();
+
+    let title = { state.settings.read().await.title.clone() };
+
+    Html(
+        state
+            .jinja
+            .get_template("admin-user.html")
+            .unwrap()
+            .render(context! {
+                lang,
+                user,
+                title,
+                uri => uri.to_string(),
+                target_user,
+                team,
+                emails,
+                divisions,
+            })
+            .unwrap(),
+    )
+    .into_response()
+}
+
+#[derive(Deserialize)]
+pub struct UserDisabledForm {
+    disabled: bool,
+}
+
+pub async fn route_admin_user_set_disabled(
+    state: State<RouterState>,
+    Extension(user): Extension<User>,
+    Extension(lang): Extension<Languages>,
+    user_id: Path<i64>,
+    Form(form): Form<UserDisabledForm>,
+) -> impl IntoResponse {
+    if user_id.0 == user.id {
+        return toast_error(&state, &lang, "admin-error-user-self");
+    }
+
+    if let Err(error) = state.db.set_user_disabled(user_id.0, form.disabled).await {
+        tracing::error!("{:#?}", error);
+        return toast_error(&state, &lang, "unknown-error");
+    }
+
+    tracing::info!(
+        admin_id = user.id,
+        user_id = user_id.0,
+        disabled = form.disabled,
+        "Set user disabled"
+    );
+
+    refresh()
+}
+
+#[derive(Deserialize)]
+pub struct UserAdminForm {
+    is_admin: bool,
+}
+
+pub async fn route_admin_user_set_admin(
+    state: State<RouterState>,
+    Extension(user): Extension<User>,
+    Extension(lang): Extension<Languages>,
+    user_id: Path<i64>,
+    Form(form): Form<UserAdminForm>,
+) -> impl IntoResponse {
+    if user_id.0 == user.id {
+        return toast_error(&state, &lang, "admin-error-user-self");
+    }
+
+    if let Err(error) = state.db.set_user_admin(user_id.0, form.is_admin).await {
+        tracing::error!("{:#?}", error);
+        return toast_error(&state, &lang, "unknown-error");
+    }
+
+    tracing::info!(
+        admin_id = user.id,
+        user_id = user_id.0,
+        is_admin = form.is_admin,
+        "Set user admin"
+    );
+
+    refresh()
+}
+
+pub async fn route_admin_user_leave_team(
+    state: State<RouterState>,
+    Extension(lang): Extension<Languages>,
+    user_id: Path<i64>,
+) -> impl IntoResponse {
+    let Ok(target_user) = state.db.get_user_from_id(user_id.0).await else {
+        return Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body("".to_owned())
+            .unwrap();
+    };
+
+    if target_user.is_team_owner {
+        return toast_error(&state, &lang, "admin-error-user-team-owner");
+    }
+
+    if let Err(error) = state
+        .db
+        .kick_user(target_user.id, target_user.team_id)
+        .await
+    {
+        tracing::error!("{:#?}", error);
+        return toast_error(&state, &lang, "unknown-error");
+    }
+    if let Some(bot) = state.bot {
+        bot.sync_user_division_roles(state.divisions, target_user.id);
+    }
+
+    refresh()
+}
+
+#[derive(Deserialize)]
+pub struct SubmissionSearchParams {
+    team_id: Option<i64>,
+    challenge_id: Option<i64>,
+    page: Option<u64>,
+}
+
+pub async fn route_admin_submissions(
+    state: State<RouterState>,
+    Extension(user): Extension<User>,
+    Extension(lang): Extension<Languages>,
+    params: Query<SubmissionSearchParams>,
+    uri: Uri,
+) -> impl IntoResponse {
+    let page = params.page.unwrap_or(1).saturating_sub(1);
+
+    let challenge_data = state.db.get_challenges();
+    let submissions = state
+        .db
+        .get_wrong_submissions(params.team_id, params.challenge_id, page);
+    let shared_flags = state.db.get_shared_wrong_flags();
+    let (challenge_data, submissions, shared_flags) =
+        futures::future::try_join3(challenge_data, submissions, shared_flags)
+            .await
+            .unwrap();
+
+    let challenge_names = challenge_data
+        .challenges
+        .iter()
+        .map(|challenge| (challenge.id, challenge.name.clone()))
+        .collect::<BTreeMap<_, _>>();
+    let title = { state.settings.read().await.title.clone() };
+
+    Html(
+        state
+            .jinja
+            .get_template("admin-submissions.html")
+            .unwrap()
+            .render(context! {
+                lang,
+                user,
+                title,
+                uri => uri.to_string(),
+                submissions,
+                shared_flags,
+                challenge_names,
+                team_id => params.team_id,
+                challenge_id => params.challenge_id,
+                page,
+            })
+            .unwrap(),
+    )
+}
+
+#[derive(Deserialize)]
+pub struct PageParams {
+    page: Option<u64>,
+}
+
+pub async fn route_admin_incidents(
+    state: State<RouterState>,
+    Extension(user): Extension<User>,
+    Extension(lang): Extension<Languages>,
+    params: Query<PageParams>,
+    uri: Uri,
+) -> impl IntoResponse {
+    let page = params.page.unwrap_or(1).saturating_sub(1);
+
+    let challenge_data = state.db.get_challenges();
+    let incidents = state.db.get_cheating_incidents(page);
+    let (challenge_data, incidents) = futures::future::try_join(challenge_data, incidents)
+        .await
+        .unwrap();
+
+    let challenge_names = challenge_data
+        .challenges
+        .iter()
+        .map(|challenge| (challenge.id, challenge.name.clone()))
+        .collect::<BTreeMap<_, _>>();
+    let title = { state.settings.read().await.title.clone() };
+
+    Html(
+        state
+            .jinja
+            .get_template("admin-incidents.html")
+            .unwrap()
+            .render(context! {
+                lang,
+                user,
+                title,
+                uri => uri.to_string(),
+                incidents,
+                challenge_names,
+                page,
+            })
+            .unwrap(),
+    )
+}
+
+/// The last day of healthchecks is summarized into this many slots
+const HEALTH_TIMELINE_SLOTS: i64 = 48;
+
+#[derive(Serialize)]
+struct HealthSlot {
+    start: DateTime<Utc>,
+    checks: usize,
+    failures: usize,
+}
+
+#[derive(Serialize)]
+struct ChallengeHealth<'a> {
+    challenge: &'a Challenge,
+    /// Percentage of healthy checks over the last day
+    uptime: Option<f64>,
+    average_latency: Option<i64>,
+    slots: Vec<HealthSlot>,
+    /// Most recent first
+    failures: Vec<&'a Healthcheck>,
+}
+
+pub async fn route_admin_health(
+    state: State<RouterState>,
+    Extension(user): Extension<User>,
+    Extension(lang): Extension<Languages>,
+    uri: Uri,
+) -> impl IntoResponse {
+    let now = Utc::now();
+    let since = now - TimeDelta::days(1);
+    let slot_length = TimeDelta::days(1) / HEALTH_TIMELINE_SLOTS as i32;
+
+    let challenge_data = state.db.get_challenges();
+    let healthchecks = state.db.get_healthchecks(since);
+    let (challenge_data, healthchecks) = futures::future::try_join(challenge_data, healthchecks)
+        .await
+        .unwrap();
+
+    let challenges = challenge_data
+        .challenges
+        .iter()
+        .filter(|challenge| challenge.healthscript.is_some())
+        .map(|challenge| {
+            let checks = healthchecks
+                .iter()
+                .filter(|healthcheck| healthcheck.challenge_id == challenge.id)
+                .collect::<Vec<_>>();
+
+            let mut slots = (0..HEALTH_TIMELINE_SLOTS as i32)
+                .map(|i| HealthSlot {
+                    start: since + slot_length * i,
+                    checks: 0,
+                    failures: 0,
+                })
+                .collect::<Vec<_>>();
+            for healthcheck in &checks {
+                let i = ((healthcheck.checked_at - since).num_seconds() / slot_length.num_seconds())
+                    .clamp(0, HEALTH_TIMELINE_SLOTS - 1) as usize;
+                slots[i].checks += 1;
+                if healthcheck.healthy != Some(true) {
+                    slots[i].failures += 1;
+                }
+            }
+
+            let healthy = checks
+                .iter()
+                .filter(|healthcheck| healthcheck.healthy == Some(true))
+                .count();
+
+            ChallengeHealth {
+                challenge,
+                uptime: (!checks.is_empty()).then(|| healthy as f64 * 100.0 / checks.len() as f64),
+                average_latency: (!checks.is_empty()).then(|| {
+                    checks
+                        .iter()
+                        .map(|healthcheck| healthcheck.latency)
+                        .sum::<i64>()
+                        / checks.len() as i64
+                }),
+                slots,
+                failures: checks
+                    .iter()
+                    .rev()
+                    .filter(|healthcheck| healthcheck.healthy != Some(true))
+                    .take(5)
+                    .copied()
+                    .collect(),
+            }
+        })
+        .collect::<Vec<_>>();
+    let title = { state.settings.read().await.title.clone() };
+
+    Html(
+        state
+            .jinja
+            .get_template("admin-health.html")
+            .unwrap()
+            .render(context! {
+                lang,
+                user,
+                title,
+                uri => uri.to_string(),
+                challenges,
+            })
+            .unwrap(),
+    )
+}
+
+pub async fn route_admin_announcements(
+    state: State<RouterState>,
+    Extension(user): Extension<User>,
+    Extension(lang): Extension<Languages>,
+    uri: Uri,
+) -> impl IntoResponse {
+    let announcements = state.db.get_announcements().await.unwrap();
+
+    let (title, discord, email) = {
+        let settings = state.settings.read().await;
+        (
+            settings.title.clone(),
+            state.bot.is_some()
+                && settings
+                    .discord
+                    .as_ref()
+                    .is_some_and(|discord| discord.announcement_channel_id.is_some()),
+            state.outbound_mailer.is_some(),
+        )
+    };
+
+    Html(
+        state
+            .jinja
+            .get_template("admin-announcements.html")
+            .unwrap()
+            .render(context! {
+                lang,
+                user,
+                title,
+                uri => uri.to_string(),
+                announcements,
+                discord,
+                email,
+            })
+            .unwrap(),
+    )
+}
+
+#[derive(Deserialize)]
+pub struct AnnouncementForm {
+    title: String,
+    content: String,
+    banner: Option<String>,
+    discord: Option<String>,
+    email: Option<String>,
+}
+
+impl AnnouncementForm {
+    fn definition(&self) -> Option<AnnouncementDefinition> {
+        let title = self.title.trim();
+        let content = self.content.trim();
+        if title.is_empty() || content.is_empty() {
+            return None;
+        }
+
+        Some(AnnouncementDefinition {
+            title: title.to_owned(),
+            content: content.to_owned(),
+            banner: self.banner.is_some(),
+        })
+    }
+}
+
+pub async fn route_admin_announcement_create(
+    state: State<RouterState>,
+    Extension(lang): Extension<Languages>,
+    Form(form): Form<AnnouncementForm>,
+) -> impl IntoResponse {
+    let Some(announcement) = form.definition() else {
+        return toast_error(&state, &lang, "admin-error-announcement-required");
+    };
+
+    if let Err(error) = announcements::create_announcement(
+        state.db,
+        state.events,
+        state.bot,
+        state.outbound_mailer,
+        &announcement,
+        form.discord.is_some(),
+        form.email.is_some(),
+    )
+    .await
+    {
+        tracing::error!("{:#?}", error);
+        return toast_error(&state, &lang, "unknown-error");
+    }
+
+    redirect("/admin/announcements")
+}
+
+pub async fn route_admin_announcement_update(
+    state: State<RouterState>,
+    Extension(lang): Extension<Languages>,
+    announcement_id: Path<i64>,
+    Form(form): Form<AnnouncementForm>,
+) -> impl IntoResponse {
+    let Some(announcement) = form.definition() else {
+        return toast_error(&state, &lang, "admin-error-announcement-required");
+    };
+
+    if let Err(error) =
+        announcements::update_announcement(state.db, state.events, announcement_id.0, &announcement)
+            .await
+    {
+        tracing::error!("{:#?}", error);
+        return toast_error(&state, &lang, "unknown-error");
+    }
+
+    redirect("/admin/announcements")
+}
+
+pub async fn route_admin_announcement_delete(
+    state: State<RouterState>,
+    Extension(lang): Extension<Languages>,
+    announcement_id: Path<i64>,
+) -> impl IntoResponse {
+    if let Err(error) =
+        announcements::delete_announcement(state.db, state.events, announcement_id.0).await
+    {
+        tracing::error!("{:#?}", error);
+        return toast_error(&state, &lang, "unknown-error");
+    }
+
+    redirect("/admin/announcements")
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{header, Method, Request},
+        Router,
+    };
+    use chrono::{TimeDelta, Utc};
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use tower::ServiceExt;
+
+    use crate::{
+        builder::{
+            hash,
+            tests::{router, JWT_SECRET},
+        },
+        internal::{
+            auth::TokenClaims,
+            database::{
+                libsql::LocalLibSQL,
+                provider::{Challenges, Database},
+            },
+        },
+    };
+
+    async fn send(router: &Router, method: Method, uri: &str, token: &str, form: &str) {
+        let response = router
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(method)
+                    .uri(uri)
+                    .header(header::AUTHORIZATION, format!("Bearer {}", token))
+                    .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                    .body(Body::from(form.to_owned()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert!(response.headers().contains_key("HX-Redirect"), "{}", uri);
+    }
+
+    fn hidden(challenge_data: &Challenges, id: i64) -> Option<bool> {
+        challenge_data
+            .challenges
+            .iter()
+            .find(|challenge| challenge.id == id)
+            .map(|challenge| challenge.hidden)
+    }
+
+    #[tokio::test]
+    async fn challenge_crud() {
+        let database = LocalLibSQL::new_memory().await.unwrap();
+        database.migrate().await.unwrap();
+
+        database
+            .conn
+            .execute_batch(
+                "
+                INSERT INTO rhombus_category (id, name, color) VALUES (1, 'misc', '#ffffff');
+                INSERT INTO rhombus_author (id, name, avatar, discord_id) VALUES (1, 'author', '', 1);
+                ",
+            )
+            .await
+            .unwrap();
+
+        let (user_id, _, _) = database
+            .upsert_user_by_email("admin", "admin@example.com", "")
+            .await
+            .unwrap();
+        database.set_user_admin(user_id, true).await.unwrap();
+        let now = Utc::now();
+        let token = encode(
+            &Header::default(),
+            &TokenClaims {
+                sub: user_id,
+                iat: now.timestamp(),
+                exp: (now + TimeDelta::hours(1)).timestamp(),
+            },
+            &EncodingKey::from_secret(JWT_SECRET.as_ref()),
+        )
+        .unwrap();
+
+        let router = router(&database).await;
+
+        let form = "name=Admin&description=&flag=flag%7Badmin%7D&flag_mode=exact&category_id=1&author_id=1&points=100&dynamic_initial=500&dynamic_minimum=100&dynamic_decay=10&dynamic_curve=linear&ticket_template=&healthscript=&healthcheck_interval=&release_at=&instance_spec=&instance_timeout=0";
+        let id = hash("Admin");
+
+        send(
+            &router,
+            Method::POST,
+            "/admin/challenges",
+            &token,
+            &format!("{}&hidden=on", form),
+        )
+        .await;
+        assert_eq!(
+            Some(true),
+            hidden(&database.get_challenges().await.unwrap(), id)
+        );
+
+        let challenge = format!("/admin/challenges/{}", id);
+        send(&router, Method::POST, &challenge, &token, form).await;
+        assert_eq!(
+            Some(false),
+            hidden(&database.get_challenges().await.unwrap(), id)
+        );
+
+        send(&router, Method::DELETE, &challenge, &token, "").await;
+        assert_eq!(None, hidden(&database.get_challenges().await.unwrap(), id));
+    }
+}
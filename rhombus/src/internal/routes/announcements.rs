@@ -0,0 +1,73 @@
+use axum::{
+    extract::State,
+    http::Uri,
+    response::{Html, IntoResponse},
+    Extension,
+};
+use minijinja::context;
+
+use crate::internal::{
+    announcements::current_banner, auth::MaybeUser, locales::Languages, router::RouterState,
+};
+
+pub async fn route_announcements(
+    state: State<RouterState>,
+    Extension(user): Extension<MaybeUser>,
+    Extension(lang): Extension<Languages>,
+    uri: Uri,
+) -> impl IntoResponse {
+    let announcements = state.db.get_announcements().await.unwrap();
+
+    let (location_url, title) = {
+        let settings = state.settings.read().await;
+        (settings.location_url.clone(), settings.title.clone())
+    };
+
+    Html(
+        state
+            .jinja
+            .get_template("announcements.html")
+            .unwrap()
+            .render(context! {
+                title,
+                lang,
+                user,
+                location_url,
+                announcements,
+                uri => uri.to_string(),
+            })
+            .unwrap(),
+    )
+}
+
+/// The banner shown at the top of every page, loaded by the layout so pages do
+/// not need to render it themselves
+pub async fn route_banner(state: State<RouterState>) -> impl IntoResponse {
+    Html(render_banner(&state).await)
+}
+
+/// Renders the current banner announcement, or nothing if there is none. Also
+/// pushed to open pages by [route_live](super::live::route_live) whenever an
+/// announcement changes.
+pub async fn render_banner(state: &RouterState) -> String {
+    let announcements = match state.db.get_announcements().await {
+        Ok(announcements) => announcements,
+        Err(error) => {
+            tracing::error!(?error, "Failed to get announcements for banner");
+            return String::new();
+        }
+    };
+
+    let Some(announcement) = current_banner(&announcements) else {
+        return String::new();
+    };
+
+    state
+        .jinja
+        .get_template("banner.html")
+        .unwrap()
+        .render(context! {
+            announcement,
+        })
+        .unwrap()
+}
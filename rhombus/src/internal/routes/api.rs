@@ -0,0 +1,1047 @@
+use std::{collections::BTreeMap, net::IpAddr, num::NonZeroU64};
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::internal::{
+    auth::{ErrorResponse, MaybeUser, User},
+    database::provider::{
+        Category, Challenge, ChallengeAttachment, ChallengeDivision, ChallengeDivisionPoints,
+        FlagMode, TeamStandingEntry,
+    },
+    division::MaxDivisionPlayers,
+    locales::Languages,
+    router::RouterState,
+    routes::{
+        account::add_email,
+        challenges::{
+            challenge_unlocked, challenges_visible, issue_flag, submit_flag, SubmitError,
+        },
+        scoreboard::{freeze_challenges, standings_frozen_at},
+    },
+};
+
+fn api_error(status: StatusCode, message: impl Into<String>) -> Response {
+    (
+        status,
+        Json(ErrorResponse {
+            message: message.into(),
+        }),
+    )
+        .into_response()
+}
+
+fn api_error_localized(
+    state: &RouterState,
+    lang: &Languages,
+    status: StatusCode,
+    message_id: &str,
+) -> Response {
+    api_error(
+        status,
+        state.localizer.localize(lang, message_id, None).unwrap(),
+    )
+}
+
+#[derive(Serialize)]
+pub struct ApiDivision {
+    id: i64,
+    name: String,
+    description: String,
+    max_players: Option<u32>,
+}
+
+pub async fn route_api_divisions(state: State<RouterState>) -> impl IntoResponse {
+    let divisions = state
+        .divisions
+        .iter()
+        .map(|division| ApiDivision {
+            id: division.id,
+            name: division.name.clone(),
+            description: division.description.clone(),
+            max_players: match division.max_players {
+                MaxDivisionPlayers::Unlimited => None,
+                MaxDivisionPlayers::Limited(n) => Some(n.get()),
+            },
+        })
+        .collect::<Vec<_>>();
+
+    Json(divisions)
+}
+
+#[derive(Serialize)]
+pub struct ApiChallengeHealth {
+    healthy: bool,
+    last_checked: DateTime<Utc>,
+}
+
+#[derive(Serialize)]
+pub struct ApiChallenge {
+    id: i64,
+    name: String,
+    description: String,
+    description_html: String,
+    category_id: i64,
+    author_id: i64,
+    health: Option<ApiChallengeHealth>,
+    division_points: Vec<ChallengeDivisionPoints>,
+    attachments: Vec<ChallengeAttachment>,
+}
+
+#[derive(Serialize)]
+pub struct ApiAuthor {
+    name: String,
+    avatar_url: String,
+}
+
+#[derive(Serialize)]
+pub struct ApiChallenges {
+    ticket_enabled: bool,
+    challenges: Vec<ApiChallenge>,
+    categories: Vec<Category>,
+    authors: BTreeMap<i64, ApiAuthor>,
+    divisions: BTreeMap<i64, ChallengeDivision>,
+}
+
+fn api_challenge(challenge: &Challenge) -> ApiChallenge {
+    ApiChallenge {
+        id: challenge.id,
+        name: challenge.name.clone(),
+        description: challenge.description.clone(),
+        description_html: challenge.description_html.clone(),
+        category_id: challenge.category_id,
+        author_id: challenge.author_id,
+        health: challenge
+            .healthy
+            .zip(challenge.last_healthcheck)
+            .map(|(healthy, last_checked)| ApiChallengeHealth {
+                healthy,
+                last_checked,
+            }),
+        division_points: challenge.division_points.clone(),
+        attachments: challenge.attachments.clone(),
+    }
+}
+
+async fn ticket_enabled(state: &RouterState) -> bool {
+    let settings = state.settings.read().await;
+    state.bot.is_some()
+        && settings
+            .discord
+            .as_ref()
+            .and_then(|d| d.support_channel_id)
+            .is_some()
+}
+
+pub async fn route_api_challenges(
+    state: State<RouterState>,
+    Extension(user): Extension<User>,
+) -> impl IntoResponse {
+    let challenge_data = state.db.get_challenges();
+    let team = state.db.get_team_from_id(user.team_id);
+    let (challenge_data, team) = tokio::join!(challenge_data, team);
+    let challenge_data = challenge_data.unwrap();
+    let team = team.unwrap();
+    let visible = challenges_visible(&state, &user).await;
+    let frozen_at = standings_frozen_at(&state, Some(&user)).await;
+    let frozen_challenge_data =
+        match freeze_challenges(state.db, challenge_data.clone(), frozen_at).await {
+            Ok(frozen_challenge_data) => frozen_challenge_data,
+            Err(error) => {
+                tracing::error!(?error, "Failed to freeze challenges");
+                return api_error(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Failed to load challenges",
+                );
+            }
+        };
+
+    Json(ApiChallenges {
+        ticket_enabled: ticket_enabled(&state).await,
+        challenges: frozen_challenge_data
+            .challenges
+            .iter()
+            .filter(|challenge| {
+                visible
+                    && !challenge.hidden
+                    && challenge_unlocked(&user, &team, &challenge_data, challenge)
+            })
+            .map(api_challenge)
+            .collect(),
+        categories: challenge_data.categories.clone(),
+        authors: challenge_data
+            .authors
+            .iter()
+            .map(|(id, author)| {
+                (
+                    *id,
+                    ApiAuthor {
+                        name: author.name.clone(),
+                        avatar_url: author.avatar_url.clone(),
+                    },
+                )
+            })
+            .collect(),
+        divisions: challenge_data.divisions.clone(),
+    })
+    .into_response()
+}
+
+pub async fn route_api_challenge(
+    state: State<RouterState>,
+    Extension(user): Extension<User>,
+    Path(challenge_id): Path<i64>,
+) -> impl IntoResponse {
+    let challenge_data = state.db.get_challenges();
+    let team = state.db.get_team_from_id(user.team_id);
+    let (challenge_data, team) = tokio::join!(challenge_data, team);
+    let challenge_data = challenge_data.unwrap();
+    let team = team.unwrap();
+    let visible = challenges_visible(&state, &user).await;
+    let frozen_at = standings_frozen_at(&state, Some(&user)).await;
+    let frozen_challenge_data =
+        match freeze_challenges(state.db, challenge_data.clone(), frozen_at).await {
+            Ok(frozen_challenge_data) => frozen_challenge_data,
+            Err(error) => {
+                tracing::error!(?error, "Failed to freeze challenges");
+                return api_error(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Failed to load challenges",
+                );
+            }
+        };
+    let Some(challenge) = frozen_challenge_data.challenges.iter().find(|c| {
+        visible
+            && c.id == challenge_id
+            && !c.hidden
+            && challenge_unlocked(&user, &team, &challenge_data, c)
+    }) else {
+        return api_error(StatusCode::NOT_FOUND, "Challenge not found");
+    };
+
+    Json(api_challenge(challenge)).into_response()
+}
+
+#[derive(Deserialize)]
+pub struct ApiSubmitFlag {
+    flag: String,
+}
+
+#[derive(Serialize)]
+pub struct ApiSubmitFlagResponse {
+    first_blood_division_ids: Vec<i64>,
+}
+
+pub async fn route_api_challenge_submit(
+    state: State<RouterState>,
+    Extension(user): Extension<User>,
+    Extension(lang): Extension<Languages>,
+    Path(challenge_id): Path<i64>,
+    Json(body): Json<ApiSubmitFlag>,
+) -> impl IntoResponse {
+    let challenge_data = state.db.get_challenges();
+    let team = state.db.get_team_from_id(user.team_id);
+    let (challenge_data, team) = tokio::join!(challenge_data, team);
+    let challenge_data = challenge_data.unwrap();
+    let team = team.unwrap();
+    let visible = challenges_visible(&state, &user).await;
+    let Some(challenge) = challenge_data.challenges.iter().find(|c| {
+        visible
+            && c.id == challenge_id
+            && !c.hidden
+            && challenge_unlocked(&user, &team, &challenge_data, c)
+    }) else {
+        return api_error(StatusCode::NOT_FOUND, "Challenge not found");
+    };
+
+    match submit_flag(&state, &user, &challenge_data, challenge, &body.flag).await {
+        Ok(first_bloods) => Json(ApiSubmitFlagResponse {
+            first_blood_division_ids: first_bloods.division_ids,
+        })
+        .into_response(),
+        Err(error) => {
+            let status = match error {
+                SubmitError::Disabled | SubmitError::Ended => StatusCode::FORBIDDEN,
+                SubmitError::RateLimited(_) => StatusCode::TOO_MANY_REQUESTS,
+                SubmitError::IncorrectFlag => StatusCode::BAD_REQUEST,
+                SubmitError::Unknown => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            let mut response = api_error_localized(&state, &lang, status, error.message_id());
+            if let SubmitError::RateLimited(locked_until) = error {
+                let retry_after = (locked_until - Utc::now()).num_seconds().max(1);
+                response
+                    .headers_mut()
+                    .insert("Retry-After", retry_after.into());
+            }
+            response
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ApiIssueFlag {
+    team_id: i64,
+}
+
+#[derive(Serialize)]
+pub struct ApiIssueFlagResponse {
+    flag: String,
+}
+
+/// Issues a fresh flag to a team for a challenge in [FlagMode::Issued], for
+/// challenge backends which run per team servers outside of Rhombus. Requires
+/// the API token of an admin.
+pub async fn route_api_challenge_issue_flag(
+    state: State<RouterState>,
+    Extension(user): Extension<User>,
+    Path(challenge_id): Path<i64>,
+    Json(body): Json<ApiIssueFlag>,
+) -> impl IntoResponse {
+    if !user.is_admin {
+        return api_error(StatusCode::FORBIDDEN, "Only admins can issue flags");
+    }
+
+    let challenge_data = state.db.get_challenges().await.unwrap();
+    let Some(challenge) = challenge_data
+        .challenges
+        .iter()
+        .find(|c| c.id == challenge_id && c.flag_mode == FlagMode::Issued)
+    else {
+        return api_error(
+            StatusCode::NOT_FOUND,
+            "Challenge with issued flags not found",
+        );
+    };
+
+    if state.db.get_team_from_id(body.team_id).await.is_err() {
+        return api_error(StatusCode::NOT_FOUND, "Team not found");
+    }
+
+    match issue_flag(&state, challenge, body.team_id).await {
+        Ok(flag) => Json(ApiIssueFlagResponse { flag }).into_response(),
+        Err(error) => {
+            tracing::error!("{:#?}", error);
+            api_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to issue flag")
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ApiTicketSubmit {
+    content: String,
+}
+
+pub async fn route_api_challenge_ticket(
+    state: State<RouterState>,
+    Extension(user): Extension<User>,
+    Extension(lang): Extension<Languages>,
+    Path(challenge_id): Path<i64>,
+    Json(body): Json<ApiTicketSubmit>,
+) -> impl IntoResponse {
+    if !ticket_enabled(&state).await {
+        return api_error_localized(
+            &state,
+            &lang,
+            StatusCode::SERVICE_UNAVAILABLE,
+            "challenges-error-ticket-disabled",
+        );
+    }
+
+    if body.content.len() > 1000 {
+        return api_error_localized(
+            &state,
+            &lang,
+            StatusCode::BAD_REQUEST,
+            "challenges-error-ticket-too-long",
+        );
+    }
+
+    let challenge_data = state.db.get_challenges();
+    let team = state.db.get_team_from_id(user.team_id);
+    let (challenge_data, team) = tokio::join!(challenge_data, team);
+    let challenge_data = challenge_data.unwrap();
+    let team = team.unwrap();
+
+    let visible = challenges_visible(&state, &user).await;
+    let Some(challenge) = challenge_data.challenges.iter().find(|c| {
+        visible
+            && c.id == challenge_id
+            && !c.hidden
+            && challenge_unlocked(&user, &team, &challenge_data, c)
+    }) else {
+        return api_error(StatusCode::NOT_FOUND, "Challenge not found");
+    };
+
+    let author = challenge_data.authors.get(&challenge.author_id).unwrap();
+
+    if let Err(error) = state
+        .bot
+        .unwrap()
+        .create_support_thread(&user, &team, challenge, author, &body.content)
+        .await
+    {
+        tracing::error!("{:#?}", error);
+        return api_error_localized(
+            &state,
+            &lang,
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "unknown-error",
+        );
+    }
+
+    StatusCode::CREATED.into_response()
+}
+
+#[derive(Serialize)]
+pub struct ApiTeamUser {
+    name: String,
+    avatar_url: String,
+    is_team_owner: bool,
+}
+
+#[derive(Serialize)]
+pub struct ApiTeam {
+    id: i64,
+    name: String,
+    users: BTreeMap<i64, ApiTeamUser>,
+    divisions: Vec<i64>,
+    standings: BTreeMap<i64, TeamStandingEntry>,
+}
+
+pub async fn route_api_team(
+    state: State<RouterState>,
+    Extension(user): Extension<User>,
+) -> impl IntoResponse {
+    let team = state.db.get_team_from_id(user.team_id);
+    let team_divisions = state.db.get_team_divisions(user.team_id);
+    let frozen_at = standings_frozen_at(&state, Some(&user)).await;
+    let standings = state.db.get_team_standings(user.team_id, frozen_at);
+    let (team, team_divisions, standings) = tokio::join!(team, team_divisions, standings);
+    let team = team.unwrap();
+
+    Json(ApiTeam {
+        id: team.id,
+        name: team.name.clone(),
+        users: team
+            .users
+            .iter()
+            .map(|(id, team_user)| {
+                (
+                    *id,
+                    ApiTeamUser {
+                        name: team_user.name.clone(),
+                        avatar_url: team_user.avatar_url.clone(),
+                        is_team_owner: team_user.is_team_owner,
+                    },
+                )
+            })
+            .collect(),
+        divisions: team_divisions.unwrap(),
+        standings: standings.unwrap().standings,
+    })
+}
+
+pub async fn route_api_team_solves(
+    state: State<RouterState>,
+    Extension(user): Extension<User>,
+) -> impl IntoResponse {
+    let team = state.db.get_team_from_id(user.team_id).await.unwrap();
+
+    Json(team.solves.clone())
+}
+
+fn division_exists(state: &RouterState, division_id: i64) -> bool {
+    state
+        .divisions
+        .iter()
+        .any(|division| division.id == division_id)
+}
+
+pub async fn route_api_scoreboard(
+    state: State<RouterState>,
+    Extension(user): Extension<MaybeUser>,
+    Path(division_id): Path<i64>,
+) -> impl IntoResponse {
+    if !division_exists(&state, division_id) {
+        return api_error(StatusCode::NOT_FOUND, "Division not found");
+    }
+
+    let frozen_at = standings_frozen_at(&state, user.as_ref()).await;
+    let scoreboard = state
+        .db
+        .get_scoreboard(division_id, frozen_at)
+        .await
+        .unwrap();
+
+    Json(scoreboard.teams).into_response()
+}
+
+#[derive(Deserialize)]
+pub struct ApiPageParams {
+    page: Option<u64>,
+}
+
+pub async fn route_api_leaderboard(
+    state: State<RouterState>,
+    Extension(user): Extension<MaybeUser>,
+    Path(division_id): Path<i64>,
+    Query(params): Query<ApiPageParams>,
+) -> impl IntoResponse {
+    if !division_exists(&state, division_id) {
+        return api_error(StatusCode::NOT_FOUND, "Division not found");
+    }
+
+    let page = params.page.unwrap_or(1).saturating_sub(1);
+    let frozen_at = standings_frozen_at(&state, user.as_ref()).await;
+    let leaderboard = state
+        .db
+        .get_leaderboard(division_id, Some(page), frozen_at)
+        .await
+        .unwrap();
+
+    Json(leaderboard).into_response()
+}
+
+#[derive(Serialize)]
+pub struct ApiAccount {
+    id: i64,
+    name: String,
+    avatar_url: String,
+    discord_id: Option<NonZeroU64>,
+    team_id: i64,
+    is_team_owner: bool,
+    divisions: Vec<i64>,
+}
+
+pub async fn route_api_account(
+    state: State<RouterState>,
+    Extension(user): Extension<User>,
+) -> impl IntoResponse {
+    let divisions = state.db.get_user_divisions(user.id).await.unwrap();
+
+    Json(ApiAccount {
+        id: user.id,
+        name: user.name.clone(),
+        avatar_url: user.avatar.clone(),
+        discord_id: user.discord_id,
+        team_id: user.team_id,
+        is_team_owner: user.is_team_owner,
+        divisions,
+    })
+}
+
+pub async fn route_api_account_emails(
+    state: State<RouterState>,
+    Extension(user): Extension<User>,
+) -> impl IntoResponse {
+    let emails = state.db.get_emails_for_user_id(user.id).await.unwrap();
+
+    Json(emails)
+}
+
+#[derive(Deserialize)]
+pub struct ApiEmailSubmit {
+    email: String,
+}
+
+pub async fn route_api_account_add_email(
+    state: State<RouterState>,
+    Extension(user): Extension<User>,
+    Extension(ip): Extension<Option<IpAddr>>,
+    Extension(lang): Extension<Languages>,
+    Json(body): Json<ApiEmailSubmit>,
+) -> impl IntoResponse {
+    if let Err(message_id) = add_email(&state, &user, ip, &body.email).await {
+        return api_error_localized(&state, &lang, StatusCode::BAD_REQUEST, message_id);
+    }
+
+    StatusCode::ACCEPTED.into_response()
+}
+
+pub async fn route_api_account_delete_email(
+    state: State<RouterState>,
+    Extension(user): Extension<User>,
+    Extension(lang): Extension<Languages>,
+    Path(email): Path<String>,
+) -> impl IntoResponse {
+    let emails = state.db.get_emails_for_user_id(user.id).await.unwrap();
+
+    if !emails.iter().any(|e| e.address == email) {
+        return api_error(StatusCode::NOT_FOUND, "Email not found");
+    }
+
+    if emails.len() == 1 {
+        return api_error_localized(
+            &state,
+            &lang,
+            StatusCode::BAD_REQUEST,
+            "account-error-email-last",
+        );
+    }
+
+    state.db.delete_email(user.id, &email).await.unwrap();
+
+    StatusCode::NO_CONTENT.into_response()
+}
+
+pub async fn route_api_openapi(state: State<RouterState>) -> impl IntoResponse {
+    let (title, location_url) = {
+        let settings = state.settings.read().await;
+        (settings.title.clone(), settings.location_url.clone())
+    };
+
+    Json(openapi_document(&title, &location_url))
+}
+
+/// OpenAPI 3 description of the `/api/v1` routes. Keep in sync with the
+/// handlers and response types above.
+fn openapi_document(title: &str, location_url: &str) -> serde_json::Value {
+    let error = json!({ "$ref": "#/components/responses/Error" });
+    let unauthorized = json!({ "$ref": "#/components/responses/Unauthorized" });
+    let id_param = |name: &str| {
+        json!({
+            "name": name,
+            "in": "path",
+            "required": true,
+            "schema": { "type": "integer", "format": "int64" },
+        })
+    };
+    let json_body = |schema: serde_json::Value| {
+        json!({
+            "required": true,
+            "content": { "application/json": { "schema": schema } },
+        })
+    };
+    let ok = |description: &str, schema: serde_json::Value| {
+        json!({
+            "description": description,
+            "content": { "application/json": { "schema": schema } },
+        })
+    };
+    let schema = |name: &str| json!({ "$ref": format!("#/components/schemas/{}", name) });
+    let map_of = |name: &str| json!({ "type": "object", "additionalProperties": schema(name) });
+    let authed = json!([{ "bearer": [] }, { "cookie": [] }]);
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": format!("{} API", title),
+            "version": "1",
+        },
+        "servers": [{ "url": format!("{}/api/v1", location_url) }],
+        "security": authed,
+        "paths": {
+            "/divisions": {
+                "get": {
+                    "summary": "List divisions",
+                    "security": [],
+                    "responses": {
+                        "200": ok("Divisions", json!({ "type": "array", "items": schema("Division") })),
+                    },
+                },
+            },
+            "/challenges": {
+                "get": {
+                    "summary": "List visible challenges with categories, authors and divisions",
+                    "responses": {
+                        "200": ok("Challenges", schema("Challenges")),
+                        "401": unauthorized,
+                    },
+                },
+            },
+            "/challenges/{id}": {
+                "get": {
+                    "summary": "Get a challenge",
+                    "parameters": [id_param("id")],
+                    "responses": {
+                        "200": ok("Challenge", schema("Challenge")),
+                        "401": unauthorized,
+                        "404": error,
+                    },
+                },
+            },
+            "/challenges/{id}/submit": {
+                "post": {
+                    "summary": "Submit a flag",
+                    "parameters": [id_param("id")],
+                    "requestBody": json_body(json!({
+                        "type": "object",
+                        "required": ["flag"],
+                        "properties": { "flag": { "type": "string" } },
+                    })),
+                    "responses": {
+                        "200": ok("Correct flag", schema("SubmitFlagResponse")),
+                        "400": error,
+                        "401": unauthorized,
+                        "403": error,
+                        "404": error,
+                        "429": error,
+                        "500": error,
+                    },
+                },
+            },
+            "/challenges/{id}/flags": {
+                "post": {
+                    "summary": "Issue a fresh flag to a team for a challenge with issued flags. Requires an admin token",
+                    "parameters": [id_param("id")],
+                    "requestBody": json_body(json!({
+                        "type": "object",
+                        "required": ["team_id"],
+                        "properties": { "team_id": { "type": "integer", "format": "int64" } },
+                    })),
+                    "responses": {
+                        "200": ok("Issued flag", schema("IssueFlagResponse")),
+                        "401": unauthorized,
+                        "403": error,
+                        "404": error,
+                        "500": error,
+                    },
+                },
+            },
+            "/challenges/{id}/ticket": {
+                "post": {
+                    "summary": "Open a support ticket for a challenge",
+                    "parameters": [id_param("id")],
+                    "requestBody": json_body(json!({
+                        "type": "object",
+                        "required": ["content"],
+                        "properties": { "content": { "type": "string", "maxLength": 1000 } },
+                    })),
+                    "responses": {
+                        "201": { "description": "Ticket opened" },
+                        "400": error,
+                        "401": unauthorized,
+                        "404": error,
+                        "503": error,
+                    },
+                },
+            },
+            "/team": {
+                "get": {
+                    "summary": "Get the current team",
+                    "responses": {
+                        "200": ok("Team", schema("Team")),
+                        "401": unauthorized,
+                    },
+                },
+            },
+            "/team/solves": {
+                "get": {
+                    "summary": "Solves of the current team keyed by challenge id",
+                    "responses": {
+                        "200": ok("Solves", map_of("Solve")),
+                        "401": unauthorized,
+                    },
+                },
+            },
+            "/scoreboard/{division_id}": {
+                "get": {
+                    "summary": "Score series of the top teams in a division keyed by team id",
+                    "security": [],
+                    "parameters": [id_param("division_id")],
+                    "responses": {
+                        "200": ok("Scoreboard", map_of("ScoreboardTeam")),
+                        "404": error,
+                    },
+                },
+            },
+            "/leaderboard/{division_id}": {
+                "get": {
+                    "summary": "Ranked teams in a division",
+                    "security": [],
+                    "parameters": [
+                        id_param("division_id"),
+                        {
+                            "name": "page",
+                            "in": "query",
+                            "schema": { "type": "integer", "minimum": 1, "default": 1 },
+                        },
+                    ],
+                    "responses": {
+                        "200": ok("Leaderboard", schema("Leaderboard")),
+                        "404": error,
+                    },
+                },
+            },
+            "/account": {
+                "get": {
+                    "summary": "Get the current user",
+                    "responses": {
+                        "200": ok("Account", schema("Account")),
+                        "401": unauthorized,
+                    },
+                },
+            },
+            "/account/emails": {
+                "get": {
+                    "summary": "List emails of the current user",
+                    "responses": {
+                        "200": ok("Emails", json!({ "type": "array", "items": schema("Email") })),
+                        "401": unauthorized,
+                    },
+                },
+                "post": {
+                    "summary": "Add an email and send a verification link to it",
+                    "requestBody": json_body(json!({
+                        "type": "object",
+                        "required": ["email"],
+                        "properties": { "email": { "type": "string", "maxLength": 255 } },
+                    })),
+                    "responses": {
+                        "202": { "description": "Verification email sent" },
+                        "400": error,
+                        "401": unauthorized,
+                    },
+                },
+            },
+            "/account/emails/{email}": {
+                "delete": {
+                    "summary": "Remove an email",
+                    "parameters": [{
+                        "name": "email",
+                        "in": "path",
+                        "required": true,
+                        "schema": { "type": "string" },
+                    }],
+                    "responses": {
+                        "204": { "description": "Email removed" },
+                        "400": error,
+                        "401": unauthorized,
+                        "404": error,
+                    },
+                },
+            },
+        },
+        "components": {
+            "securitySchemes": {
+                "bearer": {
+                    "type": "http",
+                    "scheme": "bearer",
+                    "description": "Personal API token created on the account page",
+                },
+                "cookie": {
+                    "type": "apiKey",
+                    "in": "cookie",
+                    "name": "rhombus-token",
+                },
+            },
+            "responses": {
+                "Error": {
+                    "description": "Request failed",
+                    "content": { "application/json": { "schema": schema("Error") } },
+                },
+                "Unauthorized": {
+                    "description": "Missing or invalid credentials",
+                    "content": { "application/json": { "schema": schema("Error") } },
+                },
+            },
+            "schemas": {
+                "Error": {
+                    "type": "object",
+                    "properties": { "message": { "type": "string" } },
+                },
+                "Division": {
+                    "type": "object",
+                    "properties": {
+                        "id": { "type": "integer", "format": "int64" },
+                        "name": { "type": "string" },
+                        "description": { "type": "string" },
+                        "max_players": { "type": "integer", "nullable": true },
+                    },
+                },
+                "Challenge": {
+                    "type": "object",
+                    "properties": {
+                        "id": { "type": "integer", "format": "int64" },
+                        "name": { "type": "string" },
+                        "description": { "type": "string", "description": "Markdown source" },
+                        "description_html": { "type": "string", "description": "Sanitized HTML rendered from description" },
+                        "category_id": { "type": "integer", "format": "int64" },
+                        "author_id": { "type": "integer", "format": "int64" },
+                        "health": {
+                            "type": "object",
+                            "nullable": true,
+                            "properties": {
+                                "healthy": { "type": "boolean" },
+                                "last_checked": { "type": "string", "format": "date-time" },
+                            },
+                        },
+                        "division_points": {
+                            "type": "array",
+                            "items": {
+                                "type": "object",
+                                "properties": {
+                                    "division_id": { "type": "integer", "format": "int64" },
+                                    "points": { "type": "integer" },
+                                    "solves": { "type": "integer" },
+                                },
+                            },
+                        },
+                        "attachments": {
+                            "type": "array",
+                            "items": {
+                                "type": "object",
+                                "properties": {
+                                    "name": { "type": "string" },
+                                    "url": { "type": "string" },
+                                },
+                            },
+                        },
+                    },
+                },
+                "Challenges": {
+                    "type": "object",
+                    "properties": {
+                        "ticket_enabled": { "type": "boolean" },
+                        "challenges": { "type": "array", "items": schema("Challenge") },
+                        "categories": {
+                            "type": "array",
+                            "items": {
+                                "type": "object",
+                                "properties": {
+                                    "id": { "type": "integer", "format": "int64" },
+                                    "name": { "type": "string" },
+                                    "color": { "type": "string" },
+                                },
+                            },
+                        },
+                        "authors": {
+                            "type": "object",
+                            "additionalProperties": {
+                                "type": "object",
+                                "properties": {
+                                    "name": { "type": "string" },
+                                    "avatar_url": { "type": "string" },
+                                },
+                            },
+                        },
+                        "divisions": {
+                            "type": "object",
+                            "additionalProperties": {
+                                "type": "object",
+                                "properties": { "name": { "type": "string" } },
+                            },
+                        },
+                    },
+                },
+                "SubmitFlagResponse": {
+                    "type": "object",
+                    "properties": {
+                        "first_blood_division_ids": {
+                            "type": "array",
+                            "items": { "type": "integer", "format": "int64" },
+                        },
+                    },
+                },
+                "IssueFlagResponse": {
+                    "type": "object",
+                    "properties": {
+                        "flag": { "type": "string" },
+                    },
+                },
+                "Team": {
+                    "type": "object",
+                    "properties": {
+                        "id": { "type": "integer", "format": "int64" },
+                        "name": { "type": "string" },
+                        "users": {
+                            "type": "object",
+                            "additionalProperties": {
+                                "type": "object",
+                                "properties": {
+                                    "name": { "type": "string" },
+                                    "avatar_url": { "type": "string" },
+                                    "is_team_owner": { "type": "boolean" },
+                                },
+                            },
+                        },
+                        "divisions": {
+                            "type": "array",
+                            "items": { "type": "integer", "format": "int64" },
+                        },
+                        "standings": {
+                            "type": "object",
+                            "additionalProperties": {
+                                "type": "object",
+                                "properties": {
+                                    "points": { "type": "integer" },
+                                    "rank": { "type": "integer" },
+                                },
+                            },
+                        },
+                    },
+                },
+                "Solve": {
+                    "type": "object",
+                    "properties": {
+                        "solved_at": { "type": "string", "format": "date-time" },
+                        "user_id": { "type": "integer", "format": "int64" },
+                    },
+                },
+                "ScoreboardTeam": {
+                    "type": "object",
+                    "properties": {
+                        "team_name": { "type": "string" },
+                        "series": {
+                            "type": "array",
+                            "items": {
+                                "type": "object",
+                                "properties": {
+                                    "timestamp": { "type": "integer", "format": "int64" },
+                                    "total_score": { "type": "integer", "format": "int64" },
+                                },
+                            },
+                        },
+                    },
+                },
+                "Leaderboard": {
+                    "type": "object",
+                    "properties": {
+                        "num_pages": { "type": "integer" },
+                        "entries": {
+                            "type": "array",
+                            "items": {
+                                "type": "object",
+                                "properties": {
+                                    "team_id": { "type": "integer", "format": "int64" },
+                                    "team_name": { "type": "string" },
+                                    "score": { "type": "integer", "format": "int64" },
+                                    "rank": { "type": "integer" },
+                                },
+                            },
+                        },
+                    },
+                },
+                "Account": {
+                    "type": "object",
+                    "properties": {
+                        "id": { "type": "integer", "format": "int64" },
+                        "name": { "type": "string" },
+                        "avatar_url": { "type": "string" },
+                        "discord_id": { "type": "integer", "nullable": true },
+                        "team_id": { "type": "integer", "format": "int64" },
+                        "is_team_owner": { "type": "boolean" },
+                        "divisions": {
+                            "type": "array",
+                            "items": { "type": "integer", "format": "int64" },
+                        },
+                    },
+                },
+                "Email": {
+                    "type": "object",
+                    "properties": {
+                        "address": { "type": "string" },
+                        "verified": { "type": "boolean" },
+                    },
+                },
+            },
+        },
+    })
+}
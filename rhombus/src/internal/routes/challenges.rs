@@ -3,15 +3,54 @@ use std::time::Duration;
 use axum::{
     body::Body,
     extract::{Path, State},
-    http::{Request, Uri},
+    http::{Request, StatusCode, Uri},
     response::{Html, IntoResponse, Response},
     Extension, Form, Json,
 };
+use chrono::{DateTime, TimeDelta, Utc};
 use minijinja::context;
 use serde::Deserialize;
 use serde_json::json;
 
-use crate::internal::{auth::User, locales::Languages, router::RouterState};
+use crate::internal::{
+    auth::User,
+    database::provider::{Challenge, ChallengeData, FirstBloods, FlagMode, TeamInner},
+    events::RhombusEvent,
+    instance::{Instances, RunningInstance},
+    locales::Languages,
+    router::RouterState,
+    routes::scoreboard::{freeze_challenges, standings_frozen_at},
+};
+
+/// Challenges stay hidden from everyone but admins until the CTF starts
+pub async fn challenges_visible(state: &RouterState, user: &User) -> bool {
+    user.is_admin || state.settings.read().await.has_started()
+}
+
+/// Whether `challenge` has been released and the team of `user` has met its
+/// prerequisites. Like [challenges_visible], admins can always see every
+/// challenge
+pub fn challenge_unlocked(
+    user: &User,
+    team: &TeamInner,
+    challenge_data: &ChallengeData,
+    challenge: &Challenge,
+) -> bool {
+    user.is_admin || (challenge.is_released() && challenge_data.is_unlocked(challenge, team))
+}
+
+/// Whether `challenge` is listed for `user` on the challenges page, where
+/// `visible` is the result of [challenges_visible]. Nothing naming a challenge
+/// which is not listed may be shown to the user.
+pub fn challenge_listed(
+    visible: bool,
+    user: &User,
+    team: &TeamInner,
+    challenge_data: &ChallengeData,
+    challenge: &Challenge,
+) -> bool {
+    visible && !challenge.hidden && challenge_unlocked(user, team, challenge_data, challenge)
+}
 
 pub async fn route_challenges(
     state: State<RouterState>,
@@ -26,21 +65,36 @@ pub async fn route_challenges(
     let challenge_data = challenge_data.unwrap();
     let team = team.unwrap();
 
-    let ticket_enabled = {
+    let (ticket_enabled, start_time) = {
         let settings = state.settings.read().await;
-        settings
-            .discord
-            .as_ref()
-            .and_then(|d| d.support_channel_id)
-            .is_some()
+        (
+            settings
+                .discord
+                .as_ref()
+                .and_then(|d| d.support_channel_id)
+                .is_some(),
+            settings.start_time,
+        )
     };
+    let visible = challenges_visible(&state, &user).await;
+
+    // unlocking goes by live points, only what is shown is frozen
+    let frozen_at = standings_frozen_at(&state, Some(&user)).await;
+    let frozen_challenge_data =
+        match freeze_challenges(state.db, challenge_data.clone(), frozen_at).await {
+            Ok(frozen_challenge_data) => frozen_challenge_data,
+            Err(error) => {
+                tracing::error!(?error, "Failed to freeze challenges");
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
+        };
 
     let challenge_json = json!({
         "ticket_enabled": ticket_enabled,
-        "challenges": challenge_data.challenges.iter().map(|challenge| json!({
+        "challenges": frozen_challenge_data.challenges.iter().filter(|challenge| challenge_listed(visible, &user, &team, &challenge_data, challenge)).map(|challenge| json!({
             "id": challenge.id,
             "name": challenge.name,
-            "description": challenge.description,
+            "description": challenge.description_html,
             "health": if let (Some(healthy), Some(last_checked)) = (challenge.healthy, challenge.last_healthcheck) {
                 Some(json!({
                     "last_checked": last_checked,
@@ -107,6 +161,7 @@ pub async fn route_challenges(
             title,
             uri => uri.to_string(),
             challenge_json,
+            start_time => start_time.filter(|_| !visible),
         })
         .unwrap();
 
@@ -128,16 +183,32 @@ pub async fn route_challenge_view(
     let team = team.unwrap();
     let user_writeups = user_writeups.unwrap();
 
-    let challenge = challenge_data
-        .challenges
-        .iter()
-        .find(|c| challenge_id.eq(&c.id))
-        .unwrap();
+    let visible = challenges_visible(&state, &user).await;
+    let frozen_at = standings_frozen_at(&state, Some(&user)).await;
+    let frozen_challenge_data =
+        match freeze_challenges(state.db, challenge_data.clone(), frozen_at).await {
+            Ok(frozen_challenge_data) => frozen_challenge_data,
+            Err(error) => {
+                tracing::error!(?error, "Failed to freeze challenges");
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
+        };
+    let Some(challenge) = frozen_challenge_data.challenges.iter().find(|c| {
+        visible
+            && challenge_id.eq(&c.id)
+            && !c.hidden
+            && challenge_unlocked(&user, &team, &challenge_data, c)
+    }) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
     let category = challenge_data
         .categories
         .iter()
         .find(|c| challenge.category_id.eq(&c.id))
         .unwrap();
+    let instance = state
+        .instances
+        .map(|instances| instances.get(challenge.id, user.team_id));
 
     Html(
         state
@@ -153,17 +224,19 @@ pub async fn route_challenge_view(
                 team => team,
                 divisions => challenge_data.divisions,
                 user_writeups => user_writeups,
+                instances_enabled => instance.is_some(),
+                instance => instance.flatten(),
             })
             .unwrap(),
     )
+    .into_response()
 }
 
-pub async fn route_ticket_view(
+pub async fn route_hint_unlock(
     state: State<RouterState>,
     Extension(user): Extension<User>,
     Extension(lang): Extension<Languages>,
-    challenge_id: Path<i64>,
-    uri: Uri,
+    Path((challenge_id, hint_id)): Path<(i64, i64)>,
 ) -> impl IntoResponse {
     let challenge_data = state.db.get_challenges();
     let team = state.db.get_team_from_id(user.team_id);
@@ -171,11 +244,234 @@ pub async fn route_ticket_view(
     let challenge_data = challenge_data.unwrap();
     let team = team.unwrap();
 
-    let challenge = challenge_data
+    let visible = challenges_visible(&state, &user).await;
+    let Some((challenge, hint)) = challenge_data
         .challenges
         .iter()
-        .find(|c| challenge_id.eq(&c.id))
+        .find(|c| {
+            visible
+                && c.id == challenge_id
+                && !c.hidden
+                && challenge_unlocked(&user, &team, &challenge_data, c)
+        })
+        .and_then(|c| c.hints.iter().find(|h| h.id == hint_id).map(|h| (c, h)))
+    else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    if state.settings.read().await.has_ended() {
+        return Response::builder()
+            .header("Content-Type", "text/html")
+            .header("HX-Reswap", "none")
+            .body(format!(
+                r#"<div id="htmx-toaster" data-toast="error" hx-swap-oob="true">{}</div>"#,
+                state
+                    .localizer
+                    .localize(&lang, "challenges-error-hint-ended", None)
+                    .unwrap()
+            ))
+            .unwrap()
+            .into_response();
+    }
+
+    state
+        .db
+        .unlock_hint(user.id, user.team_id, challenge.id, hint)
+        .await
         .unwrap();
+
+    let team = state.db.get_team_from_id(user.team_id).await.unwrap();
+
+    Html(
+        state
+            .jinja
+            .get_template("challenge-hint.html")
+            .unwrap()
+            .render(context! {
+                lang,
+                user,
+                challenge,
+                hint,
+                team,
+            })
+            .unwrap(),
+    )
+    .into_response()
+}
+
+/// Finds a challenge with per team instances which `user` can see
+async fn find_instanced_challenge(
+    state: &RouterState,
+    user: &User,
+    challenge_id: i64,
+) -> Option<(Challenge, &'static Instances)> {
+    let instances = state.instances?;
+
+    let challenge_data = state.db.get_challenges();
+    let team = state.db.get_team_from_id(user.team_id);
+    let (challenge_data, team) = tokio::join!(challenge_data, team);
+    let challenge_data = challenge_data.unwrap();
+    let team = team.unwrap();
+
+    let visible = challenges_visible(state, user).await;
+    challenge_data
+        .challenges
+        .iter()
+        .find(|c| {
+            visible
+                && c.id == challenge_id
+                && !c.hidden
+                && c.instance.is_some()
+                && challenge_unlocked(user, &team, &challenge_data, c)
+        })
+        .map(|c| (c.clone(), instances))
+}
+
+fn render_instance(
+    state: &RouterState,
+    lang: &Languages,
+    challenge: &Challenge,
+    instance: Option<RunningInstance>,
+) -> Response {
+    Html(
+        state
+            .jinja
+            .get_template("challenge-instance.html")
+            .unwrap()
+            .render(context! {
+                lang,
+                challenge,
+                instance,
+            })
+            .unwrap(),
+    )
+    .into_response()
+}
+
+fn instance_error(state: &RouterState, lang: &Languages, message_id: &str) -> Response {
+    Response::builder()
+        .header("Content-Type", "text/html")
+        .header("HX-Reswap", "none")
+        .body(format!(
+            r#"<div id="htmx-toaster" data-toast="error" hx-swap-oob="true">{}</div>"#,
+            state.localizer.localize(lang, message_id, None).unwrap()
+        ))
+        .unwrap()
+        .into_response()
+}
+
+pub async fn route_instance_start(
+    state: State<RouterState>,
+    Extension(user): Extension<User>,
+    Extension(lang): Extension<Languages>,
+    challenge_id: Path<i64>,
+) -> impl IntoResponse {
+    let Some((challenge, instances)) =
+        find_instanced_challenge(&state, &user, challenge_id.0).await
+    else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    let (ended, jwt_secret) = {
+        let settings = state.settings.read().await;
+        (settings.has_ended(), settings.jwt_secret.clone())
+    };
+    if ended {
+        return instance_error(&state, &lang, "challenges-error-instance-ended");
+    }
+
+    // only issued once a new instance is actually started
+    let flag = || async {
+        if challenge.flag_mode == FlagMode::Issued {
+            issue_flag(&state, &challenge, user.team_id).await
+        } else {
+            Ok(challenge.team_flag(&jwt_secret, user.team_id))
+        }
+    };
+    match instances.start(&challenge, user.team_id, flag).await {
+        Ok(Some(instance)) => {
+            tracing::info!(
+                user_id = user.id,
+                team_id = user.team_id,
+                challenge_id = challenge.id,
+                "Started instance"
+            );
+            render_instance(&state, &lang, &challenge, Some(instance))
+        }
+        Ok(None) => instance_error(&state, &lang, "challenges-error-instance-starting"),
+        Err(error) => {
+            tracing::error!(
+                ?error,
+                challenge_id = challenge.id,
+                "Failed to start instance"
+            );
+            instance_error(&state, &lang, "challenges-error-instance-start")
+        }
+    }
+}
+
+pub async fn route_instance_extend(
+    state: State<RouterState>,
+    Extension(user): Extension<User>,
+    Extension(lang): Extension<Languages>,
+    challenge_id: Path<i64>,
+) -> impl IntoResponse {
+    let Some((challenge, instances)) =
+        find_instanced_challenge(&state, &user, challenge_id.0).await
+    else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    let instance = instances.extend(&challenge, user.team_id);
+    render_instance(&state, &lang, &challenge, instance)
+}
+
+pub async fn route_instance_stop(
+    state: State<RouterState>,
+    Extension(user): Extension<User>,
+    Extension(lang): Extension<Languages>,
+    challenge_id: Path<i64>,
+) -> impl IntoResponse {
+    let Some((challenge, instances)) =
+        find_instanced_challenge(&state, &user, challenge_id.0).await
+    else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    if let Err(error) = instances.stop(challenge.id, user.team_id).await {
+        tracing::error!(
+            ?error,
+            challenge_id = challenge.id,
+            "Failed to stop instance"
+        );
+        return instance_error(&state, &lang, "unknown-error");
+    }
+
+    render_instance(&state, &lang, &challenge, None)
+}
+
+pub async fn route_ticket_view(
+    state: State<RouterState>,
+    Extension(user): Extension<User>,
+    Extension(lang): Extension<Languages>,
+    challenge_id: Path<i64>,
+    uri: Uri,
+) -> impl IntoResponse {
+    let challenge_data = state.db.get_challenges();
+    let team = state.db.get_team_from_id(user.team_id);
+    let (challenge_data, team) = tokio::join!(challenge_data, team);
+    let challenge_data = challenge_data.unwrap();
+    let team = team.unwrap();
+
+    let visible = challenges_visible(&state, &user).await;
+    let Some(challenge) = challenge_data.challenges.iter().find(|c| {
+        visible
+            && challenge_id.eq(&c.id)
+            && !c.hidden
+            && challenge_unlocked(&user, &team, &challenge_data, c)
+    }) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
     let category = challenge_data
         .categories
         .iter()
@@ -204,6 +500,7 @@ pub async fn route_ticket_view(
             })
             .unwrap(),
     )
+    .into_response()
 }
 
 #[derive(Deserialize)]
@@ -261,11 +558,18 @@ pub async fn route_ticket_submit(
     let challenge_data = challenge_data.unwrap();
     let team = team.unwrap();
 
-    let challenge = challenge_data
-        .challenges
-        .iter()
-        .find(|c| challenge_id.eq(&c.id))
-        .unwrap();
+    let visible = challenges_visible(&state, &user).await;
+    let Some(challenge) = challenge_data.challenges.iter().find(|c| {
+        visible
+            && challenge_id.eq(&c.id)
+            && !c.hidden
+            && challenge_unlocked(&user, &team, &challenge_data, c)
+    }) else {
+        return Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body("".to_owned())
+            .unwrap();
+    };
 
     let author = challenge_data.authors.get(&challenge.author_id).unwrap();
 
@@ -295,6 +599,318 @@ pub async fn route_ticket_submit(
         .unwrap()
 }
 
+#[derive(Debug)]
+pub enum SubmitError {
+    Disabled,
+    Ended,
+    RateLimited(DateTime<Utc>),
+    IncorrectFlag,
+    Unknown,
+}
+
+impl SubmitError {
+    /// Locale key of the message shown to the submitter
+    pub fn message_id(&self) -> &'static str {
+        match self {
+            SubmitError::Disabled => "account-error-disabled",
+            SubmitError::Ended => "challenges-error-ended",
+            SubmitError::RateLimited(_) => "challenges-error-rate-limited",
+            SubmitError::IncorrectFlag => "challenges-error-incorrect-flag",
+            SubmitError::Unknown => "unknown-error",
+        }
+    }
+}
+
+/// Records a cheating incident if `flag` is the per team flag of a team other
+/// than the submitter's. The submitter is only told the flag is incorrect.
+async fn record_shared_team_flag(
+    state: &RouterState,
+    user: &User,
+    challenge: &Challenge,
+    jwt_secret: &str,
+    flag: &str,
+) {
+    // anything else cannot be a team's flag, so there is no need to go
+    // through every team
+    if !challenge.is_team_flag_format(flag) {
+        return;
+    }
+
+    let team_ids = match state.db.get_team_ids().await {
+        Ok(team_ids) => team_ids,
+        Err(error) => {
+            tracing::error!("{:#?}", error);
+            return;
+        }
+    };
+
+    let Some(flag_team_id) = team_ids.into_iter().find(|&team_id| {
+        team_id != user.team_id && challenge.team_flag(jwt_secret, team_id) == flag
+    }) else {
+        return;
+    };
+
+    tracing::warn!(
+        user_id = user.id,
+        team_id = user.team_id,
+        flag_team_id,
+        challenge_id = challenge.id,
+        "Submitted another team's flag"
+    );
+
+    if let Err(error) = state
+        .db
+        .add_cheating_incident(user.id, user.team_id, flag_team_id, challenge.id, flag)
+        .await
+    {
+        tracing::error!("{:#?}", error);
+    }
+}
+
+/// Returns when the team's lockout ends if it has submitted too many wrong
+/// flags recently, either overall or to this challenge.
+async fn submission_locked_until(
+    state: &RouterState,
+    team_id: i64,
+    challenge_id: i64,
+) -> Option<DateTime<Utc>> {
+    let ratelimit = state.settings.read().await.submission_ratelimit.clone()?;
+    let window = TimeDelta::seconds(ratelimit.window_seconds.unwrap_or(60) as i64);
+    let lockout = TimeDelta::seconds(ratelimit.lockout_seconds.unwrap_or(300) as i64);
+    let now = Utc::now();
+
+    let times = state
+        .db
+        .get_team_wrong_submission_times(team_id, now - window - lockout)
+        .await
+        .unwrap();
+    let team_times = times.iter().map(|(_, at)| *at).collect::<Vec<_>>();
+    let challenge_times = times
+        .iter()
+        .filter(|(id, _)| *id == challenge_id)
+        .map(|(_, at)| *at)
+        .collect::<Vec<_>>();
+
+    [
+        ratelimit
+            .per_team_attempts
+            .and_then(|attempts| lockout_end(&team_times, attempts, window, lockout)),
+        ratelimit
+            .per_challenge_attempts
+            .and_then(|attempts| lockout_end(&challenge_times, attempts, window, lockout)),
+    ]
+    .into_iter()
+    .flatten()
+    .max()
+    .filter(|end| *end > now)
+}
+
+/// End of the latest lockout in `times`, where a lockout starts whenever
+/// `attempts` wrong submissions fall within `window`. `times` must be sorted.
+fn lockout_end(
+    times: &[DateTime<Utc>],
+    attempts: usize,
+    window: TimeDelta,
+    lockout: TimeDelta,
+) -> Option<DateTime<Utc>> {
+    if attempts == 0 {
+        return None;
+    }
+
+    times
+        .windows(attempts)
+        .filter(|run| run[attempts - 1] - run[0] <= window)
+        .map(|run| run[attempts - 1] + lockout)
+        .last()
+}
+
+/// Issues a fresh [FlagMode::Issued] flag of `challenge` to `team_id`
+pub async fn issue_flag(
+    state: &RouterState,
+    challenge: &Challenge,
+    team_id: i64,
+) -> crate::Result<String> {
+    let flag = challenge.random_flag();
+    state
+        .db
+        .add_issued_flag(challenge.id, team_id, &flag)
+        .await?;
+
+    tracing::info!(team_id, challenge_id = challenge.id, flag, "Issued flag");
+
+    Ok(flag)
+}
+
+/// Whether `flag` was issued to the team of `user`. Submitting a flag issued to
+/// another team is recorded as a cheating incident, and the submitter is only
+/// told the flag is incorrect.
+async fn check_issued_flag(
+    state: &RouterState,
+    user: &User,
+    challenge: &Challenge,
+    flag: &str,
+) -> Result<bool, SubmitError> {
+    let flag_team_id = state
+        .db
+        .get_issued_flag_team_id(challenge.id, flag)
+        .await
+        .map_err(|error| {
+            tracing::error!("{:#?}", error);
+            SubmitError::Unknown
+        })?;
+
+    match flag_team_id {
+        Some(flag_team_id) if flag_team_id == user.team_id => {
+            tracing::info!(
+                user_id = user.id,
+                team_id = user.team_id,
+                challenge_id = challenge.id,
+                flag,
+                "Accepted issued flag"
+            );
+            Ok(true)
+        }
+        Some(flag_team_id) => {
+            tracing::warn!(
+                user_id = user.id,
+                team_id = user.team_id,
+                flag_team_id,
+                challenge_id = challenge.id,
+                flag,
+                "Rejected flag issued to another team"
+            );
+            if let Err(error) = state
+                .db
+                .add_cheating_incident(user.id, user.team_id, flag_team_id, challenge.id, flag)
+                .await
+            {
+                tracing::error!("{:#?}", error);
+            }
+            Ok(false)
+        }
+        None => {
+            tracing::info!(
+                user_id = user.id,
+                team_id = user.team_id,
+                challenge_id = challenge.id,
+                flag,
+                "Rejected flag which was never issued"
+            );
+            Ok(false)
+        }
+    }
+}
+
+/// Checks a flag submission, records the solve and announces first bloods.
+/// Shared by the htmx route and the JSON API.
+pub async fn submit_flag(
+    state: &RouterState,
+    user: &User,
+    challenge_data: &ChallengeData,
+    challenge: &Challenge,
+    flag: &str,
+) -> Result<FirstBloods, SubmitError> {
+    if user.disabled {
+        return Err(SubmitError::Disabled);
+    }
+
+    if state.settings.read().await.has_ended() {
+        return Err(SubmitError::Ended);
+    }
+
+    if let Some(locked_until) = submission_locked_until(state, user.team_id, challenge.id).await {
+        return Err(SubmitError::RateLimited(locked_until));
+    }
+
+    let jwt_secret = state.settings.read().await.jwt_secret.clone();
+    let correct = if challenge.flag_mode == FlagMode::Issued {
+        check_issued_flag(state, user, challenge, flag).await?
+    } else {
+        let team_flag = challenge.team_flag(&jwt_secret, user.team_id);
+        challenge.flag_mode.matches(&team_flag, flag)
+    };
+    if !correct {
+        if let Err(error) = state
+            .db
+            .add_wrong_submission(user.id, user.team_id, challenge.id, flag)
+            .await
+        {
+            tracing::error!("{:#?}", error);
+        }
+        if challenge.flag_mode == FlagMode::PerTeam {
+            record_shared_team_flag(state, user, challenge, &jwt_secret, flag).await;
+        }
+        return Err(SubmitError::IncorrectFlag);
+    }
+
+    let first_bloods = state
+        .db
+        .solve_challenge(user.id, user.team_id, challenge)
+        .await
+        .map_err(|error| {
+            tracing::error!("{:#?}", error);
+            SubmitError::Unknown
+        })?;
+
+    // teammates solving it again change nothing, so only the first solve is
+    // published, which is also what webhooks are sent for
+    if !first_bloods.first_team_solve {
+        return Ok(first_bloods);
+    }
+
+    let team = state.db.get_team_from_id(user.team_id).await.unwrap();
+    state.events.publish(RhombusEvent::Solve {
+        user: user.clone(),
+        team: team.clone(),
+        challenge: challenge.clone(),
+    });
+    if !first_bloods.division_ids.is_empty() {
+        state.events.publish(RhombusEvent::FirstBlood {
+            user: user.clone(),
+            team: team.clone(),
+            challenge: challenge.clone(),
+            division_ids: first_bloods.division_ids.clone(),
+        });
+    }
+
+    if let Some(bot) = state.bot {
+        let first_blood_enabled = {
+            let settings = state.settings.read().await;
+            settings
+                .discord
+                .as_ref()
+                .and_then(|d| d.first_blood_channel_id)
+                .is_some()
+        };
+
+        if first_blood_enabled && !first_bloods.division_ids.is_empty() {
+            _ = bot
+                .send_first_blood(
+                    user,
+                    &team,
+                    challenge,
+                    &challenge_data.divisions,
+                    &challenge_data.categories,
+                    &first_bloods,
+                )
+                .await;
+            tracing::info!(
+                user_id = user.id,
+                challenge_id = challenge.id,
+                divisions = first_bloods
+                    .division_ids
+                    .iter()
+                    .map(|n| n.to_string())
+                    .collect::<Vec<String>>()
+                    .join(","),
+                "First blooded"
+            );
+        }
+    }
+
+    Ok(first_bloods)
+}
+
 #[derive(Deserialize)]
 pub struct SubmitChallenge {
     flag: String,
@@ -307,43 +923,32 @@ pub async fn route_challenge_submit(
     challenge_id: Path<i64>,
     Form(form): Form<SubmitChallenge>,
 ) -> impl IntoResponse {
-    let challenge_data = state.db.get_challenges().await.unwrap();
-    let challenge = challenge_data
-        .challenges
-        .iter()
-        .find(|c| challenge_id.eq(&c.id))
-        .unwrap();
-
-    if challenge.flag != form.flag {
-        let html = state
-            .jinja
-            .get_template("challenge-submit.html")
-            .unwrap()
-            .render(context! {
-                lang => lang,
-                error => state.localizer.localize(&lang, "challenges-error-incorrect-flag", None),
-            })
-            .unwrap();
+    let challenge_data = state.db.get_challenges();
+    let team = state.db.get_team_from_id(user.team_id);
+    let (challenge_data, team) = tokio::join!(challenge_data, team);
+    let challenge_data = challenge_data.unwrap();
+    let team = team.unwrap();
+    let visible = challenges_visible(&state, &user).await;
+    let Some(challenge) = challenge_data.challenges.iter().find(|c| {
+        visible
+            && challenge_id.eq(&c.id)
+            && !c.hidden
+            && challenge_unlocked(&user, &team, &challenge_data, c)
+    }) else {
         return Response::builder()
-            .header("content-type", "text/html")
-            .body(html)
+            .status(StatusCode::NOT_FOUND)
+            .body("".to_owned())
             .unwrap();
-    }
-
-    let first_bloods = state
-        .db
-        .solve_challenge(user.id, user.team_id, challenge)
-        .await;
+    };
 
-    if let Err(error) = first_bloods {
-        tracing::error!("{:#?}", error);
+    if let Err(error) = submit_flag(&state, &user, &challenge_data, challenge, &form.flag).await {
         let html = state
             .jinja
             .get_template("challenge-submit.html")
             .unwrap()
             .render(context! {
                 lang => lang,
-                error => state.localizer.localize(&lang, "unknown-error", None),
+                error => state.localizer.localize(&lang, error.message_id(), None),
             })
             .unwrap();
         return Response::builder()
@@ -352,46 +957,6 @@ pub async fn route_challenge_submit(
             .unwrap();
     }
 
-    if let Some(bot) = state.bot {
-        let first_blood_enabled = {
-            let settings = state.settings.read().await;
-            settings
-                .discord
-                .as_ref()
-                .and_then(|d| d.first_blood_channel_id)
-                .is_some()
-        };
-
-        if first_blood_enabled {
-            let first_bloods = first_bloods.unwrap();
-
-            if !first_bloods.division_ids.is_empty() {
-                let team = state.db.get_team_from_id(user.team_id).await.unwrap();
-                _ = bot
-                    .send_first_blood(
-                        &user,
-                        &team,
-                        challenge,
-                        &challenge_data.divisions,
-                        &challenge_data.categories,
-                        &first_bloods,
-                    )
-                    .await;
-                tracing::info!(
-                    user_id = user.id,
-                    challenge_id = challenge.id,
-                    divisions = first_bloods
-                        .division_ids
-                        .iter()
-                        .map(|n| n.to_string())
-                        .collect::<Vec<String>>()
-                        .join(","),
-                    "First blooded"
-                );
-            }
-        }
-    }
-
     Response::builder()
         .header("Content-Type", "text/html")
         .header(
@@ -538,3 +1103,175 @@ pub async fn route_writeup_delete(
         .body("".to_owned())
         .unwrap()
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use chrono::{DateTime, TimeDelta, Utc};
+
+    use axum::{
+        body::Body,
+        http::{header, Request, StatusCode},
+    };
+    use serde_json::json;
+    use tower::ServiceExt;
+
+    use super::{challenge_listed, lockout_end};
+    use crate::{
+        builder::tests::{router, JWT_SECRET},
+        internal::{
+            auth::UserInner,
+            database::{
+                libsql::LocalLibSQL,
+                provider::{
+                    tests::{challenge, team},
+                    ChallengeData, ChallengePrerequisite, Database,
+                },
+            },
+        },
+    };
+
+    fn at(seconds: i64) -> DateTime<Utc> {
+        DateTime::<Utc>::from_timestamp(seconds, 0).unwrap()
+    }
+
+    #[test]
+    fn lockout_after_burst() {
+        let window = TimeDelta::seconds(60);
+        let lockout = TimeDelta::seconds(300);
+
+        let spread_out = [at(0), at(100), at(200)];
+        assert_eq!(lockout_end(&spread_out, 3, window, lockout), None);
+
+        let burst = [at(0), at(100), at(110), at(120), at(500)];
+        assert_eq!(lockout_end(&burst, 3, window, lockout), Some(at(420)));
+
+        assert_eq!(lockout_end(&burst, 0, window, lockout), None);
+    }
+
+    #[test]
+    fn lockout_window_edge() {
+        let window = TimeDelta::seconds(60);
+        let lockout = TimeDelta::seconds(300);
+
+        // attempts exactly `window` apart still count as one burst
+        let on_edge = [at(0), at(30), at(60)];
+        assert_eq!(lockout_end(&on_edge, 3, window, lockout), Some(at(360)));
+
+        let past_edge = [at(0), at(30), at(61)];
+        assert_eq!(lockout_end(&past_edge, 3, window, lockout), None);
+
+        let too_few = [at(0), at(1)];
+        assert_eq!(lockout_end(&too_few, 3, window, lockout), None);
+        assert_eq!(lockout_end(&too_few, 1, window, lockout), Some(at(301)));
+    }
+
+    #[test]
+    fn listed_challenges() {
+        let user = Arc::new(UserInner {
+            id: 1,
+            name: "user".to_owned(),
+            avatar: String::new(),
+            discord_id: None,
+            team_id: 1,
+            is_team_owner: true,
+            disabled: false,
+            is_admin: false,
+        });
+        let admin = Arc::new(UserInner {
+            is_admin: true,
+            ..(*user).clone()
+        });
+
+        let open = challenge(1);
+        let mut locked = challenge(2);
+        locked.prerequisites = vec![ChallengePrerequisite::Solve { challenge_id: 1 }];
+        let mut hidden = challenge(3);
+        hidden.hidden = true;
+        let mut unreleased = challenge(4);
+        unreleased.release_at = Some(Utc::now() + TimeDelta::hours(1));
+        let challenge_data = ChallengeData {
+            challenges: vec![
+                open.clone(),
+                locked.clone(),
+                hidden.clone(),
+                unreleased.clone(),
+            ],
+            categories: vec![],
+            authors: Default::default(),
+            divisions: Default::default(),
+        };
+        let fresh_team = team(1, &[]);
+        let solved_team = team(1, &[1]);
+
+        let listed = |visible, user, team, challenge| {
+            challenge_listed(visible, user, team, &challenge_data, challenge)
+        };
+
+        assert!(listed(true, &user, &fresh_team, &open));
+        assert!(!listed(false, &user, &fresh_team, &open));
+        assert!(!listed(true, &user, &fresh_team, &locked));
+        assert!(listed(true, &user, &solved_team, &locked));
+        assert!(!listed(true, &user, &solved_team, &hidden));
+        assert!(!listed(true, &user, &solved_team, &unreleased));
+        assert!(listed(true, &admin, &fresh_team, &locked));
+        assert!(listed(true, &admin, &fresh_team, &unreleased));
+    }
+
+    #[tokio::test]
+    async fn submit_team_flags() {
+        let database = LocalLibSQL::new_memory().await.unwrap();
+        database.migrate().await.unwrap();
+
+        database
+            .conn
+            .execute_batch(
+                "
+                INSERT INTO rhombus_category (id, name, color) VALUES (1, 'misc', '#ffffff');
+                INSERT INTO rhombus_author (id, name, avatar, discord_id) VALUES (1, 'author', '', 1);
+                INSERT INTO rhombus_challenge (id, name, description, flag, category_id, author_id, score_type, static_points, flag_mode)
+                VALUES (1, 'per team', '', 'flag{{hmac}}', 1, 1, 1, 100, 5);
+                ",
+            )
+            .await
+            .unwrap();
+
+        let (user_id, team_id, _) = database
+            .upsert_user_by_email("user", "user@example.com", "")
+            .await
+            .unwrap();
+        let (_, other_team_id, _) = database
+            .upsert_user_by_email("other", "other@example.com", "")
+            .await
+            .unwrap();
+        let token = database.create_api_token(user_id, "test").await.unwrap();
+
+        let challenge_data = database.get_challenges().await.unwrap();
+        let challenge = &challenge_data.challenges[0];
+        let own_flag = challenge.team_flag(JWT_SECRET, team_id);
+        let other_flag = challenge.team_flag(JWT_SECRET, other_team_id);
+
+        let router = router(&database).await;
+        let submit = |flag: &str| {
+            Request::post("/api/v1/challenges/1/submit")
+                .header(header::AUTHORIZATION, format!("Bearer {}", token))
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(json!({ "flag": flag }).to_string()))
+                .unwrap()
+        };
+
+        let response = router.clone().oneshot(submit(&other_flag)).await.unwrap();
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+
+        let incidents = database.get_cheating_incidents(0).await.unwrap().incidents;
+        assert_eq!(1, incidents.len());
+        assert_eq!(team_id, incidents[0].team_id);
+        assert_eq!(other_team_id, incidents[0].flag_team_id);
+
+        let response = router.oneshot(submit(&own_flag)).await.unwrap();
+        assert_eq!(StatusCode::OK, response.status());
+        let team = database.get_team_from_id(team_id).await.unwrap();
+        assert!(team.solves.contains_key(&1));
+    }
+}
@@ -14,32 +14,18 @@ pub async fn route_home(
     Extension(lang): Extension<Languages>,
     uri: Uri,
 ) -> impl IntoResponse {
-    let (location_url, title, home) = {
+    let (location_url, title, content) = {
         let settings = state.settings.read().await;
         (
             settings.location_url.clone(),
             settings.title.clone(),
-            settings.home.clone(),
+            settings
+                .home
+                .as_ref()
+                .and_then(|home| home.content_html.clone()),
         )
     };
 
-    let content = home.and_then(|home| {
-        home.content.map(|content| {
-            markdown::to_html_with_options(
-                &content,
-                &markdown::Options {
-                    compile: markdown::CompileOptions {
-                        allow_dangerous_html: true,
-                        allow_dangerous_protocol: true,
-                        ..markdown::CompileOptions::default()
-                    },
-                    ..markdown::Options::default()
-                },
-            )
-            .unwrap()
-        })
-    });
-
     Html(
         state
             .jinja
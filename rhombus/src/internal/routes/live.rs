@@ -0,0 +1,174 @@
+use std::convert::Infallible;
+
+use axum::{
+    extract::State,
+    response::sse::{Event, KeepAlive, Sse},
+    Extension,
+};
+use futures::{Stream, StreamExt};
+use minijinja::context;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::internal::{
+    auth::MaybeUser,
+    events::RhombusEvent,
+    locales::Languages,
+    router::RouterState,
+    routes::{
+        announcements::render_banner,
+        challenges::{challenge_listed, challenges_visible},
+        scoreboard::standings_frozen_at,
+    },
+};
+
+/// Server-sent events for every open page, connected to from `base.html`
+/// through the htmx SSE extension. Each event is named after what it updates:
+///
+/// - `banner` carries the new banner, swapped in with `sse-swap`
+/// - `scoreboard` tells the scoreboard to refetch its standings
+/// - `challenges` tells the challenges view to refetch, after a teammate
+///   solved a challenge or a challenge's health changed
+/// - `toast` carries an out of band toast, such as a first blood
+///
+/// The user is looked up again for every event, so that team changes apply
+/// right away. A stream of a user who was banned ends.
+pub async fn route_live(
+    state: State<RouterState>,
+    Extension(user): Extension<MaybeUser>,
+    Extension(lang): Extension<Languages>,
+) -> Sse<impl Stream<Item = std::result::Result<Event, Infallible>>> {
+    let state = state.0;
+    let user_id = user.map(|user| user.id);
+
+    let stream = futures::stream::unfold(state.events.subscribe(), move |mut receiver| {
+        let lang = lang.clone();
+        async move {
+            loop {
+                let event = receiver.recv().await;
+
+                let user = match user_id {
+                    Some(user_id) => match state.db.get_user_from_id(user_id).await {
+                        Ok(user) if !user.disabled => Some(user),
+                        _ => return None,
+                    },
+                    None => None,
+                };
+
+                let events = match event {
+                    Ok(event) => live_events(state, &user, &lang, event).await,
+                    // whatever was missed, refetching catches up with it
+                    Err(RecvError::Lagged(_)) => vec![refresh("scoreboard"), refresh("challenges")],
+                    Err(RecvError::Closed) => return None,
+                };
+
+                if !events.is_empty() {
+                    return Some((events, receiver));
+                }
+            }
+        }
+    })
+    .flat_map(|events| futures::stream::iter(events.into_iter().map(Ok)));
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+async fn live_events(
+    state: RouterState,
+    user: &MaybeUser,
+    lang: &Languages,
+    event: RhombusEvent,
+) -> Vec<Event> {
+    match event {
+        RhombusEvent::Solve {
+            user: solver,
+            team,
+            challenge,
+        } => {
+            let mut events = vec![];
+
+            if standings_frozen_at(&state, user.as_ref()).await.is_none() {
+                events.push(refresh("scoreboard"));
+            }
+
+            if let Some(user) = user.as_ref().filter(|user| user.team_id == team.id) {
+                events.push(refresh("challenges"));
+
+                if user.id != solver.id {
+                    events.push(toast(
+                        state,
+                        context! {
+                            lang,
+                            name => solver.name,
+                            challenge => challenge.name,
+                        },
+                    ));
+                }
+            }
+
+            events
+        }
+        RhombusEvent::FirstBlood {
+            team, challenge, ..
+        } => {
+            let Some(user) = user else {
+                return vec![];
+            };
+
+            // first bloods would give away frozen standings
+            if standings_frozen_at(&state, Some(user)).await.is_some() {
+                return vec![];
+            }
+
+            // nor may they name a challenge the user cannot see
+            let (challenge_data, own_team) = tokio::join!(
+                state.db.get_challenges(),
+                state.db.get_team_from_id(user.team_id)
+            );
+            let (Ok(challenge_data), Ok(own_team)) = (challenge_data, own_team) else {
+                return vec![];
+            };
+            let visible = challenges_visible(&state, user).await;
+            if !challenge_listed(visible, user, &own_team, &challenge_data, &challenge) {
+                return vec![];
+            }
+
+            vec![toast(
+                state,
+                context! {
+                    lang,
+                    first_blood => true,
+                    team => team.name,
+                    challenge => challenge.name,
+                },
+            )]
+        }
+        RhombusEvent::HealthChanged { .. } if user.is_some() => {
+            vec![refresh("challenges")]
+        }
+        RhombusEvent::AnnouncementCreated { .. }
+        | RhombusEvent::AnnouncementUpdated { .. }
+        | RhombusEvent::AnnouncementDeleted { .. } => vec![Event::default()
+            .event("banner")
+            .data(render_banner(&state).await)],
+        _ => vec![],
+    }
+}
+
+/// Tells whatever listens for `name` to refetch. The data is only there
+/// because `EventSource` drops events without any.
+fn refresh(name: &str) -> Event {
+    Event::default().event(name).data("refresh")
+}
+
+/// A success toast from `live-toast.html`, swapped out of band into
+/// `#htmx-toaster`
+fn toast(state: RouterState, context: minijinja::Value) -> Event {
+    Event::default().event("toast").data(
+        state
+            .jinja
+            .get_template("live-toast.html")
+            .unwrap()
+            .render(context)
+            .unwrap(),
+    )
+}
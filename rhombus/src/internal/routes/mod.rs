@@ -1,6 +1,10 @@
 pub mod account;
+pub mod admin;
+pub mod announcements;
+pub mod api;
 pub mod challenges;
 pub mod home;
+pub mod live;
 pub mod public;
 pub mod scoreboard;
 pub mod team;
@@ -2,13 +2,18 @@ use std::collections::BTreeMap;
 
 use axum::{
     extract::{Path, State},
-    http::Uri,
+    http::{StatusCode, Uri},
     response::{Html, IntoResponse},
     Extension,
 };
 use minijinja::context;
 
-use crate::internal::{auth::MaybeUser, locales::Languages, router::RouterState};
+use crate::internal::{
+    auth::MaybeUser,
+    locales::Languages,
+    router::RouterState,
+    routes::scoreboard::{freeze_challenges, standings_frozen_at},
+};
 
 pub async fn route_public_user(
     state: State<RouterState>,
@@ -21,8 +26,19 @@ pub async fn route_public_user(
     let challenge_data = state.db.get_challenges();
     let team = state.db.get_team_from_id(public_user.team_id);
     let (challenge_data, team) = tokio::join!(challenge_data, team);
-    let challenge_data = challenge_data.unwrap();
-    let team = team.unwrap();
+    let frozen_at = standings_frozen_at(&state, user.as_ref()).await;
+    let challenge_data = match freeze_challenges(state.db, challenge_data.unwrap(), frozen_at).await
+    {
+        Ok(challenge_data) => challenge_data,
+        Err(error) => {
+            tracing::error!(?error, "Failed to freeze challenges");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    let mut team = (*team.unwrap()).clone();
+    if let Some(frozen_at) = frozen_at {
+        team.solves.retain(|_, solve| solve.solved_at <= frozen_at);
+    }
 
     let mut challenges = BTreeMap::new();
     for challenge in &challenge_data.challenges {
@@ -54,6 +70,7 @@ pub async fn route_public_user(
             })
             .unwrap(),
     )
+    .into_response()
 }
 
 pub async fn route_public_team(
@@ -63,12 +80,23 @@ pub async fn route_public_team(
     team_id: Path<i64>,
     uri: Uri,
 ) -> impl IntoResponse {
+    let frozen_at = standings_frozen_at(&state, user.as_ref()).await;
     let challenge_data = state.db.get_challenges();
     let team = state.db.get_team_from_id(team_id.0);
-    let standings = state.db.get_team_standings(team_id.0);
+    let standings = state.db.get_team_standings(team_id.0, frozen_at);
     let (challenge_data, team, standings) = tokio::join!(challenge_data, team, standings);
-    let challenge_data = challenge_data.unwrap();
-    let team = team.unwrap();
+    let challenge_data = match freeze_challenges(state.db, challenge_data.unwrap(), frozen_at).await
+    {
+        Ok(challenge_data) => challenge_data,
+        Err(error) => {
+            tracing::error!(?error, "Failed to freeze challenges");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    let mut team = (*team.unwrap()).clone();
+    if let Some(frozen_at) = frozen_at {
+        team.solves.retain(|_, solve| solve.solved_at <= frozen_at);
+    }
     let standings = standings.unwrap();
 
     let mut challenges = BTreeMap::new();
@@ -102,4 +130,5 @@ pub async fn route_public_team(
             })
             .unwrap(),
     )
+    .into_response()
 }
@@ -1,14 +1,66 @@
+use std::sync::Arc;
+
 use axum::{
     body::Body,
     extract::{Path, Query, Request, State},
     response::{Html, IntoResponse, Redirect},
     Extension, Json,
 };
+use chrono::{DateTime, Utc};
 use minijinja::context;
 use serde::Deserialize;
 use serde_json::json;
 
-use crate::internal::{auth::MaybeUser, locales::Languages, router::RouterState};
+use crate::{
+    internal::{
+        auth::{MaybeUser, User},
+        database::provider::{Challenges, Connection},
+        locales::Languages,
+        router::RouterState,
+    },
+    Result,
+};
+
+/// Public standings stop updating once `freeze_time` has passed, while admins
+/// keep seeing live ones
+pub async fn standings_frozen_at(
+    state: &RouterState,
+    user: Option<&User>,
+) -> Option<DateTime<Utc>> {
+    if user.is_some_and(|user| user.is_admin) {
+        return None;
+    }
+    state.settings.read().await.frozen_at()
+}
+
+/// `challenge_data` with the points and solves of every division as they were
+/// at `frozen_at`, so challenges do not give away the frozen standings
+pub async fn freeze_challenges(
+    db: Connection,
+    challenge_data: Challenges,
+    frozen_at: Option<DateTime<Utc>>,
+) -> Result<Challenges> {
+    let Some(frozen_at) = frozen_at else {
+        return Ok(challenge_data);
+    };
+
+    let solve_counts = db.get_division_solve_counts(frozen_at).await?;
+
+    let mut frozen = (*challenge_data).clone();
+    for challenge in &mut frozen.challenges {
+        let mut division_points = std::mem::take(&mut challenge.division_points);
+        for division_points in &mut division_points {
+            division_points.solves = solve_counts
+                .get(&(challenge.id, division_points.division_id))
+                .copied()
+                .unwrap_or_default();
+            division_points.points = challenge.points(division_points.solves);
+        }
+        challenge.division_points = division_points;
+    }
+
+    Ok(Arc::new(frozen))
+}
 
 pub async fn route_scoreboard(
     state: State<RouterState>,
@@ -43,9 +95,11 @@ pub async fn route_scoreboard_division(
 ) -> impl IntoResponse {
     let page = params.page.unwrap_or(1).saturating_sub(1);
 
-    let scoreboard = state.db.get_scoreboard(division_id);
+    let frozen_at = standings_frozen_at(&state, user.as_ref()).await;
+
+    let scoreboard = state.db.get_scoreboard(division_id, frozen_at);
     let challenge_data = state.db.get_challenges();
-    let leaderboard = state.db.get_leaderboard(division_id, Some(page));
+    let leaderboard = state.db.get_leaderboard(division_id, Some(page), frozen_at);
     let (scoreboard, challenge_data, leaderboard) =
         futures::future::try_join3(scoreboard, challenge_data, leaderboard)
             .await
@@ -74,6 +128,7 @@ pub async fn route_scoreboard_division(
                 leaderboard,
                 selected_division_id => division_id,
                 page,
+                frozen_at,
             })
             .unwrap(),
     )
@@ -86,7 +141,12 @@ pub async fn route_scoreboard_division_ctftime(
     Path(division_id): Path<i64>,
 ) -> impl IntoResponse {
     let challenge_data = state.db.get_challenges().await.unwrap();
-    let leaderboard = state.db.get_leaderboard(division_id, None).await.unwrap();
+    let frozen_at = state.settings.read().await.frozen_at();
+    let leaderboard = state
+        .db
+        .get_leaderboard(division_id, None, frozen_at)
+        .await
+        .unwrap();
 
     let tasks = challenge_data
         .challenges
@@ -16,7 +16,11 @@ use serde::{Deserialize, Serialize};
 use unicode_segmentation::UnicodeSegmentation;
 
 use crate::internal::{
-    auth::User, division::MaxDivisionPlayers, locales::Languages, router::RouterState,
+    auth::User,
+    division::MaxDivisionPlayers,
+    locales::Languages,
+    router::RouterState,
+    routes::scoreboard::{freeze_challenges, standings_frozen_at},
 };
 
 pub fn create_team_invite_token() -> String {
@@ -40,13 +44,21 @@ pub async fn route_team(
     Extension(lang): Extension<Languages>,
     uri: Uri,
 ) -> impl IntoResponse {
+    let frozen_at = standings_frozen_at(&state, Some(&user)).await;
     let challenge_data = state.db.get_challenges();
     let team = state.db.get_team_from_id(user.team_id);
     let team_divisions = state.db.get_team_divisions(user.team_id);
-    let standings = state.db.get_team_standings(user.team_id);
+    let standings = state.db.get_team_standings(user.team_id, frozen_at);
     let (challenge_data, team, team_divisions, standings) =
         tokio::join!(challenge_data, team, team_divisions, standings);
-    let challenge_data = challenge_data.unwrap();
+    let challenge_data = match freeze_challenges(state.db, challenge_data.unwrap(), frozen_at).await
+    {
+        Ok(challenge_data) => challenge_data,
+        Err(error) => {
+            tracing::error!(?error, "Failed to freeze challenges");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
     let team = team.unwrap();
     let team_divisions = team_divisions.unwrap();
     let standings = standings.unwrap();
@@ -133,6 +145,7 @@ pub async fn route_team(
             })
             .unwrap(),
     )
+    .into_response()
 }
 
 pub async fn route_team_roll_token(
@@ -233,6 +246,9 @@ pub async fn route_user_kick(
 ) -> impl IntoResponse {
     if user_id.0 == user.id && !user.is_team_owner {
         state.db.kick_user(user.id, user.team_id).await.unwrap();
+        if let Some(bot) = state.bot {
+            bot.sync_user_division_roles(state.divisions, user.id);
+        }
         return Response::builder()
             .header("Content-Type", "text/html")
             .header("HX-Trigger", "pageRefresh")
@@ -258,6 +274,9 @@ pub async fn route_user_kick(
     }
 
     state.db.kick_user(user_id.0, user.team_id).await.unwrap();
+    if let Some(bot) = state.bot {
+        bot.sync_user_division_roles(state.divisions, user_id.0);
+    }
 
     Response::builder()
         .header("Content-Type", "text/html")
@@ -331,8 +350,16 @@ pub async fn route_team_set_division(
         .set_team_division(user.team_id, division_id, eligible && form.join.is_some())
         .await
         .unwrap();
+    if let Some(bot) = state.bot {
+        bot.sync_team_division_roles(state.divisions, user.team_id);
+    }
 
-    let standings = state.db.get_team_standings(user.team_id).await.unwrap();
+    let frozen_at = standings_frozen_at(&state, Some(&user)).await;
+    let standings = state
+        .db
+        .get_team_standings(user.team_id, frozen_at)
+        .await
+        .unwrap();
 
     tracing::trace!(
         user_id = user.id,
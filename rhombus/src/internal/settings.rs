@@ -1,7 +1,10 @@
 use std::{num::NonZeroU64, sync::Arc};
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::internal::markdown::render_markdown;
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct DiscordSettings {
     pub client_id: NonZeroU64,
@@ -10,6 +13,8 @@ pub struct DiscordSettings {
     pub bot_token: String,
     pub guild_id: NonZeroU64,
     pub first_blood_channel_id: Option<NonZeroU64>,
+    pub release_channel_id: Option<NonZeroU64>,
+    pub announcement_channel_id: Option<NonZeroU64>,
     pub support_channel_id: Option<NonZeroU64>,
     pub author_role_id: Option<NonZeroU64>,
     pub verified_role_id: Option<NonZeroU64>,
@@ -31,6 +36,23 @@ pub struct RateLimitSettings {
     pub burst_size: Option<u32>,
 }
 
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct SubmissionRateLimitSettings {
+    /// Wrong flags a team may submit to a single challenge within `window_seconds`
+    /// before it is locked out of that challenge.
+    pub per_challenge_attempts: Option<usize>,
+
+    /// Wrong flags a team may submit across all challenges within `window_seconds`
+    /// before it is locked out of submitting entirely.
+    pub per_team_attempts: Option<usize>,
+
+    /// Length of the window wrong submissions are counted in. Defaults to 60 seconds.
+    pub window_seconds: Option<u64>,
+
+    /// How long a lockout lasts after the limit is hit. Defaults to 300 seconds.
+    pub lockout_seconds: Option<u64>,
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub enum IpPreset {
     #[serde(rename = "rightmost-x-forwarded-for")]
@@ -81,6 +103,58 @@ pub struct UploadProviderSettings {
     pub database: Option<bool>,
 }
 
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ProcessInstanceProviderSettings {
+    pub host: String,
+    pub port_start: u16,
+    pub port_end: u16,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct InstanceProviderSettings {
+    pub process: Option<ProcessInstanceProviderSettings>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookFormat {
+    /// The event as JSON, like `{"event": "solve", "timestamp": ..., "data": {...}}`
+    #[serde(rename = "json")]
+    Json,
+    #[serde(rename = "slack")]
+    Slack,
+    #[serde(rename = "discord")]
+    Discord,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookEventKind {
+    #[serde(rename = "solve")]
+    Solve,
+    #[serde(rename = "first-blood")]
+    FirstBlood,
+    #[serde(rename = "team-created")]
+    TeamCreated,
+    #[serde(rename = "ticket-created")]
+    TicketCreated,
+    #[serde(rename = "health-changed")]
+    HealthChanged,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct WebhookSettings {
+    pub url: String,
+
+    /// Defaults to `json`
+    pub format: Option<WebhookFormat>,
+
+    /// Deliveries are signed with HMAC-SHA256 of the body using this secret, in
+    /// the `X-Rhombus-Signature` header as `sha256=<hex>`
+    pub secret: Option<String>,
+
+    /// Events to deliver. Defaults to every event.
+    pub events: Option<Vec<WebhookEventKind>>,
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct MailgunSettings {
     pub api_key: String,
@@ -116,6 +190,9 @@ pub struct Division {
     pub requirement: Option<String>,
     pub email_regex: Option<String>,
     pub max_players: Option<String>,
+
+    /// Discord role granted to members of teams in this division
+    pub discord_role_id: Option<NonZeroU64>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
@@ -132,7 +209,21 @@ pub enum AuthProvider {
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct HomeSettings {
+    /// Markdown shown on the home page
     pub content: Option<String>,
+
+    /// `content` rendered to HTML on startup
+    #[serde(skip)]
+    pub content_html: Option<String>,
+}
+
+impl HomeSettings {
+    pub fn render(&mut self) {
+        self.content_html = self
+            .content
+            .as_deref()
+            .map(|content| render_markdown(content, &[]));
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -145,6 +236,7 @@ pub struct Settings {
     pub database_url: Option<String>,
     pub discord: Option<DiscordSettings>,
     pub ratelimit: Option<RateLimitSettings>,
+    pub submission_ratelimit: Option<SubmissionRateLimitSettings>,
     pub ip_preset: Option<IpPreset>,
     pub live_reload: bool,
     pub default_ticket_template: String,
@@ -153,8 +245,20 @@ pub struct Settings {
     pub divisions: Option<Vec<Division>>,
     pub email: Option<EmailSettings>,
     pub uploads: Option<UploadProviderSettings>,
+    pub instances: Option<InstanceProviderSettings>,
+    pub webhooks: Option<Vec<WebhookSettings>>,
     pub auth: Vec<AuthProvider>,
 
+    /// Challenges are hidden from everyone but admins until this time.
+    pub start_time: Option<DateTime<Utc>>,
+
+    /// Flags can no longer be submitted after this time.
+    pub end_time: Option<DateTime<Utc>>,
+
+    /// The public scoreboard and leaderboard stop updating at this time, and
+    /// stay frozen until it is removed. Admins always see live standings.
+    pub freeze_time: Option<DateTime<Utc>>,
+
     /// `false` will disable the in memory cache.
     ///
     /// `true` will enable the in memory cache with default 360 second interval.
@@ -168,6 +272,23 @@ pub struct Settings {
     pub turso: Option<Turso>,
 }
 
+impl Settings {
+    pub fn has_started(&self) -> bool {
+        self.start_time
+            .map_or(true, |start_time| start_time <= Utc::now())
+    }
+
+    pub fn has_ended(&self) -> bool {
+        self.end_time.is_some_and(|end_time| end_time <= Utc::now())
+    }
+
+    /// The time public standings are frozen at, once the freeze has begun
+    pub fn frozen_at(&self) -> Option<DateTime<Utc>> {
+        self.freeze_time
+            .filter(|freeze_time| *freeze_time <= Utc::now())
+    }
+}
+
 pub enum DbConfig {
     #[cfg(feature = "postgres")]
     RawPostgres(sqlx::PgPool),
@@ -0,0 +1,435 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use ring::hmac;
+use serde::Serialize;
+use serde_json::json;
+use tokio::sync::{broadcast::error::RecvError, RwLock};
+
+use crate::internal::{
+    auth::User,
+    database::provider::{Category, Challenge, Connection, Team},
+    events::{EventBus, RhombusEvent},
+    local_upload_provider::slice_to_hex_string,
+    settings::{Settings, WebhookEventKind, WebhookFormat, WebhookSettings},
+};
+
+const MAX_ATTEMPTS: u32 = 5;
+
+#[derive(Debug, Serialize, Clone)]
+pub struct WebhookUser {
+    pub id: i64,
+    pub name: String,
+}
+
+impl From<&User> for WebhookUser {
+    fn from(user: &User) -> Self {
+        WebhookUser {
+            id: user.id,
+            name: user.name.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct WebhookTeam {
+    pub id: i64,
+    pub name: String,
+}
+
+impl From<&Team> for WebhookTeam {
+    fn from(team: &Team) -> Self {
+        WebhookTeam {
+            id: team.id,
+            name: team.name.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct WebhookChallenge {
+    pub id: i64,
+    pub name: String,
+    pub category: String,
+}
+
+impl WebhookChallenge {
+    pub fn new(challenge: &Challenge, categories: &[Category]) -> Self {
+        WebhookChallenge {
+            id: challenge.id,
+            name: challenge.name.clone(),
+            category: categories
+                .iter()
+                .find(|category| category.id == challenge.category_id)
+                .map(|category| category.name.clone())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(tag = "event", content = "data", rename_all = "kebab-case")]
+pub enum WebhookEvent {
+    Solve {
+        user: WebhookUser,
+        team: WebhookTeam,
+        challenge: WebhookChallenge,
+    },
+    FirstBlood {
+        user: WebhookUser,
+        team: WebhookTeam,
+        challenge: WebhookChallenge,
+        divisions: Vec<String>,
+    },
+    TeamCreated {
+        user: WebhookUser,
+        team: WebhookTeam,
+    },
+    TicketCreated {
+        ticket_number: u64,
+        user: WebhookUser,
+        team: WebhookTeam,
+        challenge: WebhookChallenge,
+    },
+    HealthChanged {
+        challenge: WebhookChallenge,
+        healthy: Option<bool>,
+    },
+}
+
+impl WebhookEvent {
+    pub fn kind(&self) -> WebhookEventKind {
+        match self {
+            WebhookEvent::Solve { .. } => WebhookEventKind::Solve,
+            WebhookEvent::FirstBlood { .. } => WebhookEventKind::FirstBlood,
+            WebhookEvent::TeamCreated { .. } => WebhookEventKind::TeamCreated,
+            WebhookEvent::TicketCreated { .. } => WebhookEventKind::TicketCreated,
+            WebhookEvent::HealthChanged { .. } => WebhookEventKind::HealthChanged,
+        }
+    }
+
+    /// One line summary for chat services
+    pub fn text(&self) -> String {
+        match self {
+            WebhookEvent::Solve {
+                user,
+                team,
+                challenge,
+            } => format!(
+                "{} on team {} solved {} / {}",
+                user.name, team.name, challenge.category, challenge.name
+            ),
+            WebhookEvent::FirstBlood {
+                user,
+                team,
+                challenge,
+                divisions,
+            } => format!(
+                "{} on team {} got first blood on {} / {} in {}",
+                user.name,
+                team.name,
+                challenge.category,
+                challenge.name,
+                divisions.join(", ")
+            ),
+            WebhookEvent::TeamCreated { user, team } => {
+                format!("{} created team {}", user.name, team.name)
+            }
+            WebhookEvent::TicketCreated {
+                ticket_number,
+                user,
+                team,
+                challenge,
+            } => format!(
+                "{} on team {} opened ticket #{} for {} / {}",
+                user.name, team.name, ticket_number, challenge.category, challenge.name
+            ),
+            WebhookEvent::HealthChanged { challenge, healthy } => format!(
+                "{} / {} is now {}",
+                challenge.category,
+                challenge.name,
+                match healthy {
+                    Some(true) => "healthy",
+                    Some(false) => "unhealthy",
+                    None => "of unknown health",
+                }
+            ),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct WebhookPayload<'a> {
+    timestamp: DateTime<Utc>,
+    #[serde(flatten)]
+    event: &'a WebhookEvent,
+}
+
+/// The body delivered to a webhook in its format
+fn body(format: WebhookFormat, event: &WebhookEvent, timestamp: DateTime<Utc>) -> String {
+    match format {
+        WebhookFormat::Json => serde_json::to_string(&WebhookPayload { timestamp, event }).unwrap(),
+        WebhookFormat::Slack => json!({ "text": event.text() }).to_string(),
+        WebhookFormat::Discord => json!({
+            "content": event.text(),
+            "allowed_mentions": { "parse": [] },
+        })
+        .to_string(),
+    }
+}
+
+fn signature(secret: &str, body: &str) -> String {
+    let tag = hmac::sign(
+        &hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes()),
+        body.as_bytes(),
+    );
+    format!("sha256={}", slice_to_hex_string(tag.as_ref()))
+}
+
+/// Delivers events from the [EventBus] to the webhooks in the settings.
+/// Deliveries happen in the background and are retried with exponential
+/// backoff on network errors, server errors and rate limits.
+pub struct Webhooks {
+    settings: &'static RwLock<Settings>,
+    db: Connection,
+    client: reqwest::Client,
+}
+
+impl Webhooks {
+    pub fn new(settings: &'static RwLock<Settings>, db: Connection) -> Webhooks {
+        Webhooks {
+            settings,
+            db,
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .unwrap(),
+        }
+    }
+
+    pub fn listen(&'static self, events: &EventBus) {
+        let mut receiver = events.subscribe();
+        tokio::task::spawn(async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => {
+                        if let Some(event) = self.webhook_event(event).await {
+                            self.send(event).await;
+                        }
+                    }
+                    Err(RecvError::Lagged(skipped)) => {
+                        tracing::warn!(skipped, "Webhooks fell behind and skipped events");
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+
+    async fn webhook_event(&self, event: RhombusEvent) -> Option<WebhookEvent> {
+        let challenge_data = match self.db.get_challenges().await {
+            Ok(challenge_data) => challenge_data,
+            Err(error) => {
+                tracing::error!(?error, "Failed to get challenges for webhook");
+                return None;
+            }
+        };
+        let categories = &challenge_data.categories;
+
+        Some(match event {
+            RhombusEvent::Solve {
+                user,
+                team,
+                challenge,
+            } => WebhookEvent::Solve {
+                user: (&user).into(),
+                team: (&team).into(),
+                challenge: WebhookChallenge::new(&challenge, categories),
+            },
+            RhombusEvent::FirstBlood {
+                user,
+                team,
+                challenge,
+                division_ids,
+            } => WebhookEvent::FirstBlood {
+                user: (&user).into(),
+                team: (&team).into(),
+                challenge: WebhookChallenge::new(&challenge, categories),
+                divisions: division_ids
+                    .iter()
+                    .filter_map(|division_id| challenge_data.divisions.get(division_id))
+                    .map(|division| division.name.clone())
+                    .collect(),
+            },
+            RhombusEvent::TeamCreated { user, team } => WebhookEvent::TeamCreated {
+                user: (&user).into(),
+                team: (&team).into(),
+            },
+            RhombusEvent::TeamJoined { .. }
+            | RhombusEvent::AnnouncementCreated { .. }
+            | RhombusEvent::AnnouncementUpdated { .. }
+            | RhombusEvent::AnnouncementDeleted { .. } => return None,
+            RhombusEvent::TicketOpened {
+                ticket_number,
+                user,
+                team,
+                challenge,
+            } => WebhookEvent::TicketCreated {
+                ticket_number,
+                user: (&user).into(),
+                team: (&team).into(),
+                challenge: WebhookChallenge::new(&challenge, categories),
+            },
+            RhombusEvent::HealthChanged { challenge, healthy } => WebhookEvent::HealthChanged {
+                challenge: WebhookChallenge::new(&challenge, categories),
+                healthy,
+            },
+        })
+    }
+
+    async fn send(&'static self, event: WebhookEvent) {
+        let webhooks = {
+            let settings = self.settings.read().await;
+            settings
+                .webhooks
+                .iter()
+                .flatten()
+                .filter(|webhook| {
+                    webhook
+                        .events
+                        .as_ref()
+                        .map_or(true, |events| events.contains(&event.kind()))
+                })
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+
+        let timestamp = Utc::now();
+        for webhook in webhooks {
+            let body = body(
+                webhook.format.unwrap_or(WebhookFormat::Json),
+                &event,
+                timestamp,
+            );
+            tokio::task::spawn(self.deliver(webhook, event.kind(), body));
+        }
+    }
+
+    async fn deliver(&self, webhook: WebhookSettings, kind: WebhookEventKind, body: String) {
+        let kind = serde_json::to_value(kind).unwrap();
+        let kind = kind.as_str().unwrap();
+
+        for attempt in 1..=MAX_ATTEMPTS {
+            let mut request = self
+                .client
+                .post(&webhook.url)
+                .header("Content-Type", "application/json")
+                .header("X-Rhombus-Event", kind)
+                .body(body.clone());
+            if let Some(secret) = &webhook.secret {
+                request = request.header("X-Rhombus-Signature", signature(secret, &body));
+            }
+
+            let retry = match request.send().await {
+                Ok(response) if response.status().is_success() => {
+                    tracing::trace!(url = webhook.url, event = kind, "Delivered webhook");
+                    return;
+                }
+                Ok(response) => {
+                    let status = response.status();
+                    tracing::warn!(
+                        url = webhook.url,
+                        event = kind,
+                        attempt,
+                        status = status.as_u16(),
+                        "Webhook delivery failed"
+                    );
+                    status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+                }
+                Err(error) => {
+                    tracing::warn!(
+                        url = webhook.url,
+                        event = kind,
+                        attempt,
+                        ?error,
+                        "Webhook delivery failed"
+                    );
+                    true
+                }
+            };
+
+            if !retry {
+                break;
+            }
+            if attempt < MAX_ATTEMPTS {
+                tokio::time::sleep(Duration::from_secs(1 << (attempt - 1))).await;
+            }
+        }
+
+        tracing::error!(
+            url = webhook.url,
+            event = kind,
+            "Gave up delivering webhook"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::{body, signature, WebhookChallenge, WebhookEvent, WebhookTeam, WebhookUser};
+    use crate::internal::settings::WebhookFormat;
+
+    fn first_blood() -> WebhookEvent {
+        WebhookEvent::FirstBlood {
+            user: WebhookUser {
+                id: 1,
+                name: "mbund".to_owned(),
+            },
+            team: WebhookTeam {
+                id: 2,
+                name: "@everyone".to_owned(),
+            },
+            challenge: WebhookChallenge {
+                id: 3,
+                name: "babyrev".to_owned(),
+                category: "rev".to_owned(),
+            },
+            divisions: vec!["Open".to_owned(), "OSU".to_owned()],
+        }
+    }
+
+    #[test]
+    fn formats() {
+        let timestamp = Utc.timestamp_opt(0, 0).unwrap();
+
+        let json: serde_json::Value =
+            serde_json::from_str(&body(WebhookFormat::Json, &first_blood(), timestamp)).unwrap();
+        assert_eq!(json["event"], "first-blood");
+        assert_eq!(json["timestamp"], "1970-01-01T00:00:00Z");
+        assert_eq!(json["data"]["challenge"]["name"], "babyrev");
+        assert_eq!(json["data"]["divisions"][1], "OSU");
+
+        let slack: serde_json::Value =
+            serde_json::from_str(&body(WebhookFormat::Slack, &first_blood(), timestamp)).unwrap();
+        assert_eq!(
+            slack["text"],
+            "mbund on team @everyone got first blood on rev / babyrev in Open, OSU"
+        );
+
+        let discord: serde_json::Value =
+            serde_json::from_str(&body(WebhookFormat::Discord, &first_blood(), timestamp)).unwrap();
+        assert_eq!(discord["content"], slack["text"]);
+        assert_eq!(discord["allowed_mentions"]["parse"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn signed() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            signature("Jefe", "what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+}
@@ -22,8 +22,10 @@ pub mod builder;
 
 pub mod challenge_loader_plugin;
 pub mod database_upload_provider;
+mod instance_provider;
 mod local_upload_provider;
 pub mod plugin;
+pub mod process_instance_provider;
 pub mod s3_upload_provider;
 mod upload_provider;
 
@@ -49,6 +51,9 @@ pub use local_upload_provider::LocalUploadProvider;
 #[doc(inline)]
 pub use upload_provider::UploadProvider;
 
+#[doc(inline)]
+pub use instance_provider::{InstanceProvider, InstanceProviderRef};
+
 #[cfg(feature = "systemfd")]
 pub use systemfd::serve_systemfd;
 
@@ -59,3 +64,8 @@ pub mod ip {
         maybe_rightmost_x_forwarded_for, maybe_true_client_ip, maybe_x_real_ip,
     };
 }
+
+/// Events plugins can subscribe to through [RunContext](plugin::RunContext)
+pub mod events {
+    pub use crate::internal::events::{EventBus, RhombusEvent};
+}
@@ -6,11 +6,11 @@ use tokio::sync::RwLock;
 use crate::{
     builder::RawDb,
     internal::{
-        database::provider::Connection, division::Division, locales::Localizations,
-        router::RouterState, settings::Settings, templates::Templates,
+        database::provider::Connection, division::Division, events::EventBus,
+        locales::Localizations, router::RouterState, settings::Settings, templates::Templates,
     },
     upload_provider::EitherUploadProvider,
-    Result, UploadProvider,
+    InstanceProviderRef, Result, UploadProvider,
 };
 
 pub struct RunContext<'a, U: UploadProvider> {
@@ -38,6 +38,10 @@ pub struct RunContext<'a, U: UploadProvider> {
 
     /// Divisions and their eligibility functions.
     pub divisions: &'a mut Vec<Division>,
+
+    /// Events like solves and first bloods. Call [subscribe](EventBus::subscribe)
+    /// and spawn a task to react to them.
+    pub events: &'static EventBus,
 }
 
 pub struct UploadProviderContext<'a> {
@@ -48,6 +52,14 @@ pub struct UploadProviderContext<'a> {
     pub db: Connection,
 }
 
+pub struct InstanceProviderContext<'a> {
+    /// Base settings for the application.
+    pub settings: &'a Settings,
+
+    /// High level database connection used by Rhombus core.
+    pub db: Connection,
+}
+
 pub struct DatabaseProviderContext<'a> {
     /// Base settings for the application.
     pub settings: &'a mut Settings,
@@ -62,6 +74,8 @@ pub struct DatabaseProviderContext<'a> {
 /// 1. The [upload_provider](Plugin::upload_provider) function is called for each plugin in reverse
 /// order of plugins defined until the first plugin is found which implements a custom upload provider, which is then used.
 /// If no plugin implements a custom upload provider, the default upload provider creation process will happen.
+/// 1. The [instance_provider](Plugin::instance_provider) function is called the same way. If no plugin implements
+/// a custom instance provider, the one configured in settings is used, if any.
 /// 1. The [run](Plugin::run) function is called for each plugin in the order they are defined.
 #[allow(async_fn_in_trait)]
 pub trait Plugin {
@@ -97,6 +111,15 @@ pub trait Plugin {
         None
     }
 
+    /// Supply a custom [InstanceProvider](crate::InstanceProvider) to run per team
+    /// instances of challenges with, such as one backed by Docker or Kubernetes.
+    async fn instance_provider(
+        &self,
+        _context: &InstanceProviderContext<'_>,
+    ) -> Option<InstanceProviderRef> {
+        None
+    }
+
     async fn run<U: UploadProvider>(
         &self,
         context: &mut RunContext<'_, U>,
@@ -125,6 +148,13 @@ impl<P: Plugin> Plugin for (P,) {
         self.0.database_provider(context).await
     }
 
+    async fn instance_provider(
+        &self,
+        context: &InstanceProviderContext<'_>,
+    ) -> Option<InstanceProviderRef> {
+        self.0.instance_provider(context).await
+    }
+
     async fn run<U: UploadProvider>(
         &self,
         context: &mut RunContext<'_, U>,
@@ -163,6 +193,16 @@ where
         }
     }
 
+    async fn instance_provider(
+        &self,
+        context: &InstanceProviderContext<'_>,
+    ) -> Option<InstanceProviderRef> {
+        match self.0.instance_provider(context).await {
+            Some(i) => Some(i),
+            None => self.1.instance_provider(context).await,
+        }
+    }
+
     async fn run<U: UploadProvider>(
         &self,
         context: &mut RunContext<'_, U>,
@@ -0,0 +1,107 @@
+use std::{
+    collections::BTreeSet, net::TcpListener, ops::RangeInclusive, process::Stdio, sync::Mutex,
+};
+
+use async_trait::async_trait;
+use dashmap::DashMap;
+use tokio::process::{Child, Command};
+
+use crate::{
+    errors::RhombusError,
+    instance_provider::InstanceProvider,
+    internal::{database::provider::Challenge, settings::ProcessInstanceProviderSettings},
+    Result,
+};
+
+/// Runs each instance as a local process, which is handy for testing
+/// instanced challenges without Docker. The challenge's instance spec is run
+/// with `sh -c`, with the port to listen on in the `PORT` environment
+/// variable and the team's flag in `FLAG`.
+pub struct ProcessInstanceProvider {
+    pub host: String,
+    pub ports: RangeInclusive<u16>,
+    children: DashMap<(i64, i64), (u16, Child)>,
+    reserved_ports: Mutex<BTreeSet<u16>>,
+}
+
+impl ProcessInstanceProvider {
+    pub fn new(settings: &ProcessInstanceProviderSettings) -> ProcessInstanceProvider {
+        ProcessInstanceProvider {
+            host: settings.host.clone(),
+            ports: settings.port_start..=settings.port_end,
+            children: DashMap::new(),
+            reserved_ports: Mutex::new(BTreeSet::new()),
+        }
+    }
+
+    /// Reserve a port in the range which no instance is using, and which
+    /// nothing else on the machine is listening on. The port stays reserved
+    /// until it is released, so instances starting at the same time never
+    /// get the same port before their processes bind it.
+    fn reserve_port(&self) -> Option<u16> {
+        let mut reserved_ports = self.reserved_ports.lock().unwrap();
+        let port = self.ports.clone().find(|port| {
+            !reserved_ports.contains(port) && TcpListener::bind(("0.0.0.0", *port)).is_ok()
+        })?;
+        reserved_ports.insert(port);
+        Some(port)
+    }
+
+    fn release_port(&self, port: u16) {
+        self.reserved_ports.lock().unwrap().remove(&port);
+    }
+}
+
+#[async_trait]
+impl InstanceProvider for ProcessInstanceProvider {
+    async fn start(&self, challenge: &Challenge, team_id: i64, flag: &str) -> Result<String> {
+        let Some(instance) = &challenge.instance else {
+            return Err(RhombusError::Instance(format!(
+                "challenge {} is not instanced",
+                challenge.id
+            )));
+        };
+
+        let Some(port) = self.reserve_port() else {
+            return Err(RhombusError::Instance("no free ports left".to_owned()));
+        };
+
+        let child = match Command::new("sh")
+            .arg("-c")
+            .arg(&instance.spec)
+            .env("PORT", port.to_string())
+            .env("FLAG", flag)
+            .stdin(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(error) => {
+                self.release_port(port);
+                return Err(error.into());
+            }
+        };
+
+        tracing::info!(
+            challenge_id = challenge.id,
+            team_id,
+            port,
+            "Started instance process"
+        );
+
+        self.children.insert((challenge.id, team_id), (port, child));
+
+        Ok(format!("{}:{}", self.host, port))
+    }
+
+    async fn stop(&self, challenge_id: i64, team_id: i64) -> Result<()> {
+        if let Some((_, (port, mut child))) = self.children.remove(&(challenge_id, team_id)) {
+            let killed = child.kill().await;
+            self.release_port(port);
+            killed?;
+            tracing::info!(challenge_id, team_id, port, "Stopped instance process");
+        }
+
+        Ok(())
+    }
+}